@@ -6,6 +6,7 @@ mod event;
 mod files;
 mod http;
 mod isolated_buffering;
+mod json;
 mod lookup;
 mod lua;
 mod metrics_snapshot;
@@ -20,6 +21,7 @@ criterion_main!(
     files::benches,
     http::benches,
     isolated_buffering::benches,
+    json::benches,
     lookup::benches,
     lua::benches,
     metrics_snapshot::benches,
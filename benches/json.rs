@@ -0,0 +1,38 @@
+use criterion::{criterion_group, Criterion, Throughput};
+use serde_json::json;
+use vector::sources::http::parse_json;
+
+// Compare runs with and without the `sources-http-simd-json` feature to see
+// what simd-json changes for the http source.
+fn benchmark_json(c: &mut Criterion) {
+    let objects: Vec<Vec<u8>> = (0..1_000)
+        .map(|i| {
+            serde_json::to_vec(&json!({
+                "id": i,
+                "message": "GET /api/v1/events HTTP/1.1",
+                "status": 200,
+                "duration": 0.25,
+                "tags": ["http", "api", "production"],
+                "request": {"host": "example.com", "user_agent": "curl/7.64.1"},
+            }))
+            .unwrap()
+        })
+        .collect();
+
+    let mut group = c.benchmark_group("json");
+    group.throughput(Throughput::Bytes(
+        objects.iter().map(|object| object.len() as u64).sum(),
+    ));
+
+    group.bench_function("http_source", |b| {
+        b.iter(|| {
+            for object in &objects {
+                parse_json(object).unwrap();
+            }
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, benchmark_json);
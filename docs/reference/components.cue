@@ -739,6 +739,315 @@ components: {
 				}
 			}
 
+			_http_source_auth: {
+				common:      false
+				description: "Options for authenticating incoming HTTP requests."
+				required:    false
+				warnings: []
+				type: object: {
+					examples: []
+					options: {
+						strategy: {
+							common:      false
+							description: "The authentication strategy to use."
+							required:    false
+							warnings: []
+							type: string: {
+								default: "basic"
+								enum: {
+									basic:  "Requests must use [HTTP Basic Authentication][urls.basic_auth]."
+									jwt:    "Requests must carry a signed JWT bearer token in the `Authorization` header."
+									hmac:   "Requests must carry a hex encoded HMAC signature of the raw request body in `signature_header`."
+									github: "Requests must carry a valid GitHub webhook `X-Hub-Signature-256` header. The `X-GitHub-Event` and `X-GitHub-Delivery` headers are added to each event as `github_event` and `github_delivery`."
+									stripe: "Requests must carry a valid Stripe webhook `Stripe-Signature` header. Failures are rejected with a 400 response, as Stripe expects."
+									api_key: "Requests must carry one of `keys` in the `api_key_header` header or the `api_key_query` query parameter."
+								}
+							}
+						}
+						username: {
+							common:        true
+							description:   "The basic authentication user name."
+							relevant_when: "strategy == \"basic\""
+							required:      false
+							warnings: []
+							type: string: {
+								default: null
+								examples: ["${HTTP_USERNAME}", "username"]
+								syntax: "literal"
+							}
+						}
+						password: {
+							common:        true
+							description:   "The basic authentication password."
+							relevant_when: "strategy == \"basic\""
+							required:      false
+							warnings: []
+							type: string: {
+								default: null
+								examples: ["${HTTP_PASSWORD}", "password"]
+								syntax: "literal"
+							}
+						}
+						credentials: {
+							common:        false
+							description:   "Additional accepted user name and password pairs, allowing separate credentials per client. A request matching any pair is accepted."
+							relevant_when: "strategy == \"basic\""
+							required:      false
+							warnings: []
+							type: array: {
+								default: []
+								items: type: object: {
+									examples: [{username: "team_a", password: "${TEAM_A_PASSWORD}", name: "team-a"}]
+									options: {
+										username: {
+											description: "The basic authentication user name."
+											required:    true
+											warnings: []
+											type: string: {
+												examples: ["team_a"]
+												syntax: "literal"
+											}
+										}
+										password: {
+											description: "The basic authentication password."
+											required:    true
+											warnings: []
+											type: string: {
+												examples: ["${TEAM_A_PASSWORD}"]
+												syntax: "literal"
+											}
+										}
+										name: {
+											common:      false
+											description: "A name for the credential, added to events under `name_key`."
+											required:    false
+											warnings: []
+											type: string: {
+												default: null
+												examples: ["team-a"]
+												syntax: "literal"
+											}
+										}
+									}
+								}
+							}
+						}
+						name_key: {
+							common:        false
+							description:   "The field the matched credential's `name` is inserted into."
+							relevant_when: "strategy == \"basic\""
+							required:      false
+							warnings: []
+							type: string: {
+								default: null
+								examples: ["client"]
+								syntax: "literal"
+							}
+						}
+						secret: {
+							common:        false
+							description:   "The shared secret. With the `jwt` strategy it verifies `HS256` signed tokens, and exactly one of `secret` or `public_key_file` must be set. With the `hmac`, `github`, and `stripe` strategies it is the signing key and is required."
+							relevant_when: "strategy == \"jwt\" or strategy == \"hmac\" or strategy == \"github\" or strategy == \"stripe\""
+							required:      false
+							warnings: []
+							type: string: {
+								default: null
+								examples: ["${JWT_SECRET}"]
+								syntax: "literal"
+							}
+						}
+						public_key_file: {
+							common:        false
+							description:   "The path to a PEM encoded public key used to verify `RS256` signed tokens."
+							relevant_when: "strategy == \"jwt\""
+							required:      false
+							warnings: []
+							type: string: {
+								default: null
+								examples: ["/etc/vector/jwt.pem"]
+								syntax: "literal"
+							}
+						}
+						audience: {
+							common:        false
+							description:   "If set, the token's `aud` claim must contain this value."
+							relevant_when: "strategy == \"jwt\""
+							required:      false
+							warnings: []
+							type: string: {
+								default: null
+								examples: ["vector"]
+								syntax: "literal"
+							}
+						}
+						issuer: {
+							common:        false
+							description:   "If set, the token's `iss` claim must equal this value."
+							relevant_when: "strategy == \"jwt\""
+							required:      false
+							warnings: []
+							type: string: {
+								default: null
+								examples: ["https://auth.example.com/"]
+								syntax: "literal"
+							}
+						}
+						claims: {
+							common:        false
+							description:   "A list of token claims to add to each event as fields."
+							relevant_when: "strategy == \"jwt\""
+							required:      false
+							warnings: []
+							type: array: {
+								default: []
+								items: type: string: {
+									examples: ["sub", "tenant"]
+									syntax: "literal"
+								}
+							}
+						}
+						signature_header: {
+							description:   "The header carrying the request signature."
+							relevant_when: "strategy == \"hmac\""
+							required:      true
+							warnings: []
+							type: string: {
+								examples: ["X-Signature", "X-Hub-Signature-256"]
+								syntax: "literal"
+							}
+						}
+						algorithm: {
+							common:        false
+							description:   "The hash function used to compute the signature."
+							relevant_when: "strategy == \"hmac\""
+							required:      false
+							warnings: []
+							type: string: {
+								default: "sha256"
+								enum: {
+									sha1:   "HMAC-SHA1"
+									sha256: "HMAC-SHA256"
+									sha512: "HMAC-SHA512"
+								}
+							}
+						}
+						prefix: {
+							common:        false
+							description:   "A prefix preceding the hex encoded signature in `signature_header`. Requests whose signature lacks it are rejected."
+							relevant_when: "strategy == \"hmac\""
+							required:      false
+							warnings: []
+							type: string: {
+								default: null
+								examples: ["sha256="]
+								syntax: "literal"
+							}
+						}
+						tolerance_secs: {
+							common:        false
+							description:   "The maximum age of the `Stripe-Signature` timestamp. Older requests are rejected as replays."
+							relevant_when: "strategy == \"stripe\""
+							required:      false
+							warnings: []
+							type: uint: {
+								default: 300
+								unit:    "seconds"
+							}
+						}
+						api_key_header: {
+							common:        false
+							description:   "The header carrying the API key. At least one of `api_key_header` or `api_key_query` must be set."
+							relevant_when: "strategy == \"api_key\""
+							required:      false
+							warnings: []
+							type: string: {
+								default: null
+								examples: ["X-API-Key"]
+								syntax: "literal"
+							}
+						}
+						api_key_prefix: {
+							common:        false
+							description:   "A prefix preceding the API key in `api_key_header`, such as `Splunk ` for Splunk HEC tokens in the `Authorization` header. Requests whose header lacks it are rejected."
+							relevant_when: "strategy == \"api_key\""
+							required:      false
+							warnings: []
+							type: string: {
+								default: null
+								examples: ["Splunk ", "Bearer "]
+								syntax: "literal"
+							}
+						}
+						api_key_query: {
+							common:        false
+							description:   "The query parameter carrying the API key, checked when `api_key_header` is absent from the request."
+							relevant_when: "strategy == \"api_key\""
+							required:      false
+							warnings: []
+							type: string: {
+								default: null
+								examples: ["api_key"]
+								syntax: "literal"
+							}
+						}
+						keys: {
+							description:   "The accepted API keys. Listing several allows keys to be rotated without downtime."
+							relevant_when: "strategy == \"api_key\""
+							required:      true
+							warnings: []
+							type: array: items: type: object: {
+								examples: [{key: "${API_KEY}", alias: "team-a"}]
+								options: {
+									key: {
+										description: "The API key, which can't be empty."
+										required:    true
+										warnings: []
+										type: string: {
+											examples: ["${API_KEY}"]
+											syntax: "literal"
+										}
+									}
+									alias: {
+										common:      false
+										description: "A name for the key, added to events under `alias_key`."
+										required:    false
+										warnings: []
+										type: string: {
+											default: null
+											examples: ["team-a"]
+											syntax: "literal"
+										}
+									}
+								}
+							}
+						}
+						alias_key: {
+							common:        false
+							description:   "The field the matched key's `alias` is inserted into."
+							relevant_when: "strategy == \"api_key\""
+							required:      false
+							warnings: []
+							type: string: {
+								default: null
+								examples: ["client"]
+								syntax: "literal"
+							}
+						}
+						leeway_secs: {
+							common:        false
+							description:   "The number of seconds of clock skew tolerated when checking the `exp` and `nbf` claims."
+							relevant_when: "strategy == \"jwt\""
+							required:      false
+							warnings: []
+							type: uint: {
+								default: 0
+								unit:    "seconds"
+							}
+						}
+					}
+				}
+			}
+
 			_timezone: {
 				common:      false
 				description: "The name of the time zone to apply to timestamp conversions that do not contain an explicit time zone. This overrides the global [`timezone` option][docs.reference.configuration.global-options#timezone]. The time zone name may be any name in the [TZ database][urls.tz_time_zones], or `local` to indicate system local time."
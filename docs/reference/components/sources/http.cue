@@ -61,10 +61,10 @@ components: sources: http: {
 
 	configuration: {
 		address: {
-			description: "The address to accept connections on. The address _must_ include a port."
+			description: "The address to accept connections on. The address _must_ include a port. On Unix, an absolute path listens on a Unix socket instead, which is removed on shutdown. `tls` can't be used with a Unix socket, and as its clients have no address, `allowed_ips` and `denied_ips` reject all of its requests while `rate_limit` doesn't apply."
 			required:    true
 			type: string: {
-				examples: ["0.0.0.0:\(_port)", "localhost:\(_port)"]
+				examples: ["0.0.0.0:\(_port)", "localhost:\(_port)", "/run/vector/http.sock"]
 				syntax: "literal"
 			}
 		}
@@ -75,16 +75,343 @@ components: sources: http: {
 			type: string: {
 				default: "text"
 				enum: {
-					text:   "Newline-delimited text, with each line forming a message."
-					ndjson: "Newline-delimited JSON objects, where each line must contain a JSON object."
-					json:   "Array of JSON objects, which must be a JSON array containing JSON objects."
+					text:                    "Newline-delimited text, with each line forming a message."
+					ndjson:                  "Newline-delimited JSON objects, where each line must contain a JSON object."
+					json:                    "Array of JSON objects, which must be a JSON array containing JSON objects."
+					csv:                     "Comma-separated values, with each row forming an event."
+					avro:                    "An Avro object container file with an embedded schema, with each record forming an event."
+					message_pack:            "A MessagePack map, or an array of MessagePack maps, with each map forming an event."
+					protobuf:                "A protobuf message of the type configured by `proto_message_type`, forming a single event."
+					form:                    "A `application/x-www-form-urlencoded` body, forming a single event. Repeated keys are collected into an array."
+					xml:                     "An XML document, forming a single event. Elements become nested maps, attributes are placed under `@attributes`, text is placed under `#text`, and repeated elements are collected into an array."
+					logfmt:                  "Newline-delimited logfmt, with each line forming an event. Keys without values are set to `true`."
+					gelf:                    "A [GELF](\(urls.gelf)) message, or an array of them, with each message forming an event. The required `short_message` and `host` fields become the message and host, the numeric `timestamp` becomes the timestamp, additional fields have their leading underscore removed, and `version` is dropped."
+					metric:                  "A JSON metric object, or array of them, with `name`, `kind` (`counter` or `gauge`), `value` and optional `tags` and `timestamp` fields, with each forming a metric event. Metric events don't have headers, query parameters or other request fields added."
+					loki:                    "A JSON [Loki push request](\(urls.loki_push)), with each entry of each stream forming an event. The entry's line becomes the message, its nanosecond timestamp the timestamp, and the stream's labels, along with any structured metadata, are added as fields. Requests are accepted on any path, such as `/loki/api/v1/push`. Snappy compressed protobuf push requests aren't supported, so clients such as Promtail must be configured to send JSON."
+					splunk_hec:              "Concatenated [Splunk HTTP Event Collector](\(urls.splunk_hec_event_endpoint)) JSON envelopes, with each forming an event. A string `event` becomes the message, while the fields of an object `event` and of `fields` are added to the event. `time` becomes the timestamp, `host` the host, and `channel`, `index`, `source` and `sourcetype` are added as `splunk_channel`, `splunk_index`, `splunk_source` and `splunk_sourcetype`. Requests are accepted on any path, such as `/services/collector/event`, and are answered with `{\"text\":\"Success\",\"code\":0}` unless `response_body` is set. Use the `api_key` authentication strategy, with `api_key_header` set to `Authorization` and `api_key_prefix` set to `Splunk `, to check HEC tokens."
+					es_bulk:                 "An Elasticsearch [bulk request](\(urls.elasticsearch_bulk)), with the document of each `index` or `create` action forming an event with the action's `_index` and `_id` added as fields. Actions without an `_index` use the index in the path, as in `/logs/_bulk`. `update` and `delete` actions are skipped. Requests are accepted on any path, and are answered with `response_body` rather than a bulk response."
+					datadog_logs:            "A JSON array of [Datadog logs](\(urls.datadog_logs_endpoints)), or a single log, with each forming an event. `message` becomes the message and `hostname` the host, `ddtags` is split into a map of tags, and other fields such as `ddsource` and `service` are added as they are. Requests are accepted on any path, such as `/api/v2/logs`. Use the `api_key` authentication strategy, with `api_key_header` set to `DD-API-KEY`, to check Datadog API keys."
+					influx_line:             "The InfluxDB line protocol, with each field of each line forming a gauge metric event named after the field, with the measurement as its namespace and the line's tags as its tags. String fields are skipped. Timestamps are in nanoseconds unless the `precision` query parameter says otherwise, and the `db` query parameter is added as a tag. Requests are accepted on any path, such as `/write`. Metric events don't have headers, query parameters or other request fields added."
+					prometheus_remote_write: "A Prometheus [remote write](\(urls.prometheus_remote_write)) request, a snappy compressed protobuf `WriteRequest`, with each sample of each time series forming a gauge metric event named after the series' `__name__` label, with its other labels as tags. Bodies are decompressed whether or not `Content-Encoding` is set, and an `X-Prometheus-Remote-Write-Version` header other than 0.1 is rejected. Requests are accepted on any path, such as `/api/v1/write`. Metric events don't have headers, query parameters or other request fields added."
+					auto:                    "Chosen per request from the `Content-Type` header, falling back to `default_encoding` when the header is missing or unrecognized."
+				}
+				syntax: "literal"
+			}
+		}
+		default_encoding: {
+			common:        false
+			description:   "The encoding used for requests, and multipart parts, without a recognized `Content-Type` header. It can't be an encoding decoded with the request's query, path or headers, such as `es_bulk`, or a metric encoding."
+			relevant_when: "encoding == \"auto\""
+			required:      false
+			type: string: {
+				default: "text"
+				enum: {
+					text:         "Newline-delimited text, with each line forming a message."
+					ndjson:       "Newline-delimited JSON objects, where each line must contain a JSON object."
+					json:         "Array of JSON objects, which must be a JSON array containing JSON objects."
+					csv:          "Comma-separated values, with each row forming an event."
+					avro:         "An Avro object container file with an embedded schema, with each record forming an event."
+					message_pack: "A MessagePack map, or an array of MessagePack maps, with each map forming an event."
+					protobuf:     "A protobuf message of the type configured by `proto_message_type`, forming a single event."
+					form:         "A `application/x-www-form-urlencoded` body, forming a single event. Repeated keys are collected into an array."
+					xml:          "An XML document, forming a single event. Elements become nested maps, attributes are placed under `@attributes`, text is placed under `#text`, and repeated elements are collected into an array."
+					logfmt:       "Newline-delimited logfmt, with each line forming an event. Keys without values are set to `true`."
+					gelf:         "A [GELF](\(urls.gelf)) message, or an array of them, with each message forming an event. The required `short_message` and `host` fields become the message and host, the numeric `timestamp` becomes the timestamp, additional fields have their leading underscore removed, and `version` is dropped."
+				}
+				syntax: "literal"
+			}
+		}
+		framing_delimiter: {
+			common:        false
+			description:   "The single-byte character separating records in the body. When it is `\\n`, a `\\r` ending a record is dropped too, so `\\r\\n` line endings are also accepted."
+			relevant_when: "encoding == \"text\" or encoding == \"ndjson\" or encoding == \"logfmt\""
+			required:      false
+			type: string: {
+				default: "\\n"
+				examples: ["\\u001e"]
+				syntax: "literal"
+			}
+		}
+		csv_headers: {
+			common:        false
+			description:   "The field names to use for the columns of each CSV row. Columns without a configured name are named after their index, e.g. `col0`, `col1`."
+			relevant_when: "encoding == \"csv\""
+			required:      false
+			type: array: {
+				default: null
+				items: type: string: {
+					examples: ["name", "amount"]
+					syntax: "literal"
+				}
+			}
+		}
+		proto_descriptor_path: {
+			common:        false
+			description:   "The path to a protobuf `FileDescriptorSet`, as produced by `protoc --descriptor_set_out`, describing the received messages."
+			relevant_when: "encoding == \"protobuf\""
+			required:      false
+			type: string: {
+				default: null
+				examples: ["/etc/vector/events.desc"]
+				syntax: "literal"
+			}
+		}
+		proto_message_type: {
+			common:        false
+			description:   "The fully qualified name of the protobuf message type of the request body. Repeated fields become arrays, nested messages become maps, and unknown fields are dropped."
+			relevant_when: "encoding == \"protobuf\""
+			required:      false
+			type: string: {
+				default: null
+				examples: ["mycompany.events.Event"]
+				syntax: "literal"
+			}
+		}
+		charset: {
+			common:      false
+			description: "The character set of request bodies, which are transcoded to UTF-8, after decompression and before they are decoded using `encoding`. Requests whose body isn't valid in the character set are rejected with a 400 response. Any [WHATWG encoding label](\(urls.encoding_charset_labels)) is accepted."
+			required:    false
+			warnings: []
+			type: string: {
+				default: null
+				examples: ["windows-1252", "iso-8859-1", "shift_jis"]
+				syntax: "literal"
+			}
+		}
+		invalid_utf8: {
+			common:        false
+			description:   "How lines that aren't valid UTF-8 are handled."
+			relevant_when: "encoding == \"text\""
+			required:      false
+			warnings: []
+			type: string: {
+				default: "passthrough"
+				enum: {
+					error:       "The request is rejected with a 400 response."
+					replace:     "Invalid sequences are replaced with the `U+FFFD` replacement character."
+					passthrough: "The line is kept as is."
+				}
+				syntax: "literal"
+			}
+		}
+		on_empty_body: {
+			common:      false
+			description: "How requests with an empty body are handled. `GET` requests without a body are always a single event."
+			required:    false
+			warnings: []
+			type: string: {
+				default: "decode"
+				enum: {
+					decode: "The body is decoded like any other, so the result depends on the encoding. `text` and `ndjson` produce no events, while `json` rejects the request."
+					accept: "The request is a single event without any decoded fields, or no events for metric encodings."
+					reject: "The request is rejected with a 400 response."
+				}
+				syntax: "literal"
+			}
+		}
+		body_base64: {
+			common:      false
+			description: "Base64 decode request bodies, after decompression and before they are decoded using `encoding`. Requests whose body isn't valid base64 are rejected with a 400 response."
+			required:    false
+			warnings: []
+			type: bool: default: false
+		}
+		body_key: {
+			common:        false
+			description:   "The field decoded JSON objects are nested under, rather than their fields being added to the root of events. This avoids collisions with fields added from the request, such as `headers`."
+			relevant_when: "encoding == \"json\" or encoding == \"ndjson\""
+			required:      false
+			warnings: []
+			type: string: {
+				default: null
+				examples: ["payload"]
+				syntax: "literal"
+			}
+		}
+		json_pointer: {
+			common:        false
+			description:   "A JSON pointer, as in RFC 6901, to the array or object of events within each body, for bodies wrapping their events in an envelope. Requests without an array or object there are rejected with a 400 response."
+			relevant_when: "encoding == \"json\""
+			required:      false
+			warnings: []
+			type: string: {
+				default: null
+				examples: ["/data/events"]
+				syntax: "literal"
+			}
+		}
+		raw_body_key: {
+			common:      false
+			description: "The field the request body, once decompressed but before being decoded, is added to events under. Every event decoded from the body gets all of it. Bodies that aren't valid UTF-8 are base64 encoded."
+			required:    false
+			warnings: []
+			type: string: {
+				default: null
+				examples: ["raw_body"]
+				syntax: "literal"
+			}
+		}
+		on_array_error: {
+			common:        false
+			description:   "How a JSON array with elements that aren't objects is handled."
+			relevant_when: "encoding == \"json\""
+			required:      false
+			warnings: []
+			type: string: {
+				default: "reject_all"
+				enum: {
+					reject_all:   "The whole request is rejected with a 400 response."
+					skip_invalid: "The invalid elements are dropped, and counted by the `events_discarded_total` metric, and the rest are accepted."
+				}
+				syntax: "literal"
+			}
+		}
+		flatten: {
+			common:        false
+			description:   "Whether nested JSON objects are flattened into fields named after their path, such as `a.b.c`, for destinations wanting flat records."
+			relevant_when: "encoding == \"json\" or encoding == \"ndjson\""
+			required:      false
+			warnings: []
+			type: bool: default: false
+		}
+		flatten_separator: {
+			common:        false
+			description:   "The separator between the parts of flattened field names."
+			relevant_when: "flatten = true"
+			required:      false
+			warnings: []
+			type: string: {
+				default: "."
+				examples: ["_"]
+				syntax: "literal"
+			}
+		}
+		flatten_arrays: {
+			common:        false
+			description:   "Whether arrays are flattened too, into fields suffixed with the element's index, such as `tags.0`, rather than being kept as they are."
+			relevant_when: "flatten = true"
+			required:      false
+			warnings: []
+			type: bool: default: false
+		}
+		json_schema_path: {
+			common:        false
+			description:   "The path of a [JSON Schema](\(urls.json_schema)) decoded objects are validated against. Requests with invalid objects are handled according to `schema_mode`."
+			relevant_when: "encoding == \"json\" or encoding == \"ndjson\""
+			required:      false
+			warnings: []
+			type: string: {
+				default: null
+				examples: ["/etc/vector/events.schema.json"]
+				syntax: "literal"
+			}
+		}
+		schema_mode: {
+			common:        false
+			description:   "How requests containing objects failing `json_schema_path` validation are handled."
+			relevant_when: "json_schema_path != null"
+			required:      false
+			warnings: []
+			type: string: {
+				default: "reject"
+				enum: {
+					reject:       "The whole request is rejected with a 400 response naming the first invalid field."
+					drop_invalid: "The invalid objects are dropped and the rest are accepted."
+				}
+				syntax: "literal"
+			}
+		}
+		openapi_path: {
+			common:      false
+			description: "The path of an [OpenAPI 3](\(urls.openapi)) document, in JSON or YAML, describing the requests accepted. Requests aren't restricted to `path`; instead, those whose path and method match no operation in the document are rejected with a 404 or 405 response. Request bodies are validated against the JSON schema of the matched operation's `application/json` request body, rejecting invalid ones with a 400 response naming the first invalid field. Paths are matched against the request path as is, without any `servers` prefix, and `{}` enclosed path segments match any single segment."
+			required:    false
+			warnings: []
+			type: string: {
+				default: null
+				examples: ["/etc/vector/webhooks.openapi.yaml"]
+				syntax: "literal"
+			}
+		}
+		add_timestamp: {
+			common:      false
+			description: "Whether the time the request was received is added to events as their timestamp. Timestamps in the body, such as those decoded by the `gelf` and `splunk_hec` encodings or taken by `timestamp_field`, are still kept."
+			required:    false
+			warnings: []
+			type: bool: default: true
+		}
+		timestamp_precedence: {
+			common:      false
+			description: "Which is kept when the body has a field named like the timestamp key: the field itself, or the time the request was received. `timestamp_field` is applied afterwards either way."
+			required:    false
+			warnings: []
+			type: string: {
+				default: "body"
+				enum: {
+					body:     "The field from the body is kept."
+					received: "The field from the body is replaced by the time the request was received."
+				}
+				syntax: "literal"
+			}
+		}
+		received_at_key: {
+			common:      false
+			description: "The field to add the time the request was received to, regardless of the event's timestamp. Useful alongside `timestamp_field` to measure ingest lag."
+			required:    false
+			warnings: []
+			type: string: {
+				default: null
+				examples: ["received_at"]
+				syntax: "literal"
+			}
+		}
+		add_source_type: {
+			common:      false
+			description: "Whether the `source_type` field is added to events."
+			required:    false
+			warnings: []
+			type: bool: default: true
+		}
+		source_type: {
+			common:      false
+			description: "The value of the `source_type` field added to events, to tell several `http` sources apart."
+			required:    false
+			warnings: []
+			type: string: {
+				default: "http"
+				examples: ["github_webhook"]
+				syntax: "literal"
+			}
+		}
+		timestamp_field: {
+			common:      false
+			description: "A field of decoded events holding their timestamp, parsed according to `timestamp_format`. When present and valid it's used as the event's timestamp, otherwise the time the request was received is used."
+			required:    false
+			warnings: []
+			type: string: {
+				default: null
+				examples: ["time"]
+				syntax: "literal"
+			}
+		}
+		timestamp_format: {
+			common:        false
+			description:   "How the `timestamp_field` is parsed. Besides the named formats, any [`strftime` specifiers](\(urls.chrono_time_formats)) format may be given, with timestamps lacking an offset taken to be in UTC."
+			relevant_when: "timestamp_field != null"
+			required:      false
+			warnings: []
+			type: string: {
+				default: "rfc3339"
+				enum: {
+					rfc3339: "An [RFC 3339](\(urls.rfc_3339)) timestamp."
+					unix:    "Seconds since the Unix epoch, as a number or string."
+					unix_ms: "Milliseconds since the Unix epoch, as an integer or string."
 				}
 				syntax: "literal"
 			}
 		}
 		headers: {
 			common:      false
-			description: "A list of HTTP headers to include in the log event. These will override any values included in the JSON payload with conflicting names."
+			description: "A list of HTTP headers to include in the log event. These will override any values included in the JSON payload with conflicting names. Headers sent more than once are added as an array of their values."
 			required:    false
 			type: array: {
 				default: null
@@ -94,7 +421,609 @@ components: sources: http: {
 				}
 			}
 		}
-		auth: configuration._http_basic_auth
+		json_headers: {
+			common:      false
+			description: "A list of HTTP headers to include in the log event with their values decoded as JSON, so that objects become nested fields. Values that aren't valid JSON are included as they are."
+			required:    false
+			type: array: {
+				default: null
+				items: type: string: {
+					examples: ["X-Context"]
+					syntax: "literal"
+				}
+			}
+		}
+		header_patterns: {
+			common:      false
+			description: "A list of regular expressions matched against the name of each request header, case-insensitively. Matching headers are added to the log event under their lowercased names, as arrays of their values when sent more than once. Headers also listed in `headers` or `json_headers` are added as configured there instead."
+			required:    false
+			type: array: {
+				default: null
+				items: type: string: {
+					examples: ["^X-Custom-"]
+					syntax: "regex"
+				}
+			}
+		}
+		lowercase_header_keys: {
+			common:      false
+			description: "Whether the fields added for `headers` and `json_headers` are named after the lowercased header names, rather than using the casing given in the configuration. Fields added for `header_patterns` and `all_headers_key` are always lowercased."
+			required:    false
+			warnings: []
+			type: bool: default: false
+		}
+		skip_missing_headers: {
+			common:      false
+			description: "Whether headers in `headers` that the request doesn't have are left out of events, rather than added as `null`."
+			required:    false
+			warnings: []
+			type: bool: default: false
+		}
+		all_headers_key: {
+			common:      false
+			description: "The field a map of every request header is added to events under. Header names are lowercased, and headers sent more than once become arrays of their values. This is in addition to the fields added by `headers`."
+			required:    false
+			warnings: []
+			type: string: {
+				default: null
+				examples: ["headers"]
+				syntax: "literal"
+			}
+		}
+		skip_missing_query_parameters: {
+			common:      false
+			description: "Whether parameters in `query_parameters` that the request doesn't have are left out of events, rather than added as `null`."
+			required:    false
+			warnings: []
+			type: bool: default: false
+		}
+		cookies_key: {
+			common:      false
+			description: "The field a map of the cookies in the request's `Cookie` header is added to events under. Malformed cookies are skipped, and nothing is added when the request has no cookies."
+			required:    false
+			warnings: []
+			type: string: {
+				default: null
+				examples: ["cookies"]
+				syntax: "literal"
+			}
+		}
+		raw_query_key: {
+			common:      false
+			description: "The field the request's query string, as received and without the leading `?`, is added to events under. This is in addition to the fields added by `query_parameters`."
+			required:    false
+			warnings: []
+			type: string: {
+				default: null
+				examples: ["query"]
+				syntax: "literal"
+			}
+		}
+		methods: {
+			common:      false
+			description: "The HTTP methods accepted. Requests using other methods are rejected with a 405 response. A `GET` request without a body produces a single event, which is useful with `query_parameters` for beacon style clients."
+			required:    false
+			warnings: []
+			type: array: {
+				default: ["POST"]
+				items: type: string: {
+					examples: ["POST", "PUT", "PATCH", "GET"]
+					syntax: "literal"
+				}
+			}
+		}
+		allowed_content_types: {
+			common:      false
+			description: "The media types request bodies may have, ignoring parameters such as `charset`. Requests with another `Content-Type`, or none, are rejected with a 415 response before their body is decoded. If empty, any are accepted."
+			required:    false
+			warnings: []
+			type: array: {
+				default: []
+				items: type: string: {
+					examples: ["application/json", "application/x-ndjson"]
+					syntax: "literal"
+				}
+			}
+		}
+		method_key: {
+			common:      false
+			description: "The field the request's HTTP method is added to events under."
+			required:    false
+			warnings: []
+			type: string: {
+				default: null
+				examples: ["method"]
+				syntax: "literal"
+			}
+		}
+		path_template: {
+			common:      false
+			description: "Accept requests on paths matching this template rather than only on `/`. Segments starting with `:` are parameters, added to events as fields named after them. Requests whose path doesn't match are rejected with a 404 response."
+			required:    false
+			warnings: []
+			type: string: {
+				default: null
+				examples: ["/ingest/:env/:service"]
+				syntax: "literal"
+			}
+		}
+		routes: {
+			common:      false
+			description: "Accept requests on several paths, each with its own encoding, rather than only on `/`. Requests are handled by the first route whose path matches, and rejected with a 404 response if none do. A route's `auth` is used instead of the source's, while routes without one accept requests without authentication. This can't be used with `path_template`."
+			required:    false
+			warnings: []
+			type: array: {
+				default: []
+				items: type: object: {
+					examples: []
+					options: {
+						path: {
+							description: "The route's path, a template as for `path_template` whose parameters are added to events."
+							required:    true
+							warnings: []
+							type: string: {
+								examples: ["/logs", "/logs/:app"]
+								syntax: "literal"
+							}
+						}
+						encoding: {
+							common:      false
+							description: "The encoding of requests to this route, which takes the same values as `encoding`. Defaults to `encoding`."
+							required:    false
+							warnings: []
+							type: string: {
+								default: null
+								examples: ["ndjson", "metric"]
+								syntax: "literal"
+							}
+						}
+						auth: configuration._http_source_auth
+					}
+				}
+			}
+		}
+		route_key: {
+			common:      false
+			description: "The field to add the matched route to, so events can be told apart without parsing their path. This is the matched route's path, or `path_template` when set, otherwise `/`, or `*` for encodings accepting any path, such as `influx_line`."
+			required:    false
+			warnings: []
+			type: string: {
+				default: null
+				examples: ["route"]
+				syntax: "literal"
+			}
+		}
+		path_segments_key: {
+			common:      false
+			description: "The field to add the non-empty segments of the request's path to, as an array, such as `[\"event\", \"path1\"]` for `/event/path1`. Useful with `routes`, `path_template`, or encodings accepting any path, where clients put context in the path."
+			required:    false
+			warnings: []
+			type: string: {
+				default: null
+				examples: ["path_segments"]
+				syntax: "literal"
+			}
+		}
+		trailing_slash: {
+			common:      false
+			description: "Whether request paths must match exactly, or may have trailing slashes. This applies to the root path, `healthcheck_path` and `websocket_path`, while `path_template` always allows them."
+			required:    false
+			warnings: []
+			type: string: {
+				default: "strict"
+				enum: {
+					strict:  "Paths must match exactly, so `/health/` is distinct from `/health`."
+					lenient: "Trailing slashes are removed from request paths, and from the configured paths, before matching. Paths added to events have them removed too."
+				}
+				syntax: "literal"
+			}
+		}
+		healthcheck_path: {
+			common:      false
+			description: "A path answered with an empty 200 response for load balancer health checks. These requests don't require authentication and produce no events."
+			required:    false
+			warnings: []
+			type: string: {
+				default: null
+				examples: ["/health"]
+				syntax: "literal"
+			}
+		}
+		websocket_path: {
+			common:      false
+			description: "A path accepting WebSocket connections. Each text or binary message is decoded like a request body, using `encoding`, and messages that can't be decoded are answered with the error. Authentication applies to the upgrade request, so signature based strategies such as `hmac` and `stripe`, which cover the body, can't be used with it."
+			required:    false
+			warnings: []
+			type: string: {
+				default: null
+				examples: ["/stream"]
+				syntax: "literal"
+			}
+		}
+		server_sent_events: {
+			common:      false
+			description: "Whether `text/event-stream` request bodies are read as a stream of [server-sent events](\(urls.server_sent_events)). The data of each event is decoded using `encoding` as soon as the event has been received, rather than once the request ends, and events that can't be decoded are skipped. Streams are answered and closed when Vector shuts down. Compressed streams aren't supported, and `max_body_size` limits the size of each event rather than of the body. Authentication applies before the body is read, so signature based strategies such as `hmac` and `stripe` can't be used with it."
+			required:    false
+			warnings: []
+			type: bool: default: false
+		}
+		sse_comment_key: {
+			common:        false
+			description:   "The field to add the comment lines, those starting with `:`, of a server-sent event to, as an array."
+			relevant_when: "server_sent_events = true"
+			required:      false
+			warnings: []
+			type: string: {
+				default: null
+				examples: ["sse_comments"]
+				syntax: "literal"
+			}
+		}
+		sse_event_key: {
+			common:        false
+			description:   "The field to add the `event` type of a server-sent event to."
+			relevant_when: "server_sent_events = true"
+			required:      false
+			warnings: []
+			type: string: {
+				default: null
+				examples: ["sse_event"]
+				syntax: "literal"
+			}
+		}
+		sse_id_key: {
+			common:        false
+			description:   "The field to add the `id` of a server-sent event to."
+			relevant_when: "server_sent_events = true"
+			required:      false
+			warnings: []
+			type: string: {
+				default: null
+				examples: ["sse_id"]
+				syntax: "literal"
+			}
+		}
+		response_code: {
+			common:      false
+			description: "The HTTP status code returned when a request's events are accepted. Must be a `2xx` code. Error responses are unaffected."
+			required:    false
+			warnings: []
+			type: uint: {
+				default: 200
+				examples: [202, 204]
+				unit: null
+			}
+		}
+		response_body: {
+			common:      false
+			description: "The body returned when a request's events are accepted. If unset, the body is empty."
+			required:    false
+			warnings: []
+			type: string: {
+				default: null
+				examples: ["{\"ok\":true}"]
+				syntax: "literal"
+			}
+		}
+		response_content_type: {
+			common:      false
+			description: "The `Content-Type` of the response returned when a request's events are accepted."
+			required:    false
+			warnings: []
+			type: string: {
+				default: null
+				examples: ["application/json"]
+				syntax: "literal"
+			}
+		}
+		response_headers: {
+			common:      false
+			description: "Headers added to the response when a request's events are accepted. Headers managed by the server, such as `Content-Length`, can't be set."
+			required:    false
+			warnings: []
+			type: object: {
+				examples: [{"X-Vector-Received": "true"}]
+				options: {}
+			}
+		}
+		response_gzip_threshold: {
+			common:      false
+			description: "The smallest response body, in bytes, compressed with gzip for clients sending an `Accept-Encoding` header that includes `gzip`. Such responses carry a `Content-Encoding: gzip` header; smaller bodies and clients not accepting gzip get uncompressed responses. If unset, responses are never compressed. Request bodies are unaffected."
+			required:    false
+			warnings: []
+			type: uint: {
+				default: null
+				examples: [1024]
+				unit: "bytes"
+			}
+		}
+		echo_request_id_header: {
+			common:      false
+			description: "The name of a request header, such as `X-Request-Id`, whose value is copied into the response when present."
+			required:    false
+			warnings: []
+			type: string: {
+				default: null
+				examples: ["X-Request-Id"]
+				syntax: "literal"
+			}
+		}
+		request_id_key: {
+			common:        false
+			description:   "The field the value of `echo_request_id_header` is added to events under, allowing requests to be joined to the events they carried."
+			relevant_when: "echo_request_id_header != null"
+			required:      false
+			warnings: []
+			type: string: {
+				default: null
+				examples: ["request_id"]
+				syntax: "literal"
+			}
+		}
+		max_body_size: {
+			common:      false
+			description: "The largest request body accepted, in bytes. Larger requests are rejected with a 413 response without being read in full. The limit also applies to the decompressed body, so compressed requests can't expand past it."
+			required:    false
+			warnings: []
+			type: uint: {
+				default: null
+				examples: [10485760]
+				unit: "bytes"
+			}
+		}
+		max_events_per_request: {
+			common:      false
+			description: "The most events a single request may produce. Requests producing more are rejected with a 413 response, without any of their events being sent."
+			required:    false
+			warnings: []
+			type: uint: {
+				default: null
+				examples: [10000]
+				unit: null
+			}
+		}
+		when_full: {
+			common:      false
+			description: "What happens to requests arriving while downstream components aren't keeping up."
+			required:    false
+			warnings: []
+			type: string: {
+				default: "block"
+				enum: {
+					block:  "The request waits until its events can be sent."
+					reject: "The request is rejected with a 503 response carrying a `Retry-After` header, so that clients back off. Events sent before the pipeline filled up are kept. WebSocket connections are answered with the same error as a message and stay open, and server-sent event streams are ended with it."
+				}
+				syntax: "literal"
+			}
+		}
+		shutdown_timeout_secs: {
+			common:      false
+			description: "How long requests in flight when Vector shuts down or reloads are waited for before their connections are closed. No new connections are accepted meanwhile, and requests arriving on open ones are rejected with a 503 response. If unset, in-flight requests are waited for as long as they take."
+			required:    false
+			warnings: []
+			type: uint: {
+				default: null
+				examples: [30]
+				unit: "seconds"
+			}
+		}
+		allowed_ips: {
+			common:      false
+			description: "A list of CIDR blocks clients must be in. Requests from other addresses are rejected with a 403 response. If empty, all clients not in `denied_ips` are accepted."
+			required:    false
+			warnings: []
+			type: array: {
+				default: []
+				items: type: string: {
+					examples: ["10.0.0.0/8", "2001:db8::/32"]
+					syntax: "literal"
+				}
+			}
+		}
+		denied_ips: {
+			common:      false
+			description: "A list of CIDR blocks whose requests are rejected with a 403 response. This takes precedence over `allowed_ips`."
+			required:    false
+			warnings: []
+			type: array: {
+				default: []
+				items: type: string: {
+					examples: ["192.0.2.0/24"]
+					syntax: "literal"
+				}
+			}
+		}
+		trusted_proxies: {
+			common:      false
+			description: "A list of CIDR blocks of proxies trusted to set `X-Forwarded-For`. For requests from these addresses, the client address checked against `allowed_ips` and `denied_ips` is the rightmost `X-Forwarded-For` entry not belonging to a trusted proxy."
+			required:    false
+			warnings: []
+			type: array: {
+				default: []
+				items: type: string: {
+					examples: ["10.0.0.0/8"]
+					syntax: "literal"
+				}
+			}
+		}
+		remote_ip_key: {
+			common:      false
+			description: "The field the client's IP address is added to events under. When the client is one of the `trusted_proxies`, the rightmost untrusted address in `X-Forwarded-For` is used instead."
+			required:    false
+			warnings: []
+			type: string: {
+				default: null
+				examples: ["remote_ip"]
+				syntax: "literal"
+			}
+		}
+		sni_key: {
+			common:      false
+			description: "The field to add the server name clients asked for with TLS SNI to, for telling apart hostnames served on the same port. Requests without TLS, or without SNI, don't have it."
+			required:    false
+			warnings: []
+			type: string: {
+				default: null
+				examples: ["sni"]
+				syntax: "literal"
+			}
+		}
+		verify_client: {
+			common:      false
+			description: "If `true`, clients must present a certificate signed by `tls.ca_file`, and connections without one are rejected. Requires `tls.enabled`."
+			required:    false
+			warnings: []
+			type: bool: default: false
+		}
+		client_cn_key: {
+			common:      false
+			description: "The field to add the subject common name of the client's verified certificate to. Requests without one don't have it."
+			required:    false
+			warnings: []
+			type: string: {
+				default: null
+				examples: ["client_cn"]
+				syntax: "literal"
+			}
+		}
+		client_sans_key: {
+			common:      false
+			description: "The field to add the subject alternative names of the client's verified certificate to, as an array of DNS names, email addresses, URIs and IP addresses. Requests without any don't have it."
+			required:    false
+			warnings: []
+			type: string: {
+				default: null
+				examples: ["client_sans"]
+				syntax: "literal"
+			}
+		}
+		rate_limit: {
+			common:      false
+			description: "Limits the rate of requests from each client address, which respects `trusted_proxies`. Requests over the limit are rejected with a 429 response carrying a `Retry-After` header."
+			required:    false
+			warnings: []
+			type: object: {
+				examples: []
+				options: {
+					requests_per_second: {
+						description: "The sustained number of requests allowed per second from each client."
+						required:    true
+						warnings: []
+						type: float: {
+							examples: [10.0]
+						}
+					}
+					burst: {
+						common:      false
+						description: "The number of requests a client may make in a burst. Defaults to `requests_per_second`."
+						required:    false
+						warnings: []
+						type: float: {
+							default: null
+							examples: [50.0]
+						}
+					}
+				}
+			}
+		}
+		cors: {
+			common:      false
+			description: "Enables cross-origin requests from browsers. `OPTIONS` preflight requests are answered without creating events, and `Access-Control-*` headers are added to responses for allowed origins."
+			required:    false
+			warnings: []
+			type: object: {
+				examples: []
+				options: {
+					allowed_origins: {
+						description: "The origins allowed to make requests, or `*` to allow any origin."
+						required:    true
+						warnings: []
+						type: array: items: type: string: {
+							examples: ["https://example.com", "*"]
+							syntax: "literal"
+						}
+					}
+					allowed_methods: {
+						common:      false
+						description: "The request methods allowed for cross-origin requests."
+						required:    false
+						warnings: []
+						type: array: {
+							default: ["POST"]
+							items: type: string: {
+								examples: ["POST", "PUT"]
+								syntax: "literal"
+							}
+						}
+					}
+					allowed_headers: {
+						common:      false
+						description: "The request headers allowed for cross-origin requests."
+						required:    false
+						warnings: []
+						type: array: {
+							default: []
+							items: type: string: {
+								examples: ["Content-Type", "Authorization"]
+								syntax: "literal"
+							}
+						}
+					}
+					max_age_secs: {
+						common:      false
+						description: "How long browsers may cache preflight responses."
+						required:    false
+						warnings: []
+						type: uint: {
+							default: null
+							examples: [3600]
+							unit: "seconds"
+						}
+					}
+				}
+			}
+		}
+		idempotency: {
+			common:      false
+			description: "Recognizes retried requests by a key header. A request repeating the key of one already accepted gets a success response without its events being sent again. Keys are only remembered once a request's events are accepted, so failed requests can be retried."
+			required:    false
+			warnings: []
+			type: object: {
+				examples: []
+				options: {
+					header: {
+						description: "The request header carrying the idempotency key."
+						required:    true
+						warnings: []
+						type: string: {
+							examples: ["Idempotency-Key"]
+							syntax: "literal"
+						}
+					}
+					max_keys: {
+						common:      false
+						description: "The number of keys remembered, with the least recently accepted forgotten first."
+						required:    false
+						warnings: []
+						type: uint: {
+							default: 10000
+							unit:    null
+						}
+					}
+					ttl_secs: {
+						common:      false
+						description: "How long a key is remembered after its request is accepted."
+						required:    false
+						warnings: []
+						type: uint: {
+							default: 86400
+							unit:    "seconds"
+						}
+					}
+				}
+			}
+		}
+		auth: configuration._http_source_auth
 		query_parameters: {
 			common:      false
 			description: "A list of URL query parameters to include in the log event. These will override any values included in the body with conflicting names."
@@ -200,16 +1129,68 @@ components: sources: http: {
 	]
 
 	telemetry: metrics: {
+		events_discarded_total:  components.sources.internal_metrics.output.metrics.events_discarded_total
 		http_bad_requests_total: components.sources.internal_metrics.output.metrics.http_bad_requests_total
+		http_request_body_size_bytes: components.sources.internal_metrics.output.metrics.http_request_body_size_bytes
 		parse_errors_total:      components.sources.internal_metrics.output.metrics.parse_errors_total
+		processed_bytes_total:   components.sources.internal_metrics.output.metrics.processed_bytes_total
+		processed_events_total:  components.sources.internal_metrics.output.metrics.processed_events_total
+		requests_received_total: components.sources.internal_metrics.output.metrics.requests_received_total
+		timestamp_parse_errors_total: components.sources.internal_metrics.output.metrics.timestamp_parse_errors_total
 	}
 
 	how_it_works: {
+		multipart: {
+			title: "Multipart Bodies"
+			body: """
+				Requests with a `multipart/form-data` `Content-Type` produce one event per part, regardless of
+				the configured `encoding`. The content of each part is placed under a field named after the
+				part. Parts with a structured `Content-Type`, such as `application/json`, are decoded
+				accordingly, and file parts are stored as a `filename` and the raw `content`.
+				"""
+		}
 		decompression: {
 			title: "Decompression"
 			body: """
 				Received body is decompressed according to `Content-Encoding` header.
-				Supported algorithms are `gzip`, `deflate`, and `snappy`.
+				Supported algorithms are `gzip`, `deflate`, `br`, `zstd`, and `snappy`. Snappy bodies
+				may use either the raw block format, as Prometheus remote write does, or the framing
+				format.
+				"""
+		}
+		client_certificates: {
+			title: "Client Certificates"
+			body: """
+				Setting `verify_client` requires clients to present a certificate signed by `tls.ca_file`,
+				and connections without one are rejected. The subject common name and subject alternative
+				names of the verified certificate are added to each event at `client_cn_key` and
+				`client_sans_key`, when set.
+				"""
+		}
+		line_streaming: {
+			title: "Line Streaming"
+			body: """
+				Uncompressed `text`, `ndjson` and `logfmt` bodies are decoded a line at a time as they are
+				received, with the events of each line sent on as soon as the line ends, so large bodies
+				aren't held in memory. A line that can't be decoded still fails the request, but the lines
+				before it have already been sent.
+				"""
+		}
+		json_numbers: {
+			title: "JSON Numbers"
+			body: """
+				JSON integers are kept as exact integers, and numbers with a fraction or exponent as
+				floats. Integers outside the range of a 64-bit signed integer, such as unsigned IDs above
+				9223372036854775807, become the nearest float and lose precision.
+				"""
+		}
+		simd_json: {
+			title: "SIMD JSON Parsing"
+			body: """
+				When Vector is built with the `sources-http-simd-json` feature, the `json` and `ndjson`
+				encodings are parsed with [simd-json](\(urls.simd_json)) instead of the standard parser. The
+				resulting events are the same either way. simd-json needs AVX2 or SSE4.2, so Vector must be
+				built for a target supporting them, such as with `RUSTFLAGS="-C target-cpu=native"`.
 				"""
 		}
 	}
@@ -369,10 +369,16 @@ components: sources: internal_metrics: {
 			}
 		}
 		http_bad_requests_total: {
-			description:       "The total number of HTTP `400 Bad Request` errors encountered."
+			description:       "The total number of HTTP requests rejected by this component."
 			type:              "counter"
 			default_namespace: "vector"
-			tags:              _internal_metrics_tags
+			tags:              _internal_metrics_tags & {
+				status: {
+					description: "The HTTP status code of the response."
+					required:    true
+					examples: ["400", "401", "413"]
+				}
+			}
 		}
 		http_error_response_total: {
 			description:       "The total number of HTTP error responses for this component."
@@ -380,6 +386,18 @@ components: sources: internal_metrics: {
 			default_namespace: "vector"
 			tags:              _internal_metrics_tags
 		}
+		http_request_body_size_bytes: {
+			description:       "The size of HTTP request bodies received by this component, after decompression."
+			type:              "histogram"
+			default_namespace: "vector"
+			tags:              _internal_metrics_tags & {
+				status_class: {
+					description: "The class of the HTTP status code of the response."
+					required:    true
+					examples: ["2xx", "4xx", "5xx"]
+				}
+			}
+		}
 		http_request_errors_total: {
 			description:       "The total number of HTTP request errors for this component."
 			type:              "counter"
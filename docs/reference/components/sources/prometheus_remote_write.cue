@@ -67,7 +67,7 @@ components: sources: prometheus_remote_write: {
 				syntax: "literal"
 			}
 		}
-		auth: configuration._http_basic_auth
+		auth: configuration._http_source_auth
 	}
 
 	output: metrics: {
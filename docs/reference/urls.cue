@@ -175,6 +175,7 @@ urls: {
 	gcs_predefined_acl:                                       "https://cloud.google.com/storage/docs/access-control/lists#predefined-acl"
 	gcs_storage_classes:                                      "https://cloud.google.com/storage/docs/storage-classes"
 	gcs_custom_metadata:                                      "https://cloud.google.com/storage/docs/metadata#custom-metadata"
+	gelf:                                                     "https://docs.graylog.org/en/latest/pages/gelf.html"
 	git:                                                      "https://git-scm.com/"
 	github:                                                   "https://github.com"
 	github_protected_branches:                                "https://help.github.com/en/github/administering-a-repository/about-protected-branches"
@@ -221,6 +222,7 @@ urls: {
 	jemalloc:                                                 "\(github)/jemalloc/jemalloc"
 	journald:                                                 "https://www.freedesktop.org/software/systemd/man/systemd-journald.service.html"
 	json:                                                     "\(wikipedia)/wiki/JSON"
+	json_schema:                                              "https://json-schema.org/"
 	json_types:                                               "\(wikipedia)/wiki/JSON#Data_types_and_syntax"
 	jsonnet:                                                  "https://jsonnet.org/"
 	kafka:                                                    "https://kafka.apache.org/"
@@ -249,6 +251,7 @@ urls: {
 	logfmt:                                                   "https://brandur.org/logfmt"
 	loki:                                                     "https://grafana.com/oss/loki/"
 	loki_multi_tenancy:                                       "\(github)/grafana/loki/blob/master/docs/operations/multi-tenancy.md"
+	loki_push:                                                "https://grafana.com/docs/loki/latest/api/#post-lokiapiv1push"
 	log_event_source:                                         "\(vector_repo)/blob/master/src/event/"
 	logplex:                                                  "https://devcenter.heroku.com/articles/logplex"
 	logplex_protocol:                                         "\(github)/heroku/logplex/blob/master/doc/README.http_drains.md"
@@ -303,6 +306,7 @@ urls: {
 	nix:                                                      "https://nixos.org/nix/"
 	nixos:                                                    "https://nixos.org/"
 	nixpkgs_9682:                                             "\(github)/NixOS/nixpkgs/issues/9682"
+	openapi:                                                  "https://spec.openapis.org/oas/v3.0.3"
 	openssl:                                                  "https://www.openssl.org/"
 	order_of_ops:                                             "\(wikipedia)/wiki/Order_of_operations"
 	papertrail:                                               "https://www.papertrail.com/"
@@ -358,9 +362,11 @@ urls: {
 	sematext_monitoring:                                      "https://sematext.com/docs/monitoring/"
 	sematext_registration:                                    "https://apps.sematext.com/ui/registration"
 	semver:                                                   "https://semver.org/"
+	server_sent_events:                                       "https://html.spec.whatwg.org/multipage/server-sent-events.html"
 	sha1:                                                     "https://en.wikipedia.org/wiki/SHA-1"
 	sha2:                                                     "https://en.wikipedia.org/wiki/SHA-2"
 	sha3:                                                     "https://en.wikipedia.org/wiki/SHA-3"
+	simd_json:                                                "https://github.com/simd-lite/simd-json"
 	snake_case:                                               "\(wikipedia)/wiki/Snake_case"
 	snappy:                                                   "https://google.github.io/snappy/"
 	socket:                                                   "\(wikipedia)/wiki/Network_socket"
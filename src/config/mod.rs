@@ -385,6 +385,7 @@ pub enum Resource {
     SystemFdOffset(usize),
     Stdin,
     DiskBuffer(String),
+    UnixSocket(PathBuf),
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Hash, Ord, PartialOrd, Copy)]
@@ -460,6 +461,7 @@ impl Display for Resource {
             Resource::SystemFdOffset(offset) => write!(fmt, "systemd {}th socket", offset + 1),
             Resource::Stdin => write!(fmt, "stdin"),
             Resource::DiskBuffer(name) => write!(fmt, "disk buffer {:?}", name),
+            Resource::UnixSocket(path) => write!(fmt, "unix socket {:?}", path),
         }
     }
 }
@@ -718,6 +720,7 @@ mod resource_tests {
     use indoc::indoc;
     use std::collections::{HashMap, HashSet};
     use std::net::{Ipv4Addr, SocketAddr};
+    use std::path::PathBuf;
 
     fn localhost(port: u16) -> Resource {
         Resource::tcp(SocketAddr::new(Ipv4Addr::LOCALHOST.into(), port))
@@ -829,6 +832,21 @@ mod resource_tests {
         assert_eq!(conflicting, HashMap::new());
     }
 
+    #[test]
+    fn conflicting_unix_socket() {
+        let socket = |path: &str| Resource::UnixSocket(PathBuf::from(path));
+        let components = vec![
+            ("source_0", vec![socket("/run/a.sock")]),
+            ("source_1", vec![socket("/run/b.sock")]),
+            ("source_2", vec![socket("/run/a.sock")]),
+        ];
+        let conflicting = Resource::conflicts(components);
+        assert_eq!(
+            conflicting,
+            hashmap(vec![(socket("/run/a.sock"), vec!["source_0", "source_2"])])
+        );
+    }
+
     #[test]
     fn config_conflict_detected() {
         assert!(load_from_str(
@@ -1,7 +1,27 @@
 use super::InternalEvent;
-use metrics::counter;
+use metrics::{counter, histogram};
 use std::error::Error;
 
+#[derive(Debug)]
+pub struct HTTPRequestReceived<'a> {
+    pub method: &'a str,
+    pub path: &'a str,
+}
+
+impl<'a> InternalEvent for HTTPRequestReceived<'a> {
+    fn emit_logs(&self) {
+        trace!(
+            message = "Received HTTP request.",
+            method = %self.method,
+            path = %self.path,
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("requests_received_total", 1, "method" => self.method.to_owned());
+    }
+}
+
 #[derive(Debug)]
 pub struct HTTPEventsReceived {
     pub events_count: usize,
@@ -23,6 +43,26 @@ impl InternalEvent for HTTPEventsReceived {
     }
 }
 
+#[derive(Debug)]
+pub struct HTTPRequestBodySize {
+    pub byte_size: usize,
+    pub status: u16,
+}
+
+impl InternalEvent for HTTPRequestBodySize {
+    fn emit_logs(&self) {
+        trace!(
+            message = "Handled request body.",
+            byte_size = %self.byte_size,
+            status = %self.status,
+        );
+    }
+
+    fn emit_metrics(&self) {
+        histogram!("http_request_body_size_bytes", self.byte_size as f64, "status_class" => format!("{}xx", self.status / 100));
+    }
+}
+
 #[derive(Debug)]
 pub struct HTTPBadRequest<'a> {
     pub error_code: u16,
@@ -40,7 +80,49 @@ impl<'a> InternalEvent for HTTPBadRequest<'a> {
     }
 
     fn emit_metrics(&self) {
-        counter!("http_bad_requests_total", 1);
+        counter!("http_bad_requests_total", 1, "status" => self.error_code.to_string());
+    }
+}
+
+#[derive(Debug)]
+pub struct HTTPTimestampParseError<'a> {
+    pub field: &'a str,
+    pub error: &'a str,
+}
+
+impl<'a> InternalEvent for HTTPTimestampParseError<'a> {
+    fn emit_logs(&self) {
+        warn!(
+            message = "Failed parsing timestamp; using time of receipt.",
+            field = %self.field,
+            error = %self.error,
+            internal_log_rate_secs = 10,
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("timestamp_parse_errors_total", 1);
+    }
+}
+
+#[derive(Debug)]
+pub struct HTTPInvalidEventsDropped {
+    pub count: usize,
+    pub reason: &'static str,
+}
+
+impl InternalEvent for HTTPInvalidEventsDropped {
+    fn emit_logs(&self) {
+        warn!(
+            message = "Dropping invalid events.",
+            count = %self.count,
+            reason = %self.reason,
+            internal_log_rate_secs = 10,
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("events_discarded_total", self.count as u64);
     }
 }
 
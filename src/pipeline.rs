@@ -1,5 +1,5 @@
 use crate::{internal_events::EventOut, transforms::FunctionTransform, Event};
-use futures::{task::Poll, Sink};
+use futures::{task::Poll, Sink, SinkExt};
 use std::{collections::VecDeque, fmt, pin::Pin, task::Context};
 use tokio::sync::mpsc;
 
@@ -101,6 +101,36 @@ impl Sink<Event> for Pipeline {
 }
 
 impl Pipeline {
+    /// Sends events without waiting for room in the pipeline, stopping at the
+    /// first event there's no room for. Returns whether every event was sent.
+    pub async fn try_send_all(
+        &mut self,
+        events: impl IntoIterator<Item = Event>,
+    ) -> Result<bool, ClosedError> {
+        for event in events {
+            if !self.has_room()? {
+                return Ok(false);
+            }
+            self.send(event).await?;
+        }
+        Ok(true)
+    }
+
+    /// Checks, without waiting, whether the channel has room for an event,
+    /// holding that room for the next event sent when it does.
+    fn has_room(&mut self) -> Result<bool, ClosedError> {
+        if !self.enqueued.is_empty() {
+            return Ok(false);
+        }
+        let waker = futures::task::noop_waker();
+        let mut cx = Context::from_waker(&waker);
+        match self.inner.poll_ready(&mut cx) {
+            Poll::Ready(Ok(())) => Ok(true),
+            Poll::Ready(Err(_)) => Err(ClosedError),
+            Poll::Pending => Ok(false),
+        }
+    }
+
     #[cfg(test)]
     pub fn new_test() -> (Self, mpsc::Receiver<Event>) {
         Self::new_with_buffer(100, vec![])
@@ -144,6 +174,17 @@ mod test {
 
     const VALS: [&str; 2] = ["Pineapple", "Coconut"];
 
+    #[tokio::test]
+    async fn try_send_all_stops_when_full() -> Result<(), crate::Error> {
+        let (mut pipeline, receiver) = Pipeline::new_with_buffer(2, vec![]);
+        let events = (0..3).map(|i| Event::from(format!("event {}", i)));
+
+        assert!(!pipeline.try_send_all(events).await?);
+        assert_eq!(collect_ready(receiver).await.len(), 2);
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn multiple_transforms() -> Result<(), crate::Error> {
         let transform_1 = AddFields::new(
@@ -6,7 +6,9 @@ use crate::{
     event::Event,
     internal_events::{HerokuLogplexRequestReadError, HerokuLogplexRequestReceived},
     shutdown::ShutdownSignal,
-    sources::util::{add_query_parameters, ErrorMessage, HttpSource, HttpSourceAuthConfig},
+    sources::util::{
+        add_query_parameters, ErrorMessage, HttpSource, HttpSourceAuthConfig, RequestInfo,
+    },
     tls::TlsConfig,
     Pipeline,
 };
@@ -62,9 +64,11 @@ impl HttpSource for LogplexSource {
         body: Bytes,
         header_map: HeaderMap,
         query_parameters: HashMap<String, String>,
+        _request: &RequestInfo,
     ) -> Result<Vec<Event>, ErrorMessage> {
-        decode_message(body, header_map)
-            .map(|events| add_query_parameters(events, &self.query_parameters, query_parameters))
+        decode_message(body, header_map).map(|events| {
+            add_query_parameters(events, &self.query_parameters, false, query_parameters)
+        })
     }
 }
 
@@ -81,7 +85,14 @@ impl SourceConfig for LogplexConfig {
         let source = LogplexSource {
             query_parameters: self.query_parameters.clone(),
         };
-        source.run(self.address, "events", &self.tls, &self.auth, out, shutdown)
+        source.run(
+            self.address.into(),
+            "events",
+            &self.tls,
+            &self.auth,
+            out,
+            shutdown,
+        )
     }
 
     fn output_type(&self) -> DataType {
@@ -286,8 +297,8 @@ mod tests {
     ) -> u16 {
         let len = body.lines().count();
         let mut req = reqwest::Client::new().post(&format!("http://{}/events?{}", address, query));
-        if let Some(auth) = auth {
-            req = req.basic_auth(auth.username, Some(auth.password));
+        if let Some(HttpSourceAuthConfig::Basic(auth)) = auth {
+            req = req.basic_auth(auth.username.unwrap(), auth.password);
         }
         req.header("Logplex-Msg-Count", len)
             .header("Logplex-Frame-Id", "frame-foo")
@@ -306,10 +317,7 @@ mod tests {
 
         let body = r#"267 <158>1 2020-01-08T22:33:57.353034+00:00 host heroku router - at=info method=GET path="/cart_link" host=lumberjack-store.timber.io request_id=05726858-c44e-4f94-9a20-37df73be9006 fwd="73.75.38.87" dyno=web.1 connect=1ms service=22ms status=304 bytes=656 protocol=http"#;
 
-        let auth = HttpSourceAuthConfig {
-            username: "vector_user".to_owned(),
-            password: "vector_pass".to_owned(),
-        };
+        let auth = HttpSourceAuthConfig::basic("vector_user", "vector_pass");
 
         let (rx, addr) = source(
             Some(auth.clone()),
@@ -0,0 +1,121 @@
+use crate::{
+    config::log_schema,
+    event::{Event, Value},
+    sources::util::ErrorMessage,
+};
+use serde_json::{Map, Value as JsonValue};
+use std::collections::BTreeMap;
+use warp::http::StatusCode;
+
+/// Decodes a Datadog logs intake body, a JSON array of logs or a single one,
+/// with each forming an event.
+///
+/// `message` becomes the message and `hostname` the host, while `ddtags` is
+/// split into a map of tags. Other fields, such as `ddsource` and `service`,
+/// are added as they are.
+pub fn decode(body: &[u8]) -> Result<Vec<Event>, ErrorMessage> {
+    let value = serde_json::from_slice(body)
+        .map_err(|error| bad_request(format!("Invalid JSON: {}", error)))?;
+    match value {
+        JsonValue::Object(log) => Ok(vec![decode_log(log)?]),
+        JsonValue::Array(logs) => logs
+            .into_iter()
+            .map(|log| match log {
+                JsonValue::Object(log) => decode_log(log),
+                _ => Err(bad_request("Expected an object".to_owned())),
+            })
+            .collect(),
+        _ => Err(bad_request("Expected an object or array".to_owned())),
+    }
+}
+
+fn decode_log(mut fields: Map<String, JsonValue>) -> Result<Event, ErrorMessage> {
+    let mut event = Event::new_empty_log();
+    let log = event.as_mut_log();
+
+    match fields.remove("message") {
+        Some(JsonValue::String(message)) => log.insert(log_schema().message_key(), message),
+        Some(_) => return Err(bad_request("message must be a string".to_owned())),
+        None => return Err(bad_request("Missing message".to_owned())),
+    };
+    match fields.remove("hostname") {
+        Some(JsonValue::String(hostname)) => {
+            log.insert(log_schema().host_key(), hostname);
+        }
+        Some(_) => return Err(bad_request("hostname must be a string".to_owned())),
+        None => {}
+    }
+    match fields.remove("ddtags") {
+        Some(JsonValue::String(tags)) => {
+            log.insert_flat("ddtags", parse_tags(&tags));
+        }
+        Some(_) => return Err(bad_request("ddtags must be a string".to_owned())),
+        None => {}
+    }
+    for (key, value) in fields {
+        log.insert_flat(key, value);
+    }
+    Ok(event)
+}
+
+/// Splits `env:prod,team:web,canary` into a map, with tags lacking a value
+/// mapped to `true`.
+fn parse_tags(tags: &str) -> Value {
+    let tags = tags
+        .split(',')
+        .map(str::trim)
+        .filter(|tag| !tag.is_empty())
+        .map(|tag| match tag.find(':') {
+            Some(colon) => (tag[..colon].to_owned(), Value::from(&tag[colon + 1..])),
+            None => (tag.to_owned(), Value::Boolean(true)),
+        })
+        .collect::<BTreeMap<_, _>>();
+    Value::Map(tags)
+}
+
+fn bad_request(message: String) -> ErrorMessage {
+    ErrorMessage::new(
+        StatusCode::BAD_REQUEST,
+        format!("Bad Datadog logs: {}", message),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_logs() {
+        let events = decode(
+            br#"[{"message":"one","ddsource":"nginx","ddtags":"env:prod, version:1.2:3,canary",
+                  "hostname":"web-1","service":"frontend","status":"info"},
+                 {"message":"two"}]"#,
+        )
+        .unwrap();
+
+        assert_eq!(events.len(), 2);
+        let log = events[0].as_log();
+        assert_eq!(log[log_schema().message_key()], "one".into());
+        assert_eq!(log[log_schema().host_key()], "web-1".into());
+        assert_eq!(log["ddsource"], "nginx".into());
+        assert_eq!(log["service"], "frontend".into());
+        assert_eq!(log["status"], "info".into());
+        assert_eq!(log["ddtags.env"], "prod".into());
+        assert_eq!(log["ddtags.version"], "1.2:3".into());
+        assert_eq!(log["ddtags.canary"], Value::Boolean(true));
+        assert_eq!(events[1].as_log()[log_schema().message_key()], "two".into());
+    }
+
+    #[test]
+    fn rejects_invalid_logs() {
+        for body in &[
+            r#"{"hostname":"web-1"}"#,
+            r#"{"message":1}"#,
+            r#"{"message":"one","ddtags":["env:prod"]}"#,
+            r#"[{"message":"one"},"two"]"#,
+            r#""one""#,
+        ] {
+            assert!(decode(body.as_bytes()).is_err(), "{}", body);
+        }
+    }
+}
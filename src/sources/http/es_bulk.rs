@@ -0,0 +1,143 @@
+use crate::{event::Event, sources::util::ErrorMessage};
+use serde_json::{Map, Value as JsonValue};
+use warp::http::StatusCode;
+
+/// Decodes an Elasticsearch `_bulk` request body, with the document of each
+/// `index` or `create` action forming an event with the action's `_index` and
+/// `_id` as fields. `update` and `delete` actions are skipped.
+///
+/// Actions without an `_index` use the one in the path, as in `/logs/_bulk`.
+pub fn decode(body: &[u8], path: &str) -> Result<Vec<Event>, ErrorMessage> {
+    let default_index = path
+        .trim_matches('/')
+        .strip_suffix("_bulk")
+        .map(|index| index.trim_end_matches('/'))
+        .filter(|index| !index.is_empty());
+
+    let mut lines = body
+        .split(|b| *b == b'\n')
+        .enumerate()
+        .filter(|(_, line)| !line.iter().all(u8::is_ascii_whitespace));
+    let mut events = Vec::new();
+    while let Some((number, line)) = lines.next() {
+        let (action, metadata) = parse_action(line)
+            .map_err(|error| bad_request(format!("Line {}: {}", number + 1, error)))?;
+        let document = match action.as_str() {
+            "delete" => continue,
+            "update" => {
+                lines.next();
+                continue;
+            }
+            _ => match lines.next() {
+                Some((number, line)) => match serde_json::from_slice(line) {
+                    Ok(JsonValue::Object(document)) => document,
+                    _ => {
+                        return Err(bad_request(format!(
+                            "Line {}: expected a document object",
+                            number + 1
+                        )))
+                    }
+                },
+                None => {
+                    return Err(bad_request(format!(
+                        "Line {}: {} action without a document",
+                        number + 1,
+                        action
+                    )))
+                }
+            },
+        };
+
+        let mut event = Event::new_empty_log();
+        let log = event.as_mut_log();
+        for (key, value) in document {
+            log.insert_flat(key, value);
+        }
+        if let Some(JsonValue::String(index)) = metadata.get("_index") {
+            log.insert_flat("_index", index.clone());
+        } else if let Some(index) = default_index {
+            log.insert_flat("_index", index);
+        }
+        if let Some(JsonValue::String(id)) = metadata.get("_id") {
+            log.insert_flat("_id", id.clone());
+        }
+        events.push(event);
+    }
+    Ok(events)
+}
+
+/// Parses an action line, such as `{"index":{"_index":"logs"}}`.
+fn parse_action(line: &[u8]) -> Result<(String, Map<String, JsonValue>), String> {
+    let action = match serde_json::from_slice(line) {
+        Ok(JsonValue::Object(action)) => action,
+        _ => return Err("expected an action object".to_owned()),
+    };
+    let mut action = action.into_iter();
+    match (action.next(), action.next()) {
+        (Some((name, JsonValue::Object(metadata))), None)
+            if matches!(name.as_str(), "index" | "create" | "update" | "delete") =>
+        {
+            Ok((name, metadata))
+        }
+        _ => Err("expected one of index, create, update or delete".to_owned()),
+    }
+}
+
+fn bad_request(message: String) -> ErrorMessage {
+    ErrorMessage::new(
+        StatusCode::BAD_REQUEST,
+        format!("Bad bulk request: {}", message),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_actions() {
+        let body = br#"{"index":{"_index":"logs","_id":"1"}}
+{"message":"one"}
+{"delete":{"_index":"logs","_id":"2"}}
+{"create":{}}
+{"message":"two"}
+{"update":{"_id":"3"}}
+{"doc":{"message":"three"}}
+
+"#;
+        let events = decode(body, "/default/_bulk").unwrap();
+
+        assert_eq!(events.len(), 2);
+        let log = events[0].as_log();
+        assert_eq!(log["message"], "one".into());
+        assert_eq!(log["_index"], "logs".into());
+        assert_eq!(log["_id"], "1".into());
+        let log = events[1].as_log();
+        assert_eq!(log["message"], "two".into());
+        assert_eq!(log["_index"], "default".into());
+        assert!(log.get("_id").is_none());
+
+        let events = decode(
+            br#"{"index":{}}
+{"message":"one"}"#,
+            "/_bulk",
+        )
+        .unwrap();
+        assert!(events[0].as_log().get("_index").is_none());
+    }
+
+    #[test]
+    fn rejects_malformed_actions() {
+        for body in &[
+            r#"{"message":"one"}"#,
+            r#"{"upsert":{}}"#,
+            r#"{"index":{},"create":{}}"#,
+            r#"{"index":"logs"}"#,
+            r#"{"index":{}}"#,
+            "{\"index\":{}}\n[\"one\"]",
+            "not json",
+        ] {
+            assert!(decode(body.as_bytes(), "/_bulk").is_err(), "{}", body);
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use crate::{config::log_schema, event::Event, sources::util::ErrorMessage};
+use chrono::{TimeZone, Utc};
+use serde_json::{Map, Value as JsonValue};
+use warp::http::StatusCode;
+
+/// Decodes a GELF message, or an array of them.
+///
+/// `short_message` becomes the message and `host` the host, both being required,
+/// while the numeric `timestamp` becomes the timestamp. Additional fields have
+/// their leading underscore removed, and `version` is dropped.
+pub fn decode(body: &[u8]) -> Result<Vec<Event>, ErrorMessage> {
+    let value = serde_json::from_slice(body)
+        .map_err(|error| bad_request(format!("Invalid JSON: {}", error)))?;
+    match value {
+        JsonValue::Object(message) => Ok(vec![decode_message(message)?]),
+        JsonValue::Array(messages) => messages
+            .into_iter()
+            .map(|message| match message {
+                JsonValue::Object(message) => decode_message(message),
+                _ => Err(bad_request("Expected an object".to_owned())),
+            })
+            .collect(),
+        _ => Err(bad_request("Expected an object or array".to_owned())),
+    }
+}
+
+fn decode_message(mut message: Map<String, JsonValue>) -> Result<Event, ErrorMessage> {
+    let short_message = match message.remove("short_message") {
+        Some(JsonValue::String(short_message)) => short_message,
+        Some(_) => return Err(bad_request("short_message must be a string".to_owned())),
+        None => return Err(bad_request("Missing short_message".to_owned())),
+    };
+    let host = match message.remove("host") {
+        Some(JsonValue::String(host)) => host,
+        Some(_) => return Err(bad_request("host must be a string".to_owned())),
+        None => return Err(bad_request("Missing host".to_owned())),
+    };
+    let timestamp = match message.remove("timestamp") {
+        Some(JsonValue::Number(timestamp)) => {
+            let timestamp = timestamp
+                .as_f64()
+                .ok_or_else(|| bad_request("timestamp out of range".to_owned()))?;
+            let timestamp = Utc
+                .timestamp_opt(
+                    timestamp.floor() as i64,
+                    (timestamp.fract() * 1_000_000_000.0).round() as u32,
+                )
+                .single()
+                .ok_or_else(|| bad_request("timestamp out of range".to_owned()))?;
+            Some(timestamp)
+        }
+        Some(_) => return Err(bad_request("timestamp must be a number".to_owned())),
+        None => None,
+    };
+    message.remove("version");
+
+    let mut event = Event::new_empty_log();
+    let log = event.as_mut_log();
+    log.insert(log_schema().message_key(), short_message);
+    log.insert(log_schema().host_key(), host);
+    if let Some(timestamp) = timestamp {
+        log.insert(log_schema().timestamp_key(), timestamp);
+    }
+    for (key, value) in message {
+        let key = match key.strip_prefix('_') {
+            Some(key) => key.to_owned(),
+            None => key,
+        };
+        log.insert_flat(key, value);
+    }
+    Ok(event)
+}
+
+fn bad_request(message: String) -> ErrorMessage {
+    ErrorMessage::new(StatusCode::BAD_REQUEST, format!("Bad GELF: {}", message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::Value;
+
+    #[test]
+    fn decodes_message() {
+        let events = decode(
+            br#"{"version":"1.1","host":"example.org","short_message":"A short message",
+                "full_message":"Backtrace here","timestamp":1612325106.125,"level":1,
+                "_user_id":9001,"_some_info":"foo"}"#,
+        )
+        .unwrap();
+
+        assert_eq!(events.len(), 1);
+        let log = events[0].as_log();
+        assert_eq!(log[log_schema().message_key()], "A short message".into());
+        assert_eq!(log[log_schema().host_key()], "example.org".into());
+        assert_eq!(
+            log[log_schema().timestamp_key()],
+            Utc.ymd(2021, 2, 3).and_hms_milli(4, 5, 6, 125).into()
+        );
+        assert_eq!(log["full_message"], "Backtrace here".into());
+        assert_eq!(log["level"], Value::Integer(1));
+        assert_eq!(log["user_id"], Value::Integer(9001));
+        assert_eq!(log["some_info"], "foo".into());
+        assert!(log.get("version").is_none());
+    }
+
+    #[test]
+    fn requires_fields() {
+        assert!(decode(br#"{"host":"example.org"}"#).is_err());
+        assert!(decode(br#"{"short_message":"hi"}"#).is_err());
+        assert!(decode(br#"{"host":"example.org","short_message":1}"#).is_err());
+        assert!(decode(br#"[{"host":"a","short_message":"hi"},"hi"]"#).is_err());
+        assert_eq!(
+            decode(br#"[{"host":"a","short_message":"hi"},{"host":"b","short_message":"ho"}]"#)
+                .unwrap()
+                .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn rejects_out_of_range_timestamp() {
+        let error = decode(br#"{"host":"a","short_message":"hi","timestamp":1e300}"#).unwrap_err();
+        assert_eq!(error.code(), 400);
+    }
+}
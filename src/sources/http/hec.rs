@@ -0,0 +1,143 @@
+use crate::{config::log_schema, event::Event, sources::util::ErrorMessage};
+use chrono::{TimeZone, Utc};
+use serde_json::{Map, Value as JsonValue};
+use warp::http::StatusCode;
+
+/// The response Splunk HTTP Event Collector clients expect on success.
+pub const SUCCESS: &str = r#"{"text":"Success","code":0}"#;
+
+// The same fields as the `splunk_hec` source
+const CHANNEL: &str = "splunk_channel";
+const INDEX: &str = "splunk_index";
+const SOURCE: &str = "splunk_source";
+const SOURCETYPE: &str = "splunk_sourcetype";
+
+/// Decodes a Splunk HTTP Event Collector body of concatenated JSON envelopes,
+/// each forming an event.
+pub fn decode(body: &[u8]) -> Result<Vec<Event>, ErrorMessage> {
+    serde_json::Deserializer::from_slice(body)
+        .into_iter::<JsonValue>()
+        .enumerate()
+        .map(|(index, envelope)| {
+            match envelope {
+                Ok(JsonValue::Object(envelope)) => decode_envelope(envelope),
+                Ok(_) => Err("expected an object".to_owned()),
+                Err(error) => Err(format!("invalid JSON: {}", error)),
+            }
+            .map_err(|error| bad_request(format!("Event {}: {}", index, error)))
+        })
+        .collect()
+}
+
+fn decode_envelope(mut envelope: Map<String, JsonValue>) -> Result<Event, String> {
+    let mut event = Event::new_empty_log();
+    let log = event.as_mut_log();
+
+    match envelope.remove("event") {
+        Some(JsonValue::String(message)) if !message.is_empty() => {
+            log.insert(log_schema().message_key(), message);
+        }
+        Some(JsonValue::Object(fields)) if !fields.is_empty() => {
+            for (key, value) in fields {
+                log.insert_flat(key, value);
+            }
+        }
+        Some(JsonValue::String(_)) | Some(JsonValue::Object(_)) => {
+            return Err("event is empty".to_owned())
+        }
+        Some(_) => return Err("event must be a string or object".to_owned()),
+        None => return Err("event is required".to_owned()),
+    }
+
+    let timestamp = match envelope.remove("time") {
+        Some(JsonValue::Number(time)) => time.as_f64(),
+        Some(JsonValue::String(time)) => Some(
+            time.parse::<f64>()
+                .map_err(|_| format!("invalid time {:?}", time))?,
+        ),
+        Some(_) => return Err("time must be a number".to_owned()),
+        None => None,
+    };
+    if let Some(time) = timestamp {
+        let timestamp = Utc
+            .timestamp_opt(time.floor() as i64, (time.fract() * 1e9) as u32)
+            .single()
+            .ok_or_else(|| format!("invalid time {}", time))?;
+        log.insert(log_schema().timestamp_key(), timestamp);
+    }
+
+    if let Some(JsonValue::Object(fields)) = envelope.remove("fields") {
+        for (key, value) in fields {
+            log.insert_flat(key, value);
+        }
+    }
+    if let Some(JsonValue::String(host)) = envelope.remove("host") {
+        log.insert(log_schema().host_key(), host);
+    }
+    for (field, key) in &[
+        ("channel", CHANNEL),
+        ("index", INDEX),
+        ("source", SOURCE),
+        ("sourcetype", SOURCETYPE),
+    ] {
+        if let Some(JsonValue::String(value)) = envelope.remove(*field) {
+            log.insert(*key, value);
+        }
+    }
+    Ok(event)
+}
+
+fn bad_request(message: String) -> ErrorMessage {
+    ErrorMessage::new(
+        StatusCode::BAD_REQUEST,
+        format!("Bad HEC request: {}", message),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_envelopes() {
+        let events = decode(
+            br#"{"event":"one","time":1612325106.5,"host":"a","sourcetype":"app","index":"main"}
+                {"event":{"level":"info","user":"b"},"time":"1612325107","fields":{"env":"prod"}}"#,
+        )
+        .unwrap();
+
+        assert_eq!(events.len(), 2);
+        let log = events[0].as_log();
+        assert_eq!(log[log_schema().message_key()], "one".into());
+        assert_eq!(log[log_schema().host_key()], "a".into());
+        assert_eq!(log[SOURCETYPE], "app".into());
+        assert_eq!(log[INDEX], "main".into());
+        assert_eq!(
+            log[log_schema().timestamp_key()],
+            Utc.ymd(2021, 2, 3).and_hms_milli(4, 5, 6, 500).into()
+        );
+        let log = events[1].as_log();
+        assert_eq!(log["level"], "info".into());
+        assert_eq!(log["user"], "b".into());
+        assert_eq!(log["env"], "prod".into());
+        assert_eq!(
+            log[log_schema().timestamp_key()],
+            Utc.ymd(2021, 2, 3).and_hms(4, 5, 7).into()
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_envelopes() {
+        for body in &[
+            r#"{"time":1}"#,
+            r#"{"event":""}"#,
+            r#"{"event":1}"#,
+            r#"{"event":"one","time":"yesterday"}"#,
+            r#"{"event":"one","time":1e13}"#,
+            r#"{"event":"one"} ["two"]"#,
+            r#"{"event":"one"} {"event""#,
+        ] {
+            assert!(decode(body.as_bytes()).is_err(), "{}", body);
+        }
+    }
+}
@@ -0,0 +1,271 @@
+use crate::{
+    event::{Event, Metric, MetricKind, MetricValue},
+    sources::util::ErrorMessage,
+};
+use chrono::{TimeZone, Utc};
+use std::collections::{BTreeMap, HashMap};
+use warp::http::StatusCode;
+
+/// Decodes a body in the InfluxDB line protocol. Each field of a line becomes a
+/// gauge named after the field, with the measurement as its namespace. String
+/// fields can't be metric values, so are skipped.
+///
+/// The `db` query parameter is added as a tag, and `precision` gives the unit
+/// of timestamps, which are otherwise nanoseconds.
+pub fn decode(
+    body: &[u8],
+    query_parameters: &HashMap<String, String>,
+) -> Result<Vec<Event>, ErrorMessage> {
+    let nanoseconds = match query_parameters.get("precision").map(String::as_str) {
+        None | Some("n") | Some("ns") => 1,
+        Some("u") | Some("us") => 1_000,
+        Some("ms") => 1_000_000,
+        Some("s") => 1_000_000_000,
+        Some(precision) => return Err(bad_request(format!("Unknown precision {}", precision))),
+    };
+    let body = std::str::from_utf8(body)
+        .map_err(|error| bad_request(format!("Body is not valid UTF-8: {}", error)))?;
+    let database = query_parameters.get("db");
+
+    let mut events = Vec::new();
+    for (number, line) in body.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let point = parse_line(line)
+            .map_err(|error| bad_request(format!("Line {}: {}", number + 1, error)))?;
+
+        let mut tags = point.tags;
+        if let Some(database) = database {
+            tags.insert("db".to_owned(), database.clone());
+        }
+        let timestamp = match point.timestamp {
+            Some(timestamp) => {
+                let timestamp = timestamp.checked_mul(nanoseconds).ok_or_else(|| {
+                    bad_request(format!("Line {}: timestamp out of range", number + 1))
+                })?;
+                Utc.timestamp(
+                    timestamp.div_euclid(1_000_000_000),
+                    timestamp.rem_euclid(1_000_000_000) as u32,
+                )
+            }
+            None => Utc::now(),
+        };
+        for (field, value) in point.fields {
+            events.push(
+                Metric::new(field, MetricKind::Absolute, MetricValue::Gauge { value })
+                    .with_namespace(Some(point.measurement.clone()))
+                    .with_tags(Some(tags.clone()))
+                    .with_timestamp(Some(timestamp))
+                    .into(),
+            );
+        }
+    }
+    Ok(events)
+}
+
+fn bad_request(message: String) -> ErrorMessage {
+    ErrorMessage::new(
+        StatusCode::BAD_REQUEST,
+        format!("Bad line protocol: {}", message),
+    )
+}
+
+#[derive(Debug, PartialEq)]
+struct Point {
+    measurement: String,
+    tags: BTreeMap<String, String>,
+    fields: Vec<(String, f64)>,
+    timestamp: Option<i64>,
+}
+
+fn parse_line(line: &str) -> Result<Point, String> {
+    let sections = split_unescaped(line, b' ', true);
+    let (series, fields, timestamp) = match sections.as_slice() {
+        [series, fields] => (series, fields, None),
+        [series, fields, timestamp] => (series, fields, Some(timestamp)),
+        _ => return Err("expected a measurement, fields and an optional timestamp".to_owned()),
+    };
+
+    let mut series = split_unescaped(series, b',', false).into_iter();
+    let measurement = unescape(series.next().unwrap_or_default());
+    if measurement.is_empty() {
+        return Err("missing measurement".to_owned());
+    }
+    let tags = series
+        .map(|tag| {
+            let (key, value) = key_value(tag)?;
+            Ok((unescape(key), unescape(value)))
+        })
+        .collect::<Result<_, String>>()?;
+
+    let mut parsed = Vec::new();
+    for field in split_unescaped(fields, b',', true) {
+        let (key, value) = key_value(field)?;
+        if let Some(value) = parse_field_value(value)? {
+            parsed.push((unescape(key), value));
+        }
+    }
+
+    let timestamp = timestamp
+        .map(|timestamp| {
+            timestamp
+                .parse()
+                .map_err(|_| format!("invalid timestamp {:?}", timestamp))
+        })
+        .transpose()?;
+
+    Ok(Point {
+        measurement,
+        tags,
+        fields: parsed,
+        timestamp,
+    })
+}
+
+/// Splits at the first unescaped `=`, as values may be strings containing it.
+fn key_value(pair: &str) -> Result<(&str, &str), String> {
+    let key = split_unescaped(pair, b'=', false)[0];
+    match pair.get(key.len() + 1..) {
+        Some(value) if !key.is_empty() && !value.is_empty() => Ok((key, value)),
+        _ => Err(format!("expected key=value, got {:?}", pair)),
+    }
+}
+
+/// Parses a field value, or returns `None` for strings.
+fn parse_field_value(value: &str) -> Result<Option<f64>, String> {
+    let invalid = || format!("invalid field value {:?}", value);
+    if value.starts_with('"') {
+        return if value.len() > 1 && value.ends_with('"') {
+            Ok(None)
+        } else {
+            Err(invalid())
+        };
+    }
+    let number = match value {
+        "t" | "T" | "true" | "True" | "TRUE" => 1.0,
+        "f" | "F" | "false" | "False" | "FALSE" => 0.0,
+        _ if value.ends_with('i') => value[..value.len() - 1]
+            .parse::<i64>()
+            .map_err(|_| invalid())? as f64,
+        _ if value.ends_with('u') => value[..value.len() - 1]
+            .parse::<u64>()
+            .map_err(|_| invalid())? as f64,
+        _ => value.parse().map_err(|_| invalid())?,
+    };
+    Ok(Some(number))
+}
+
+/// Splits on `delimiter` where it isn't escaped by a backslash or, if `quoted`,
+/// within a double quoted string.
+fn split_unescaped(s: &str, delimiter: u8, quoted: bool) -> Vec<&str> {
+    let bytes = s.as_bytes();
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut in_quotes = false;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 1,
+            b'"' if quoted => in_quotes = !in_quotes,
+            byte if byte == delimiter && !in_quotes => {
+                parts.push(&s[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    parts.push(&s[start..]);
+    parts
+}
+
+fn unescape(s: &str) -> String {
+    let mut unescaped = String::with_capacity(s.len());
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('\\', Some(',')) | ('\\', Some('=')) | ('\\', Some(' ')) | ('\\', Some('\\')) => {
+                unescaped.extend(chars.next())
+            }
+            _ => unescaped.push(c),
+        }
+    }
+    unescaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_lines() {
+        assert_eq!(
+            parse_line(
+                r#"cpu\ load,host=a\,b,region=us usage=0.5,cores=4i,up=t,name="x=y z" 1612325106000000000"#
+            ),
+            Ok(Point {
+                measurement: "cpu load".to_owned(),
+                tags: vec![
+                    ("host".to_owned(), "a,b".to_owned()),
+                    ("region".to_owned(), "us".to_owned())
+                ]
+                .into_iter()
+                .collect(),
+                fields: vec![
+                    ("usage".to_owned(), 0.5),
+                    ("cores".to_owned(), 4.0),
+                    ("up".to_owned(), 1.0)
+                ],
+                timestamp: Some(1612325106000000000),
+            })
+        );
+        assert_eq!(
+            parse_line("disk free=10u").unwrap().fields,
+            vec![("free".to_owned(), 10.0)]
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_lines() {
+        for line in &[
+            "cpu",
+            "cpu usage",
+            "cpu usage=",
+            "cpu usage=high",
+            "cpu,host usage=1",
+            "cpu usage=1 soon",
+            r#"cpu name="unterminated"#,
+            ",host=a usage=1",
+        ] {
+            assert!(parse_line(line).is_err(), "{}", line);
+        }
+    }
+
+    #[test]
+    fn decodes_with_precision_and_database() {
+        let query = vec![
+            ("db".to_owned(), "telegraf".to_owned()),
+            ("precision".to_owned(), "s".to_owned()),
+        ]
+        .into_iter()
+        .collect();
+        let events = decode(b"# comment\n\ncpu usage=1 1612325106\r\n", &query).unwrap();
+
+        assert_eq!(events.len(), 1);
+        let metric = events[0].as_metric();
+        assert_eq!(metric.namespace(), Some("cpu"));
+        assert_eq!(metric.name(), "usage");
+        assert_eq!(metric.tags().unwrap()["db"], "telegraf");
+        assert_eq!(
+            metric.data.timestamp,
+            Some(Utc.ymd(2021, 2, 3).and_hms(4, 5, 6))
+        );
+
+        let query = vec![("precision".to_owned(), "d".to_owned())]
+            .into_iter()
+            .collect();
+        assert!(decode(b"cpu usage=1", &query).is_err());
+    }
+}
@@ -0,0 +1,167 @@
+use crate::sources::util::{ClientInfo, ErrorMessage};
+use cidr_utils::cidr::IpCidr;
+use snafu::Snafu;
+use std::{net::IpAddr, str::FromStr};
+use warp::http::{HeaderMap, StatusCode};
+
+#[derive(Debug, Snafu)]
+pub enum BuildError {
+    #[snafu(display("Invalid CIDR {:?} in {}: {}", cidr, option, message))]
+    InvalidCidr {
+        option: &'static str,
+        cidr: String,
+        message: String,
+    },
+}
+
+/// Admits or rejects requests based on the address of the client.
+#[derive(Clone, Debug, Default)]
+pub struct IpFilter {
+    allowed: Vec<IpCidr>,
+    denied: Vec<IpCidr>,
+    trusted_proxies: Vec<IpCidr>,
+}
+
+impl IpFilter {
+    pub fn new(
+        allowed: &[String],
+        denied: &[String],
+        trusted_proxies: &[String],
+    ) -> Result<Self, BuildError> {
+        Ok(Self {
+            allowed: parse_cidrs("allowed_ips", allowed)?,
+            denied: parse_cidrs("denied_ips", denied)?,
+            trusted_proxies: parse_cidrs("trusted_proxies", trusted_proxies)?,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.allowed.is_empty() && self.denied.is_empty()
+    }
+
+    pub fn check(&self, client: &ClientInfo, headers: &HeaderMap) -> Result<(), ErrorMessage> {
+        if self.is_empty() {
+            return Ok(());
+        }
+
+        let ip = client
+            .remote_addr
+            .map(|addr| self.client_ip(addr.ip(), headers))
+            .ok_or_else(|| forbidden("Unknown client address"))?;
+        if contains(&self.denied, ip) || (!self.allowed.is_empty() && !contains(&self.allowed, ip))
+        {
+            return Err(forbidden(&format!("Client address {} is not allowed", ip)));
+        }
+        Ok(())
+    }
+
+    /// The address of the client, which when the peer is a trusted proxy is the
+    /// rightmost address in `X-Forwarded-For` not belonging to a trusted proxy.
+    pub fn client_ip(&self, peer: IpAddr, headers: &HeaderMap) -> IpAddr {
+        if !contains(&self.trusted_proxies, peer) {
+            return peer;
+        }
+
+        let forwarded = headers
+            .get_all("x-forwarded-for")
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .filter_map(|ip| IpAddr::from_str(ip.trim()).ok())
+            .collect::<Vec<_>>();
+        forwarded
+            .iter()
+            .rev()
+            .find(|ip| !contains(&self.trusted_proxies, **ip))
+            .or_else(|| forwarded.first())
+            .copied()
+            .unwrap_or(peer)
+    }
+}
+
+fn parse_cidrs(option: &'static str, cidrs: &[String]) -> Result<Vec<IpCidr>, BuildError> {
+    cidrs
+        .iter()
+        .map(|cidr| {
+            IpCidr::from_str(cidr).map_err(|error| BuildError::InvalidCidr {
+                option,
+                cidr: cidr.clone(),
+                message: error.to_string(),
+            })
+        })
+        .collect()
+}
+
+fn contains(cidrs: &[IpCidr], ip: IpAddr) -> bool {
+    cidrs.iter().any(|cidr| cidr.contains(ip))
+}
+
+fn forbidden(message: &str) -> ErrorMessage {
+    ErrorMessage::new(StatusCode::FORBIDDEN, message.to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn client(ip: &str) -> ClientInfo {
+        ClientInfo {
+            remote_addr: Some((IpAddr::from_str(ip).unwrap(), 9000).into()),
+            ..ClientInfo::default()
+        }
+    }
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn rejects_invalid_cidr() {
+        assert!(IpFilter::new(&strings(&["10.0.0.0/33"]), &[], &[]).is_err());
+    }
+
+    #[test]
+    fn allows_and_denies() {
+        let filter =
+            IpFilter::new(&strings(&["10.0.0.0/8"]), &strings(&["10.1.0.0/16"]), &[]).unwrap();
+        let headers = HeaderMap::new();
+
+        assert!(filter.check(&client("10.2.3.4"), &headers).is_ok());
+        assert_eq!(
+            filter
+                .check(&client("10.1.3.4"), &headers)
+                .unwrap_err()
+                .code(),
+            403
+        );
+        assert_eq!(
+            filter
+                .check(&client("192.168.0.1"), &headers)
+                .unwrap_err()
+                .code(),
+            403
+        );
+    }
+
+    #[test]
+    fn uses_forwarded_address_from_trusted_proxies() {
+        let filter = IpFilter::new(
+            &[],
+            &strings(&["203.0.113.7/32"]),
+            &strings(&["10.0.0.0/8"]),
+        )
+        .unwrap();
+        let mut headers = HeaderMap::new();
+        headers.insert("X-Forwarded-For", "203.0.113.7, 10.0.0.2".parse().unwrap());
+
+        assert_eq!(
+            filter
+                .check(&client("10.0.0.1"), &headers)
+                .unwrap_err()
+                .code(),
+            403
+        );
+        // Untrusted peers can't spoof their address
+        assert!(filter.check(&client("198.51.100.1"), &headers).is_ok());
+    }
+}
@@ -0,0 +1,132 @@
+use crate::{internal_events::HTTPInvalidEventsDropped, sources::util::ErrorMessage};
+use jsonschema::JSONSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use snafu::{ResultExt, Snafu};
+use std::path::{Path, PathBuf};
+use warp::http::StatusCode;
+
+#[derive(Debug, Snafu)]
+pub enum BuildError {
+    #[snafu(display("Could not read JSON schema {:?}: {}", path, source))]
+    ReadSchema {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[snafu(display("Could not parse JSON schema {:?}: {}", path, source))]
+    ParseSchema {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+    #[snafu(display("Invalid JSON schema {:?}: {}", path, message))]
+    CompileSchema { path: PathBuf, message: String },
+}
+
+/// What happens to a batch of objects when some of them fail validation.
+#[derive(Deserialize, Serialize, Debug, Eq, PartialEq, Clone, Copy, Derivative)]
+#[serde(rename_all = "snake_case")]
+#[derivative(Default)]
+pub enum SchemaMode {
+    /// The whole request is rejected.
+    #[derivative(Default)]
+    Reject,
+    /// Only the invalid objects are dropped.
+    DropInvalid,
+}
+
+pub struct JsonSchemaValidator {
+    schema: JSONSchema,
+    mode: SchemaMode,
+}
+
+impl JsonSchemaValidator {
+    pub fn load(path: &Path, mode: SchemaMode) -> Result<Self, BuildError> {
+        let schema = std::fs::read(path).context(ReadSchema { path })?;
+        let schema = serde_json::from_slice(&schema).context(ParseSchema { path })?;
+        let schema = JSONSchema::compile(&schema).map_err(|error| BuildError::CompileSchema {
+            path: path.into(),
+            message: error.to_string(),
+        })?;
+        Ok(Self { schema, mode })
+    }
+
+    /// Validates a batch of objects, dropping the invalid ones or rejecting the
+    /// batch with a 400 response that names the first failing field.
+    pub fn validate(&self, values: Vec<JsonValue>) -> Result<Vec<JsonValue>, ErrorMessage> {
+        let count = values.len();
+        let mut valid = Vec::with_capacity(count);
+        for value in values {
+            match (self.check(&value), self.mode) {
+                (Ok(()), _) => valid.push(value),
+                (Err(error), SchemaMode::Reject) => return Err(error),
+                (Err(_), SchemaMode::DropInvalid) => (),
+            }
+        }
+
+        if valid.len() < count {
+            emit!(HTTPInvalidEventsDropped {
+                count: count - valid.len(),
+                reason: "failed JSON schema validation",
+            });
+        }
+        Ok(valid)
+    }
+
+    fn check(&self, value: &JsonValue) -> Result<(), ErrorMessage> {
+        let mut errors = match self.schema.validate(value) {
+            Ok(()) => return Ok(()),
+            Err(errors) => errors,
+        };
+        let message = match errors.next() {
+            Some(error) => format!(
+                "Field {:?} failed JSON schema validation: {}",
+                error.instance_path.to_string(),
+                error
+            ),
+            None => "Failed JSON schema validation".to_owned(),
+        };
+        Err(ErrorMessage::new(StatusCode::BAD_REQUEST, message))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::io::Write;
+
+    fn validator(mode: SchemaMode) -> JsonSchemaValidator {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        write!(
+            file,
+            "{}",
+            json!({
+                "type": "object",
+                "properties": {"level": {"enum": ["info", "error"]}},
+                "required": ["level"]
+            })
+        )
+        .unwrap();
+        JsonSchemaValidator::load(file.path(), mode).unwrap()
+    }
+
+    #[test]
+    fn rejects_invalid_batch() {
+        let error = validator(SchemaMode::Reject)
+            .validate(vec![json!({"level": "info"}), json!({"level": "debug"})])
+            .unwrap_err();
+        assert_eq!(error.code(), 400);
+    }
+
+    #[test]
+    fn drops_invalid_objects() {
+        let valid = validator(SchemaMode::DropInvalid)
+            .validate(vec![
+                json!({"level": "info"}),
+                json!({"level": "debug"}),
+                json!({"message": "no level"}),
+            ])
+            .unwrap();
+        assert_eq!(valid, vec![json!({"level": "info"})]);
+    }
+}
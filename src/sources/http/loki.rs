@@ -0,0 +1,115 @@
+use crate::{config::log_schema, event::Event, sources::util::ErrorMessage};
+use chrono::{TimeZone, Utc};
+use serde::Deserialize;
+use serde_json::Value as JsonValue;
+use std::collections::BTreeMap;
+use warp::http::StatusCode;
+
+#[derive(Deserialize)]
+struct PushRequest {
+    streams: Vec<Stream>,
+}
+
+#[derive(Deserialize)]
+struct Stream {
+    #[serde(default)]
+    stream: BTreeMap<String, String>,
+    values: Vec<Vec<JsonValue>>,
+}
+
+/// Decodes a JSON Loki push request, with each entry of each stream forming
+/// an event with the stream's labels as fields.
+pub fn decode(body: &[u8]) -> Result<Vec<Event>, ErrorMessage> {
+    let request: PushRequest = serde_json::from_slice(body)
+        .map_err(|error| bad_request(format!("Invalid push request: {}", error)))?;
+
+    let mut events = Vec::new();
+    for stream in request.streams {
+        for entry in stream.values {
+            let (timestamp, line, metadata) = match entry.as_slice() {
+                [JsonValue::String(timestamp), JsonValue::String(line)] => (timestamp, line, None),
+                [JsonValue::String(timestamp), JsonValue::String(line), JsonValue::Object(metadata)] => {
+                    (timestamp, line, Some(metadata))
+                }
+                _ => {
+                    return Err(bad_request(
+                        "Expected entries of a timestamp and a line".to_owned(),
+                    ))
+                }
+            };
+            let timestamp = timestamp
+                .parse::<i64>()
+                .map_err(|_| bad_request(format!("Invalid timestamp {:?}", timestamp)))?;
+
+            let mut event = Event::new_empty_log();
+            let log = event.as_mut_log();
+            log.insert(log_schema().message_key(), line.clone());
+            log.insert(
+                log_schema().timestamp_key(),
+                Utc.timestamp(
+                    timestamp.div_euclid(1_000_000_000),
+                    timestamp.rem_euclid(1_000_000_000) as u32,
+                ),
+            );
+            for (key, value) in stream.stream.iter() {
+                log.insert_flat(key.clone(), value.clone());
+            }
+            for (key, value) in metadata.into_iter().flatten() {
+                log.insert_flat(key.clone(), value.clone());
+            }
+            events.push(event);
+        }
+    }
+    Ok(events)
+}
+
+fn bad_request(message: String) -> ErrorMessage {
+    ErrorMessage::new(
+        StatusCode::BAD_REQUEST,
+        format!("Bad Loki push: {}", message),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_streams() {
+        let events = decode(
+            br#"{"streams":[
+                {"stream":{"job":"app","env":"prod"},"values":[
+                    ["1612325106000000001","one"],
+                    ["1612325107000000000","two",{"trace_id":"abc"}]]},
+                {"stream":{"job":"db"},"values":[["1612325108000000000","three"]]}]}"#,
+        )
+        .unwrap();
+
+        assert_eq!(events.len(), 3);
+        let log = events[0].as_log();
+        assert_eq!(log[log_schema().message_key()], "one".into());
+        assert_eq!(log["job"], "app".into());
+        assert_eq!(log["env"], "prod".into());
+        assert_eq!(
+            log[log_schema().timestamp_key()],
+            Utc.ymd(2021, 2, 3).and_hms_nano(4, 5, 6, 1).into()
+        );
+        assert_eq!(events[1].as_log()["trace_id"], "abc".into());
+        assert_eq!(events[2].as_log()["job"], "db".into());
+        assert!(events[2].as_log().get("env").is_none());
+    }
+
+    #[test]
+    fn rejects_malformed_streams() {
+        for body in &[
+            r#"{"streams":{}}"#,
+            r#"{"streams":[{"stream":{}}]}"#,
+            r#"{"streams":[{"values":[["1612325106000000000"]]}]}"#,
+            r#"{"streams":[{"values":[[1612325106000000000,"one"]]}]}"#,
+            r#"{"streams":[{"values":[["yesterday","one"]]}]}"#,
+            r#"{"streams":[{"stream":{"job":1},"values":[]}]}"#,
+        ] {
+            assert!(decode(body.as_bytes()).is_err(), "{}", body);
+        }
+    }
+}
@@ -0,0 +1,5741 @@
+use crate::{
+    config::{
+        log_schema, DataType, GenerateConfig, GlobalOptions, Resource, SourceConfig,
+        SourceDescription,
+    },
+    event::{Event, Metric, MetricKind, MetricValue, Value},
+    internal_events::{HTTPInvalidEventsDropped, HTTPTimestampParseError},
+    shutdown::ShutdownSignal,
+    sources::util::{
+        add_query_parameters, ClientInfo, CorsConfig, ErrorMessage, HttpListenAddr, HttpSource,
+        HttpSourceAuth, HttpSourceAuthConfig, IdempotencyConfig, RequestInfo, ServerSentEvent,
+    },
+    tls::TlsConfig,
+    Pipeline,
+};
+use avro_rs::types::Value as AvroValue;
+use bytes::Bytes;
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use regex::{Regex, RegexBuilder};
+use rmpv::Value as MsgPackValue;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value as JsonValue};
+use snafu::{ResultExt, Snafu};
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap},
+    convert::TryFrom,
+    net::SocketAddr,
+    path::PathBuf,
+    str::FromStr,
+    sync::Arc,
+    time::Duration,
+};
+
+use warp::{
+    http::{
+        header::{
+            GetAll, HeaderName, CONNECTION, CONTENT_LENGTH, CONTENT_TYPE, COOKIE, TRANSFER_ENCODING,
+        },
+        HeaderMap, HeaderValue, Method, StatusCode,
+    },
+    reply::Response,
+};
+use xml::reader::{EventReader, ParserConfig, XmlEvent};
+
+mod datadog;
+mod es_bulk;
+mod gelf;
+mod hec;
+mod influx;
+mod ip_filter;
+mod json_schema;
+mod loki;
+mod multipart;
+mod openapi;
+mod path_template;
+mod protobuf;
+mod rate_limit;
+mod remote_write;
+
+use ip_filter::IpFilter;
+use json_schema::{JsonSchemaValidator, SchemaMode};
+use openapi::OpenApiValidator;
+use path_template::PathTemplate;
+use protobuf::ProtobufDecoder;
+use rate_limit::{RateLimitConfig, RateLimiter};
+
+#[derive(Debug, Snafu)]
+enum BuildError {
+    #[snafu(display(
+        "proto_descriptor_path and proto_message_type are required for the protobuf encoding"
+    ))]
+    MissingProtobufDescriptor,
+    #[snafu(display("Could not load protobuf descriptor: {}", source))]
+    LoadProtobufDescriptor { source: protobuf::BuildError },
+    #[snafu(display("{}", source))]
+    LoadJsonSchema { source: json_schema::BuildError },
+    #[snafu(display("{}", source))]
+    LoadOpenApi { source: openapi::BuildError },
+    #[snafu(display("Unknown charset {:?}", charset))]
+    UnknownCharset { charset: String },
+    #[snafu(display("default_encoding can't be auto"))]
+    AutoDefaultEncoding,
+    #[snafu(display(
+        "default_encoding can't be metric, influx_line, es_bulk or prometheus_remote_write"
+    ))]
+    UnsupportedDefaultEncoding,
+    #[snafu(display("framing_delimiter must be a single byte, got {:?}", delimiter))]
+    MultiByteFramingDelimiter { delimiter: char },
+    #[snafu(display("json_pointer must be empty or start with a /, got {:?}", pointer))]
+    InvalidJsonPointer { pointer: String },
+    #[snafu(display("response_code must be a 2xx status code, got {}", code))]
+    InvalidResponseCode { code: u16 },
+    #[snafu(display("Invalid response_content_type {:?}: {}", content_type, source))]
+    InvalidResponseContentType {
+        content_type: String,
+        source: warp::http::header::InvalidHeaderValue,
+    },
+    #[snafu(display("header_patterns {:?} is not a valid regex: {}", pattern, source))]
+    InvalidHeaderPattern {
+        pattern: String,
+        source: regex::Error,
+    },
+    #[snafu(display("Invalid response header {:?}: {}", name, message))]
+    InvalidResponseHeader { name: String, message: String },
+    #[snafu(display("Invalid echo_request_id_header {:?}: {}", name, source))]
+    InvalidRequestIdHeader {
+        name: String,
+        source: warp::http::header::InvalidHeaderName,
+    },
+    #[snafu(display("Invalid HTTP method {:?}", method))]
+    InvalidMethod { method: String },
+    #[snafu(display("{}", message))]
+    InvalidPathTemplate { message: String },
+    #[snafu(display("path_template can't be used with routes"))]
+    PathTemplateWithRoutes,
+    #[snafu(display("Invalid auth for route {:?}: {}", path, message))]
+    InvalidRouteAuth { path: String, message: String },
+    #[snafu(display("{}", source))]
+    InvalidIpFilter { source: ip_filter::BuildError },
+    #[snafu(display("tls can't be used with a Unix socket address"))]
+    UnixSocketTls,
+    #[snafu(display("verify_client requires tls to be enabled"))]
+    VerifyClientWithoutTls,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct SimpleHttpConfig {
+    address: HttpListenAddr,
+    #[serde(default)]
+    encoding: Encoding,
+    #[serde(default)]
+    default_encoding: Encoding,
+    framing_delimiter: Option<char>,
+    #[serde(default)]
+    invalid_utf8: InvalidUtf8,
+    #[serde(default)]
+    on_empty_body: EmptyBodyMode,
+    #[serde(default)]
+    body_base64: bool,
+    charset: Option<String>,
+    csv_headers: Option<Vec<String>>,
+    body_key: Option<String>,
+    raw_body_key: Option<String>,
+    json_pointer: Option<String>,
+    json_schema_path: Option<PathBuf>,
+    #[serde(default)]
+    schema_mode: SchemaMode,
+    openapi_path: Option<PathBuf>,
+    #[serde(default)]
+    on_array_error: ArrayErrorMode,
+    #[serde(default)]
+    flatten: bool,
+    #[serde(default = "default_flatten_separator")]
+    flatten_separator: String,
+    #[serde(default)]
+    flatten_arrays: bool,
+    timestamp_field: Option<String>,
+    #[serde(default)]
+    timestamp_format: TimestampFormat,
+    #[serde(default = "crate::serde::default_true")]
+    add_timestamp: bool,
+    #[serde(default)]
+    timestamp_precedence: TimestampPrecedence,
+    received_at_key: Option<String>,
+    #[serde(default = "crate::serde::default_true")]
+    add_source_type: bool,
+    source_type: Option<String>,
+    proto_descriptor_path: Option<PathBuf>,
+    proto_message_type: Option<String>,
+    #[serde(default)]
+    headers: Vec<String>,
+    #[serde(default)]
+    skip_missing_headers: bool,
+    #[serde(default)]
+    json_headers: Vec<String>,
+    #[serde(default)]
+    header_patterns: Vec<String>,
+    #[serde(default)]
+    lowercase_header_keys: bool,
+    all_headers_key: Option<String>,
+    cookies_key: Option<String>,
+    #[serde(default)]
+    query_parameters: Vec<String>,
+    #[serde(default)]
+    skip_missing_query_parameters: bool,
+    raw_query_key: Option<String>,
+    #[serde(default = "default_methods")]
+    methods: Vec<String>,
+    #[serde(default)]
+    allowed_content_types: Vec<String>,
+    method_key: Option<String>,
+    path_template: Option<String>,
+    #[serde(default)]
+    routes: Vec<RouteConfig>,
+    route_key: Option<String>,
+    path_segments_key: Option<String>,
+    #[serde(default)]
+    trailing_slash: TrailingSlash,
+    healthcheck_path: Option<String>,
+    websocket_path: Option<String>,
+    #[serde(default)]
+    server_sent_events: bool,
+    sse_event_key: Option<String>,
+    sse_id_key: Option<String>,
+    sse_comment_key: Option<String>,
+    #[serde(default = "default_response_code")]
+    response_code: u16,
+    response_body: Option<String>,
+    response_content_type: Option<String>,
+    #[serde(default)]
+    response_headers: HashMap<String, String>,
+    response_gzip_threshold: Option<usize>,
+    echo_request_id_header: Option<String>,
+    request_id_key: Option<String>,
+    max_body_size: Option<usize>,
+    max_events_per_request: Option<usize>,
+    #[serde(default)]
+    when_full: WhenFull,
+    shutdown_timeout_secs: Option<u64>,
+    #[serde(default)]
+    allowed_ips: Vec<String>,
+    #[serde(default)]
+    denied_ips: Vec<String>,
+    #[serde(default)]
+    trusted_proxies: Vec<String>,
+    remote_ip_key: Option<String>,
+    sni_key: Option<String>,
+    #[serde(default)]
+    verify_client: bool,
+    client_cn_key: Option<String>,
+    client_sans_key: Option<String>,
+    rate_limit: Option<RateLimitConfig>,
+    cors: Option<CorsConfig>,
+    idempotency: Option<IdempotencyConfig>,
+    tls: Option<TlsConfig>,
+    auth: Option<HttpSourceAuthConfig>,
+}
+
+inventory::submit! {
+    SourceDescription::new::<SimpleHttpConfig>("http")
+}
+
+impl GenerateConfig for SimpleHttpConfig {
+    fn generate_config() -> toml::Value {
+        toml::Value::try_from(Self {
+            address: "0.0.0.0:80".parse::<SocketAddr>().unwrap().into(),
+            encoding: Default::default(),
+            default_encoding: Default::default(),
+            framing_delimiter: None,
+            invalid_utf8: Default::default(),
+            on_empty_body: Default::default(),
+            body_base64: false,
+            charset: None,
+            csv_headers: None,
+            body_key: None,
+            raw_body_key: None,
+            json_pointer: None,
+            json_schema_path: None,
+            openapi_path: None,
+            schema_mode: Default::default(),
+            on_array_error: Default::default(),
+            flatten: false,
+            flatten_separator: default_flatten_separator(),
+            flatten_arrays: false,
+            timestamp_field: None,
+            timestamp_format: Default::default(),
+            add_timestamp: true,
+            timestamp_precedence: Default::default(),
+            received_at_key: None,
+            add_source_type: true,
+            source_type: None,
+            proto_descriptor_path: None,
+            proto_message_type: None,
+            headers: Vec::new(),
+            skip_missing_headers: false,
+            json_headers: Vec::new(),
+            header_patterns: Vec::new(),
+            lowercase_header_keys: false,
+            all_headers_key: None,
+            cookies_key: None,
+            query_parameters: Vec::new(),
+            skip_missing_query_parameters: false,
+            raw_query_key: None,
+            methods: default_methods(),
+            allowed_content_types: Vec::new(),
+            method_key: None,
+            path_template: None,
+            routes: Vec::new(),
+            route_key: None,
+            path_segments_key: None,
+            trailing_slash: Default::default(),
+            healthcheck_path: None,
+            websocket_path: None,
+            server_sent_events: false,
+            sse_event_key: None,
+            sse_id_key: None,
+            sse_comment_key: None,
+            response_code: default_response_code(),
+            response_body: None,
+            response_content_type: None,
+            response_headers: HashMap::new(),
+            response_gzip_threshold: None,
+            echo_request_id_header: None,
+            request_id_key: None,
+            max_body_size: None,
+            max_events_per_request: None,
+            when_full: Default::default(),
+            shutdown_timeout_secs: None,
+            allowed_ips: Vec::new(),
+            denied_ips: Vec::new(),
+            trusted_proxies: Vec::new(),
+            remote_ip_key: None,
+            sni_key: None,
+            verify_client: false,
+            client_cn_key: None,
+            client_sans_key: None,
+            rate_limit: None,
+            cors: None,
+            idempotency: None,
+            tls: None,
+            auth: None,
+        })
+        .unwrap()
+    }
+}
+
+fn default_methods() -> Vec<String> {
+    vec!["POST".to_owned()]
+}
+
+const fn default_response_code() -> u16 {
+    200
+}
+
+fn default_flatten_separator() -> String {
+    ".".to_owned()
+}
+
+#[derive(Clone)]
+struct SimpleHttpSource {
+    encoding: Encoding,
+    default_encoding: Encoding,
+    framing_delimiter: u8,
+    invalid_utf8: InvalidUtf8,
+    on_empty_body: EmptyBodyMode,
+    body_base64: bool,
+    charset: Option<&'static encoding_rs::Encoding>,
+    csv_headers: Option<Vec<String>>,
+    body_key: Option<String>,
+    raw_body_key: Option<String>,
+    json_pointer: Option<String>,
+    json_schema: Option<Arc<JsonSchemaValidator>>,
+    openapi: Option<Arc<OpenApiValidator>>,
+    on_array_error: ArrayErrorMode,
+    flatten: bool,
+    flatten_separator: String,
+    flatten_arrays: bool,
+    timestamp_field: Option<String>,
+    timestamp_format: TimestampFormat,
+    add_timestamp: bool,
+    timestamp_precedence: TimestampPrecedence,
+    received_at_key: Option<String>,
+    source_type: Option<Bytes>,
+    protobuf: Option<Arc<ProtobufDecoder>>,
+    headers: Vec<String>,
+    skip_missing_headers: bool,
+    json_headers: Vec<String>,
+    header_patterns: Vec<Regex>,
+    all_headers_key: Option<String>,
+    cookies_key: Option<String>,
+    query_parameters: Vec<String>,
+    skip_missing_query_parameters: bool,
+    raw_query_key: Option<String>,
+    ip_filter: IpFilter,
+    remote_ip_key: Option<String>,
+    sni_key: Option<String>,
+    client_cn_key: Option<String>,
+    client_sans_key: Option<String>,
+    methods: Vec<Method>,
+    allowed_content_types: Vec<String>,
+    method_key: Option<String>,
+    path_template: Option<PathTemplate>,
+    routes: Vec<Route>,
+    route_key: Option<String>,
+    path_segments_key: Option<String>,
+    trailing_slash: TrailingSlash,
+    healthcheck_path: Option<String>,
+    websocket_path: Option<String>,
+    server_sent_events: bool,
+    sse_event_key: Option<String>,
+    sse_id_key: Option<String>,
+    sse_comment_key: Option<String>,
+    response_code: StatusCode,
+    response_body: Option<Bytes>,
+    response_content_type: Option<HeaderValue>,
+    response_headers: HeaderMap,
+    response_gzip_threshold: Option<usize>,
+    echo_request_id_header: Option<HeaderName>,
+    request_id_key: Option<String>,
+    max_body_size: Option<usize>,
+    max_events_per_request: Option<usize>,
+    when_full: WhenFull,
+    shutdown_timeout_secs: Option<u64>,
+    rate_limiter: Option<RateLimiter>,
+    cors: Option<CorsConfig>,
+    idempotency: Option<IdempotencyConfig>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct RouteConfig {
+    /// A path template, as for `path_template`.
+    path: String,
+    /// Defaults to the source's `encoding`.
+    encoding: Option<Encoding>,
+    /// Used instead of the source's `auth` for requests to this route, which
+    /// are accepted without authentication if it isn't set.
+    auth: Option<HttpSourceAuthConfig>,
+}
+
+#[derive(Clone)]
+struct Route {
+    template: PathTemplate,
+    encoding: Encoding,
+    auth: HttpSourceAuth,
+}
+
+#[derive(Deserialize, Serialize, Debug, Eq, PartialEq, Clone, Derivative, Copy)]
+#[serde(rename_all = "snake_case")]
+#[derivative(Default)]
+pub enum Encoding {
+    #[derivative(Default)]
+    Text,
+    Ndjson,
+    Json,
+    Csv,
+    Avro,
+    MessagePack,
+    Protobuf,
+    Form,
+    Xml,
+    Logfmt,
+    Gelf,
+    Loki,
+    SplunkHec,
+    EsBulk,
+    DatadogLogs,
+    Metric,
+    InfluxLine,
+    PrometheusRemoteWrite,
+    Auto,
+}
+
+/// What happens to requests when the pipeline is full.
+#[derive(Deserialize, Serialize, Debug, Eq, PartialEq, Clone, Copy, Derivative)]
+#[serde(rename_all = "snake_case")]
+#[derivative(Default)]
+pub enum WhenFull {
+    #[derivative(Default)]
+    Block,
+    Reject,
+}
+
+/// Which wins when the body has a field named like the timestamp key: the
+/// field itself, or the time the request was received.
+#[derive(Deserialize, Serialize, Debug, Eq, PartialEq, Clone, Copy, Derivative)]
+#[serde(rename_all = "snake_case")]
+#[derivative(Default)]
+pub enum TimestampPrecedence {
+    #[derivative(Default)]
+    Body,
+    Received,
+}
+
+/// Whether request paths must match exactly, or may have a trailing slash.
+#[derive(Deserialize, Serialize, Debug, Eq, PartialEq, Clone, Copy, Derivative)]
+#[serde(rename_all = "snake_case")]
+#[derivative(Default)]
+pub enum TrailingSlash {
+    #[derivative(Default)]
+    Strict,
+    Lenient,
+}
+
+impl TrailingSlash {
+    /// Removes the trailing slashes of a configured path when requests have
+    /// theirs removed too.
+    fn apply(self, path: String) -> String {
+        match self {
+            TrailingSlash::Strict => path,
+            TrailingSlash::Lenient => match path.trim_end_matches('/') {
+                "" => "/".to_owned(),
+                trimmed => trimmed.to_owned(),
+            },
+        }
+    }
+}
+
+/// How requests with an empty body are handled, besides `GET` requests, which
+/// are always a single event.
+#[derive(Deserialize, Serialize, Debug, Eq, PartialEq, Clone, Copy, Derivative)]
+#[serde(rename_all = "snake_case")]
+#[derivative(Default)]
+pub enum EmptyBodyMode {
+    /// Decoded like any other body, which depends on the encoding
+    #[derivative(Default)]
+    Decode,
+    /// A single event without any decoded fields
+    Accept,
+    /// Rejected with a 400 response
+    Reject,
+}
+
+/// How lines of text that aren't valid UTF-8 are handled.
+#[derive(Deserialize, Serialize, Debug, Eq, PartialEq, Clone, Copy, Derivative)]
+#[serde(rename_all = "snake_case")]
+#[derivative(Default)]
+pub enum InvalidUtf8 {
+    Error,
+    Replace,
+    #[derivative(Default)]
+    Passthrough,
+}
+
+impl InvalidUtf8 {
+    fn apply(self, line: Bytes) -> Result<Bytes, ErrorMessage> {
+        match self {
+            InvalidUtf8::Passthrough => Ok(line),
+            InvalidUtf8::Error => match std::str::from_utf8(&line) {
+                Ok(_) => Ok(line),
+                Err(error) => Err(ErrorMessage::new(
+                    StatusCode::BAD_REQUEST,
+                    format!("Invalid UTF-8: {}", error),
+                )),
+            },
+            InvalidUtf8::Replace => match String::from_utf8_lossy(&line) {
+                Cow::Borrowed(_) => Ok(line),
+                Cow::Owned(replaced) => Ok(Bytes::from(replaced)),
+            },
+        }
+    }
+}
+
+/// What happens to a JSON array when some of its elements can't be decoded.
+#[derive(Deserialize, Serialize, Debug, Eq, PartialEq, Clone, Copy, Derivative)]
+#[serde(rename_all = "snake_case")]
+#[derivative(Default)]
+pub enum ArrayErrorMode {
+    #[derivative(Default)]
+    RejectAll,
+    SkipInvalid,
+}
+
+/// How `timestamp_field` is parsed: `rfc3339`, `unix`, `unix_ms`, or otherwise
+/// a `strftime` style format.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Derivative)]
+#[serde(from = "String", into = "String")]
+#[derivative(Default)]
+pub enum TimestampFormat {
+    #[derivative(Default)]
+    Rfc3339,
+    Unix,
+    UnixMs,
+    Custom(String),
+}
+
+impl From<String> for TimestampFormat {
+    fn from(format: String) -> Self {
+        match format.as_str() {
+            "rfc3339" => TimestampFormat::Rfc3339,
+            "unix" => TimestampFormat::Unix,
+            "unix_ms" => TimestampFormat::UnixMs,
+            _ => TimestampFormat::Custom(format),
+        }
+    }
+}
+
+impl From<TimestampFormat> for String {
+    fn from(format: TimestampFormat) -> Self {
+        match format {
+            TimestampFormat::Rfc3339 => "rfc3339".to_owned(),
+            TimestampFormat::Unix => "unix".to_owned(),
+            TimestampFormat::UnixMs => "unix_ms".to_owned(),
+            TimestampFormat::Custom(format) => format,
+        }
+    }
+}
+
+impl TimestampFormat {
+    fn parse(&self, value: &Value) -> Result<DateTime<Utc>, String> {
+        let timestamp = match (self, value) {
+            (_, Value::Timestamp(timestamp)) => Some(*timestamp),
+            (TimestampFormat::Unix, Value::Integer(secs)) => Utc.timestamp_opt(*secs, 0).single(),
+            (TimestampFormat::Unix, Value::Float(secs)) => Utc
+                .timestamp_opt(secs.trunc() as i64, (secs.fract() * 1e9) as u32)
+                .single(),
+            (TimestampFormat::UnixMs, Value::Integer(millis)) => {
+                Utc.timestamp_millis_opt(*millis).single()
+            }
+            (_, Value::Bytes(bytes)) => {
+                let text = std::str::from_utf8(bytes).map_err(|error| error.to_string())?;
+                return self.parse_str(text.trim());
+            }
+            _ => None,
+        };
+        timestamp.ok_or_else(|| format!("{:?} is not a valid timestamp", value))
+    }
+
+    fn parse_str(&self, text: &str) -> Result<DateTime<Utc>, String> {
+        match self {
+            TimestampFormat::Rfc3339 => DateTime::parse_from_rfc3339(text)
+                .map(|timestamp| timestamp.with_timezone(&Utc))
+                .map_err(|error| error.to_string()),
+            TimestampFormat::Unix => match i64::from_str(text) {
+                Ok(secs) => self.parse(&Value::Integer(secs)),
+                Err(_) => f64::from_str(text)
+                    .map_err(|error| error.to_string())
+                    .and_then(|secs| self.parse(&Value::Float(secs))),
+            },
+            TimestampFormat::UnixMs => i64::from_str(text)
+                .map_err(|error| error.to_string())
+                .and_then(|millis| self.parse(&Value::Integer(millis))),
+            TimestampFormat::Custom(format) => DateTime::parse_from_str(text, format)
+                .map(|timestamp| timestamp.with_timezone(&Utc))
+                .or_else(|_| {
+                    NaiveDateTime::parse_from_str(text, format)
+                        .map(|timestamp| Utc.from_utc_datetime(&timestamp))
+                })
+                .map_err(|error| error.to_string()),
+        }
+    }
+}
+
+impl Encoding {
+    /// Picks the encoding matching the media type of a `Content-Type` header.
+    fn from_content_type(content_type: &str) -> Option<Self> {
+        let media_type = content_type
+            .split(';')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_ascii_lowercase();
+        match media_type.as_str() {
+            "text/plain" => Some(Encoding::Text),
+            "application/x-ndjson" | "application/ndjson" => Some(Encoding::Ndjson),
+            "application/json" => Some(Encoding::Json),
+            "text/csv" => Some(Encoding::Csv),
+            "avro/binary" | "application/avro" => Some(Encoding::Avro),
+            "application/msgpack" | "application/x-msgpack" => Some(Encoding::MessagePack),
+            "application/protobuf" | "application/x-protobuf" => Some(Encoding::Protobuf),
+            "application/x-www-form-urlencoded" => Some(Encoding::Form),
+            "application/xml" | "text/xml" => Some(Encoding::Xml),
+            _ => None,
+        }
+    }
+}
+
+impl Encoding {
+    fn is_metric(self) -> bool {
+        matches!(
+            self,
+            Encoding::Metric | Encoding::InfluxLine | Encoding::PrometheusRemoteWrite
+        )
+    }
+}
+
+impl SimpleHttpSource {
+    /// Matches a request path to its route, returning the route's encoding, the
+    /// route itself and its path parameters, or a 404 response if none match.
+    ///
+    /// Without `routes`, the route is the path template, `/`, or `*` for
+    /// encodings accepting any path.
+    fn match_route(
+        &self,
+        path: &str,
+    ) -> Result<(Encoding, &str, Vec<(String, String)>), ErrorMessage> {
+        if !self.routes.is_empty() {
+            return self
+                .routes
+                .iter()
+                .find_map(|route| {
+                    route
+                        .template
+                        .parameters(path)
+                        .ok()
+                        .map(|parameters| (route.encoding, route.template.as_str(), parameters))
+                })
+                .ok_or_else(|| {
+                    ErrorMessage::new(
+                        StatusCode::NOT_FOUND,
+                        format!("Path {} does not match any route", path),
+                    )
+                });
+        }
+        match &self.path_template {
+            Some(template) => Ok((self.encoding, template.as_str(), template.parameters(path)?)),
+            None if self.strict_path() => Ok((self.encoding, "/", Vec::new())),
+            None => Ok((self.encoding, "*", Vec::new())),
+        }
+    }
+
+    fn encoding_for_content_type(&self, content_type: Option<&str>) -> Encoding {
+        content_type
+            .and_then(Encoding::from_content_type)
+            .filter(|encoding| *encoding != Encoding::Protobuf || self.protobuf.is_some())
+            .unwrap_or(self.default_encoding)
+    }
+
+    /// Emits one event per part, with the part's content nested under its name.
+    fn decode_multipart(&self, body: Bytes, boundary: &str) -> Result<Vec<Event>, ErrorMessage> {
+        let parts = multipart::parse(&body, boundary).map_err(|error| {
+            ErrorMessage::new(StatusCode::BAD_REQUEST, format!("Bad multipart: {}", error))
+        })?;
+
+        let mut events = Vec::new();
+        for part in parts {
+            let name = part.name.unwrap_or_else(|| "part".to_owned());
+            let values = match (part.filename, part.content_type.as_deref()) {
+                (Some(filename), _) => {
+                    let mut file = BTreeMap::new();
+                    file.insert("filename".to_owned(), Value::from(filename));
+                    file.insert("content".to_owned(), Value::from(part.body));
+                    vec![Value::Map(file)]
+                }
+                (None, content_type) => match content_type.and_then(Encoding::from_content_type) {
+                    Some(Encoding::Text) | None => vec![Value::from(part.body)],
+                    Some(_) => {
+                        let encoding = self.encoding_for_content_type(content_type);
+                        self.decode_body(part.body, encoding)?
+                            .into_iter()
+                            .map(|event| Value::Map(event.as_log().as_map().clone()))
+                            .collect()
+                    }
+                },
+            };
+
+            for value in values {
+                let mut event = Event::new_empty_log();
+                let log = event.as_mut_log();
+                log.insert_flat(name.clone(), value);
+                events.push(event);
+            }
+        }
+        Ok(events)
+    }
+
+    fn request_id<'a>(&self, header_map: &'a HeaderMap) -> Option<&'a HeaderValue> {
+        header_map.get(self.echo_request_id_header.as_ref()?)
+    }
+
+    /// Flattens a decoded JSON object when configured to.
+    fn flatten_json(&self, value: JsonValue) -> JsonValue {
+        match value {
+            JsonValue::Object(object) if self.flatten => JsonValue::Object(flatten_object(
+                object,
+                &self.flatten_separator,
+                self.flatten_arrays,
+            )),
+            value => value,
+        }
+    }
+
+    fn decode_body(&self, body: Bytes, encoding: Encoding) -> Result<Vec<Event>, ErrorMessage> {
+        // A leading byte order mark carries no meaning for the text based encodings
+        let body = match encoding {
+            Encoding::Avro | Encoding::MessagePack | Encoding::Protobuf => body,
+            _ => strip_bom(body),
+        };
+        match encoding {
+            Encoding::Text => body_to_lines(body, self.framing_delimiter)
+                .map(|line| {
+                    let mut event = Event::new_empty_log();
+                    event
+                        .as_mut_log()
+                        .insert(log_schema().message_key(), self.invalid_utf8.apply(line)?);
+                    Ok(event)
+                })
+                .collect::<Result<_, _>>(),
+            Encoding::Ndjson => {
+                let parsed_json = body_to_lines(body, self.framing_delimiter)
+                    .map(|j| {
+                        parse_json(&j).map_err(|error| {
+                            json_error(format!("Error parsing Ndjson: {:?}", error))
+                        })
+                    })
+                    .collect::<Result<Vec<JsonValue>, _>>()?;
+                let parsed_json = match &self.json_schema {
+                    Some(json_schema) => json_schema.validate(parsed_json)?,
+                    None => parsed_json,
+                };
+                parsed_json
+                    .into_iter()
+                    .map(|parsed_json| self.flatten_json(parsed_json))
+                    .map(|parsed_json| json_parse_object(parsed_json, self.body_key.as_deref()))
+                    .collect::<Result<_, _>>()
+            }
+            Encoding::Json => {
+                let parsed_json = parse_json(&body)
+                    .map_err(|error| json_error(format!("Error parsing Json: {:?}", error)))?;
+                let parsed_json = match &self.json_pointer {
+                    Some(pointer) => json_pointer_target(parsed_json, pointer)?,
+                    None => parsed_json,
+                };
+                let parsed_json = match (&self.json_schema, parsed_json) {
+                    (Some(json_schema), JsonValue::Array(values)) => {
+                        JsonValue::Array(json_schema.validate(values)?)
+                    }
+                    (Some(json_schema), value @ JsonValue::Object(_)) => {
+                        match json_schema.validate(vec![value])?.pop() {
+                            Some(value) => value,
+                            None => return Ok(Vec::new()),
+                        }
+                    }
+                    (_, value) => value,
+                };
+                let parsed_json = match parsed_json {
+                    JsonValue::Array(values) => JsonValue::Array(
+                        values
+                            .into_iter()
+                            .map(|value| self.flatten_json(value))
+                            .collect(),
+                    ),
+                    value => self.flatten_json(value),
+                };
+                json_parse_array_of_object(
+                    parsed_json,
+                    self.body_key.as_deref(),
+                    self.on_array_error,
+                )
+            }
+            Encoding::Csv => csv_parse_records(body, &self.csv_headers),
+            Encoding::Avro => avro_parse_container(body),
+            Encoding::MessagePack => {
+                let parsed = rmpv::decode::read_value(&mut body.as_ref()).map_err(|error| {
+                    msgpack_error(format!("Error parsing MessagePack: {:?}", error))
+                })?;
+                msgpack_parse_array_of_map(parsed)
+            }
+            Encoding::Protobuf => {
+                let decoder = self
+                    .protobuf
+                    .as_ref()
+                    .expect("protobuf decoder is built with the source");
+                let fields = decoder.decode(&body).map_err(|error| {
+                    ErrorMessage::new(StatusCode::BAD_REQUEST, format!("Bad protobuf: {}", error))
+                })?;
+                let mut event = Event::new_empty_log();
+                let log = event.as_mut_log();
+                for (k, v) in fields {
+                    log.insert_flat(k, v);
+                }
+                Ok(vec![event])
+            }
+            Encoding::Form => Ok(vec![form_parse(body)]),
+            Encoding::Xml => Ok(vec![xml_parse_document(body)?]),
+            Encoding::Gelf => gelf::decode(&body),
+            Encoding::Loki => loki::decode(&body),
+            Encoding::SplunkHec => hec::decode(&body),
+            Encoding::DatadogLogs => datadog::decode(&body),
+            Encoding::Metric => metric_parse_body(&body),
+            Encoding::InfluxLine | Encoding::EsBulk | Encoding::PrometheusRemoteWrite => {
+                unreachable!("decoded with the request's query, path or headers")
+            }
+            Encoding::Logfmt => body_to_lines(body, self.framing_delimiter)
+                .map(|line| {
+                    let line = std::str::from_utf8(&line)
+                        .map_err(|error| logfmt_error(format!("{}", error)))?;
+                    logfmt_parse_line(line)
+                })
+                .collect::<Result<_, _>>(),
+            Encoding::Auto => unreachable!("auto encoding is resolved per request"),
+        }
+    }
+}
+
+impl HttpSource for SimpleHttpSource {
+    fn build_event(
+        &self,
+        body: Bytes,
+        header_map: HeaderMap,
+        query_parameters: HashMap<String, String>,
+        request: &RequestInfo,
+    ) -> Result<Vec<Event>, ErrorMessage> {
+        let (encoding, route, path_parameters) = self.match_route(&request.path)?;
+        let content_type = header_map
+            .get("content-type")
+            .and_then(|value| value.to_str().ok());
+        let request_id = self.request_id(&header_map).cloned();
+        let cookies = self
+            .cookies_key
+            .as_ref()
+            .and_then(|_| parse_cookies(&header_map));
+        let remote_ip = request
+            .client
+            .remote_addr
+            .map(|addr| self.ip_filter.client_ip(addr.ip(), &header_map));
+        let raw_body = self.raw_body_key.as_ref().map(|_| raw_body_value(&body));
+        let body = if self.body_base64 {
+            decode_base64(&body)?
+        } else {
+            body
+        };
+        let body = match self.charset {
+            Some(charset) => transcode(&body, charset)?,
+            None => body,
+        };
+        if let Some(openapi) = &self.openapi {
+            openapi.validate(&request.method, &request.path, &body)?;
+        }
+
+        let events = match content_type.and_then(multipart::boundary) {
+            // Query-only requests, such as beacons, are a single event
+            None if body.is_empty()
+                && !encoding.is_metric()
+                && (request.method == Method::GET
+                    || self.on_empty_body == EmptyBodyMode::Accept) =>
+            {
+                Ok(vec![Event::new_empty_log()])
+            }
+            None if body.is_empty() && self.on_empty_body == EmptyBodyMode::Accept => {
+                Ok(Vec::new())
+            }
+            None if body.is_empty() && self.on_empty_body == EmptyBodyMode::Reject => Err(
+                ErrorMessage::new(StatusCode::BAD_REQUEST, "Empty request body".to_owned()),
+            ),
+            Some(boundary) => self.decode_multipart(body, &boundary),
+            None => {
+                let encoding = match encoding {
+                    Encoding::Auto => self.encoding_for_content_type(content_type),
+                    encoding => encoding,
+                };
+                match encoding {
+                    Encoding::InfluxLine => influx::decode(&body, &query_parameters),
+                    Encoding::EsBulk => es_bulk::decode(&body, &request.path),
+                    Encoding::PrometheusRemoteWrite => {
+                        remote_write::decode(body, &header_map, self.max_body_size)
+                    }
+                    encoding => self.decode_body(body, encoding),
+                }
+            }
+        };
+        let events = events.and_then(|events| match self.max_events_per_request {
+            Some(max) if events.len() > max => Err(ErrorMessage::new(
+                StatusCode::PAYLOAD_TOO_LARGE,
+                format!(
+                    "Request produced {} events, more than the limit of {}",
+                    events.len(),
+                    max
+                ),
+            )),
+            _ => Ok(events),
+        });
+
+        // Headers, query parameters and the like are only added to logs
+        if encoding.is_metric() {
+            return events;
+        }
+
+        events
+            .map(|mut events| {
+                // Add the time of receipt, unless the body has a timestamp taking precedence
+                if self.add_timestamp {
+                    let now = Utc::now();
+                    for event in events.iter_mut() {
+                        let log = event.as_mut_log();
+                        match self.timestamp_precedence {
+                            TimestampPrecedence::Body => {
+                                log.try_insert(log_schema().timestamp_key(), now)
+                            }
+                            TimestampPrecedence::Received => {
+                                log.insert(log_schema().timestamp_key(), now);
+                            }
+                        }
+                    }
+                }
+                if let Some(field) = &self.timestamp_field {
+                    set_timestamps(&mut events, field, &self.timestamp_format);
+                }
+                // Add the time of receipt, whatever the timestamp ended up being
+                if let Some(key) = &self.received_at_key {
+                    let now = Utc::now();
+                    for event in events.iter_mut() {
+                        event.as_mut_log().insert(key.as_str(), now);
+                    }
+                }
+                events
+            })
+            .map(|events| {
+                add_headers(
+                    events,
+                    &self.headers,
+                    &self.json_headers,
+                    &self.header_patterns,
+                    self.skip_missing_headers,
+                    self.all_headers_key.as_deref(),
+                    header_map,
+                )
+            })
+            .map(|events| {
+                add_query_parameters(
+                    events,
+                    &self.query_parameters,
+                    self.skip_missing_query_parameters,
+                    query_parameters,
+                )
+            })
+            .map(|mut events| {
+                // Add source type
+                if let Some(source_type) = &self.source_type {
+                    let key = log_schema().source_type_key();
+                    for event in events.iter_mut() {
+                        event.as_mut_log().try_insert(key, source_type.clone());
+                    }
+                }
+                // Add the body as received
+                if let (Some(key), Some(raw_body)) = (&self.raw_body_key, &raw_body) {
+                    for event in events.iter_mut() {
+                        event.as_mut_log().insert(key.as_str(), raw_body.clone());
+                    }
+                }
+                // Add the cookies
+                if let (Some(key), Some(cookies)) = (&self.cookies_key, &cookies) {
+                    for event in events.iter_mut() {
+                        event.as_mut_log().insert(key.as_str(), cookies.clone());
+                    }
+                }
+                // Add the request method
+                if let Some(key) = &self.method_key {
+                    for event in events.iter_mut() {
+                        event
+                            .as_mut_log()
+                            .insert(key.as_str(), request.method.as_str());
+                    }
+                }
+                // Add the query string
+                if let (Some(key), Some(raw_query)) = (&self.raw_query_key, &request.raw_query) {
+                    for event in events.iter_mut() {
+                        event.as_mut_log().insert(key.as_str(), raw_query.clone());
+                    }
+                }
+                // Add the path parameters
+                for (key, value) in path_parameters.iter() {
+                    for event in events.iter_mut() {
+                        event.as_mut_log().insert(key.as_str(), value.clone());
+                    }
+                }
+                // Add the matched route
+                if let Some(key) = &self.route_key {
+                    for event in events.iter_mut() {
+                        event.as_mut_log().insert(key.as_str(), route);
+                    }
+                }
+                // Add the path's segments
+                if let Some(key) = &self.path_segments_key {
+                    let segments = Value::Array(
+                        request
+                            .path
+                            .split('/')
+                            .filter(|segment| !segment.is_empty())
+                            .map(Value::from)
+                            .collect(),
+                    );
+                    for event in events.iter_mut() {
+                        event.as_mut_log().insert(key.as_str(), segments.clone());
+                    }
+                }
+                // Add the client address
+                if let (Some(key), Some(remote_ip)) = (&self.remote_ip_key, remote_ip) {
+                    let remote_ip = remote_ip.to_string();
+                    for event in events.iter_mut() {
+                        event.as_mut_log().insert(key.as_str(), remote_ip.clone());
+                    }
+                }
+                // Add the server name asked for
+                if let (Some(key), Some(server_name)) = (&self.sni_key, &request.client.server_name)
+                {
+                    for event in events.iter_mut() {
+                        event.as_mut_log().insert(key.as_str(), server_name.clone());
+                    }
+                }
+                // Add the verified client certificate's subject
+                if let (Some(key), Some(cn)) = (&self.client_cn_key, &request.client.certificate_cn)
+                {
+                    for event in events.iter_mut() {
+                        event.as_mut_log().insert(key.as_str(), cn.clone());
+                    }
+                }
+                // Add the verified client certificate's subject alternative names
+                if let Some(key) = &self.client_sans_key {
+                    if !request.client.certificate_sans.is_empty() {
+                        let sans = Value::Array(
+                            request
+                                .client
+                                .certificate_sans
+                                .iter()
+                                .map(|san| san.clone().into())
+                                .collect(),
+                        );
+                        for event in events.iter_mut() {
+                            event.as_mut_log().insert(key.as_str(), sans.clone());
+                        }
+                    }
+                }
+                // Add the request id
+                if let (Some(key), Some(request_id)) = (&self.request_id_key, request_id) {
+                    let request_id = Bytes::copy_from_slice(request_id.as_bytes());
+                    for event in events.iter_mut() {
+                        event.as_mut_log().insert(key.as_str(), request_id.clone());
+                    }
+                }
+                events
+            })
+    }
+
+    fn admit_client(
+        &self,
+        client: &ClientInfo,
+        header_map: &HeaderMap,
+    ) -> Result<(), ErrorMessage> {
+        self.ip_filter.check(client, header_map)?;
+        match (&self.rate_limiter, client.remote_addr) {
+            (Some(rate_limiter), Some(addr)) => {
+                rate_limiter.check(self.ip_filter.client_ip(addr.ip(), header_map))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn methods(&self) -> Vec<Method> {
+        self.methods.clone()
+    }
+
+    fn allowed_content_types(&self) -> Vec<String> {
+        self.allowed_content_types.clone()
+    }
+
+    fn path_auth(&self, path: &str) -> Option<&HttpSourceAuth> {
+        self.routes
+            .iter()
+            .find(|route| route.template.parameters(path).is_ok())
+            .map(|route| &route.auth)
+    }
+
+    fn healthcheck_path(&self) -> Option<String> {
+        self.healthcheck_path.clone()
+    }
+
+    fn trim_trailing_slash(&self) -> bool {
+        self.trailing_slash == TrailingSlash::Lenient
+    }
+
+    fn websocket_path(&self) -> Option<String> {
+        self.websocket_path.clone()
+    }
+
+    fn server_sent_events(&self) -> bool {
+        self.server_sent_events
+    }
+
+    fn build_server_sent_event(
+        &self,
+        event: ServerSentEvent,
+        header_map: HeaderMap,
+        query_parameters: HashMap<String, String>,
+        request: &RequestInfo,
+    ) -> Result<Vec<Event>, ErrorMessage> {
+        let mut events = self.build_event(event.data, header_map, query_parameters, request)?;
+        for log in events.iter_mut().filter_map(|event| match event {
+            Event::Log(log) => Some(log),
+            Event::Metric(_) => None,
+        }) {
+            if let (Some(key), Some(value)) = (&self.sse_event_key, &event.event) {
+                log.insert(key.as_str(), value.clone());
+            }
+            if let (Some(key), Some(value)) = (&self.sse_id_key, &event.id) {
+                log.insert(key.as_str(), value.clone());
+            }
+            if let Some(key) = &self.sse_comment_key {
+                if !event.comments.is_empty() {
+                    log.insert(key.as_str(), event.comments.clone());
+                }
+            }
+        }
+        Ok(events)
+    }
+
+    fn strict_path(&self) -> bool {
+        // InfluxDB clients write to `/write` or `/api/v2/write`, Loki clients to
+        // `/loki/api/v1/push`, HEC clients to `/services/collector/event`, and
+        // Elasticsearch clients to `/_bulk` or `/<index>/_bulk`, Datadog agents
+        // to `/v1/input` or `/api/v2/logs`, and Prometheus servers to whatever
+        // their remote write URL has, such as `/api/v1/write`. An OpenAPI
+        // document describes the accepted paths itself.
+        self.path_template.is_none()
+            && self.routes.is_empty()
+            && self.openapi.is_none()
+            && !matches!(
+                self.encoding,
+                Encoding::InfluxLine
+                    | Encoding::Loki
+                    | Encoding::SplunkHec
+                    | Encoding::EsBulk
+                    | Encoding::DatadogLogs
+                    | Encoding::PrometheusRemoteWrite
+            )
+    }
+
+    fn reject_when_full(&self) -> bool {
+        self.when_full == WhenFull::Reject
+    }
+
+    fn shutdown_timeout(&self) -> Option<Duration> {
+        self.shutdown_timeout_secs.map(Duration::from_secs)
+    }
+
+    fn max_body_size(&self) -> Option<usize> {
+        self.max_body_size
+    }
+
+    fn response_gzip_threshold(&self) -> Option<usize> {
+        self.response_gzip_threshold
+    }
+
+    fn cors(&self) -> Option<CorsConfig> {
+        self.cors.clone()
+    }
+
+    fn idempotency(&self) -> Option<IdempotencyConfig> {
+        self.idempotency.clone()
+    }
+
+    fn line_delimiter(&self, request: &RequestInfo, header_map: &HeaderMap) -> Option<u8> {
+        // These need the body as a whole, or all of its events at once
+        if self.body_base64
+            || self.charset.is_some()
+            || self.raw_body_key.is_some()
+            || self.openapi.is_some()
+            || self.max_events_per_request.is_some()
+        {
+            return None;
+        }
+        let content_type = header_map
+            .get("content-type")
+            .and_then(|value| value.to_str().ok());
+        if content_type.and_then(multipart::boundary).is_some() {
+            return None;
+        }
+        let encoding = match self.match_route(&request.path).ok()?.0 {
+            Encoding::Auto => self.encoding_for_content_type(content_type),
+            encoding => encoding,
+        };
+        match encoding {
+            Encoding::Text | Encoding::Ndjson | Encoding::Logfmt => Some(self.framing_delimiter),
+            _ => None,
+        }
+    }
+
+    fn success_response(&self, request: &RequestInfo, header_map: &HeaderMap) -> Response {
+        let encoding = self
+            .match_route(&request.path)
+            .map_or(self.encoding, |(encoding, _, _)| encoding);
+        let mut response = match &self.response_body {
+            Some(body) => Response::new(body.clone().into()),
+            None if encoding == Encoding::SplunkHec => {
+                let mut response = Response::new(hec::SUCCESS.into());
+                response
+                    .headers_mut()
+                    .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+                response
+            }
+            None => Response::default(),
+        };
+        *response.status_mut() = self.response_code;
+        if let Some(content_type) = &self.response_content_type {
+            response
+                .headers_mut()
+                .insert(CONTENT_TYPE, content_type.clone());
+        }
+        if let (Some(name), Some(request_id)) =
+            (&self.echo_request_id_header, self.request_id(header_map))
+        {
+            response
+                .headers_mut()
+                .insert(name.clone(), request_id.clone());
+        }
+        for (name, value) in self.response_headers.iter() {
+            response
+                .headers_mut()
+                .entry(name)
+                .or_insert_with(|| value.clone());
+        }
+        response
+    }
+}
+
+#[async_trait::async_trait]
+#[typetag::serde(name = "http")]
+impl SourceConfig for SimpleHttpConfig {
+    async fn build(
+        &self,
+        _: &str,
+        _: &GlobalOptions,
+        shutdown: ShutdownSignal,
+        out: Pipeline,
+    ) -> crate::Result<super::Source> {
+        if self.default_encoding == Encoding::Auto {
+            return Err(BuildError::AutoDefaultEncoding.into());
+        }
+        // Multipart parts fall back to the default encoding too, and don't have
+        // the query, path or headers es_bulk and the metric encodings decode with
+        if self.default_encoding.is_metric() || self.default_encoding == Encoding::EsBulk {
+            return Err(BuildError::UnsupportedDefaultEncoding.into());
+        }
+        #[cfg(unix)]
+        {
+            let tls_enabled = self
+                .tls
+                .as_ref()
+                .map_or(false, |tls| tls.enabled.unwrap_or(false));
+            if matches!(self.address, HttpListenAddr::UnixPath(_)) && tls_enabled {
+                return Err(BuildError::UnixSocketTls.into());
+            }
+        }
+        let tls = match &self.tls {
+            Some(tls) if self.verify_client && tls.enabled.unwrap_or(false) => {
+                let mut tls = tls.clone();
+                tls.options.verify_certificate = Some(true);
+                Some(tls)
+            }
+            _ if self.verify_client => return Err(BuildError::VerifyClientWithoutTls.into()),
+            tls => tls.clone(),
+        };
+
+        let framing_delimiter = match self.framing_delimiter {
+            Some(delimiter) if delimiter.is_ascii() => delimiter as u8,
+            Some(delimiter) => {
+                return Err(BuildError::MultiByteFramingDelimiter { delimiter }.into())
+            }
+            None => b'\n',
+        };
+        if let Some(pointer) = &self.json_pointer {
+            if !pointer.is_empty() && !pointer.starts_with('/') {
+                return Err(BuildError::InvalidJsonPointer {
+                    pointer: pointer.clone(),
+                }
+                .into());
+            }
+        }
+
+        let protobuf = match (&self.proto_descriptor_path, &self.proto_message_type) {
+            (Some(path), Some(message_type)) => Some(Arc::new(
+                ProtobufDecoder::load(path, message_type).context(LoadProtobufDescriptor)?,
+            )),
+            _ if self.encoding == Encoding::Protobuf
+                || self.default_encoding == Encoding::Protobuf
+                || self
+                    .routes
+                    .iter()
+                    .any(|route| route.encoding == Some(Encoding::Protobuf)) =>
+            {
+                return Err(BuildError::MissingProtobufDescriptor.into())
+            }
+            _ => None,
+        };
+
+        let charset = self
+            .charset
+            .as_ref()
+            .map(|charset| {
+                encoding_rs::Encoding::for_label(charset.as_bytes()).ok_or_else(|| {
+                    BuildError::UnknownCharset {
+                        charset: charset.clone(),
+                    }
+                })
+            })
+            .transpose()?;
+
+        let json_schema = self
+            .json_schema_path
+            .as_ref()
+            .map(|path| JsonSchemaValidator::load(path, self.schema_mode).map(Arc::new))
+            .transpose()
+            .context(LoadJsonSchema)?;
+
+        let openapi = self
+            .openapi_path
+            .as_ref()
+            .map(|path| OpenApiValidator::load(path).map(Arc::new))
+            .transpose()
+            .context(LoadOpenApi)?;
+
+        let methods = self
+            .methods
+            .iter()
+            .map(|method| {
+                Method::from_bytes(method.to_ascii_uppercase().as_bytes()).map_err(|_| {
+                    BuildError::InvalidMethod {
+                        method: method.clone(),
+                    }
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let response_code = StatusCode::from_u16(self.response_code)
+            .ok()
+            .filter(StatusCode::is_success)
+            .ok_or(BuildError::InvalidResponseCode {
+                code: self.response_code,
+            })?;
+
+        let response_content_type = self
+            .response_content_type
+            .as_ref()
+            .map(|content_type| {
+                HeaderValue::from_str(content_type)
+                    .context(InvalidResponseContentType { content_type })
+            })
+            .transpose()?;
+
+        // Header lookups are case-insensitive, so only the field names change
+        let header_keys = |names: &[String]| -> Vec<String> {
+            if self.lowercase_header_keys {
+                names.iter().map(|name| name.to_ascii_lowercase()).collect()
+            } else {
+                names.to_vec()
+            }
+        };
+
+        // Header names are case-insensitive, so the patterns are too
+        let header_patterns = self
+            .header_patterns
+            .iter()
+            .map(|pattern| {
+                RegexBuilder::new(pattern)
+                    .case_insensitive(true)
+                    .build()
+                    .context(InvalidHeaderPattern { pattern })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let response_headers = self
+            .response_headers
+            .iter()
+            .map(|(name, value)| {
+                let invalid = |message: String| BuildError::InvalidResponseHeader {
+                    name: name.clone(),
+                    message,
+                };
+                let name =
+                    HeaderName::from_str(name).map_err(|error| invalid(error.to_string()))?;
+                // These describe the framing of the response, which is managed by the server.
+                if [CONTENT_LENGTH, TRANSFER_ENCODING, CONNECTION].contains(&name) {
+                    return Err(invalid("the header is set by the server".to_owned()));
+                }
+                let value =
+                    HeaderValue::from_str(value).map_err(|error| invalid(error.to_string()))?;
+                Ok((name, value))
+            })
+            .collect::<Result<HeaderMap, _>>()?;
+
+        let echo_request_id_header = self
+            .echo_request_id_header
+            .as_ref()
+            .map(|name| HeaderName::from_str(name).context(InvalidRequestIdHeader { name }))
+            .transpose()?;
+
+        let path_template = self
+            .path_template
+            .as_deref()
+            .map(PathTemplate::new)
+            .transpose()
+            .map_err(|message| BuildError::InvalidPathTemplate { message })?;
+
+        if self.path_template.is_some() && !self.routes.is_empty() {
+            return Err(BuildError::PathTemplateWithRoutes.into());
+        }
+        let routes = self
+            .routes
+            .iter()
+            .map(|route| {
+                let template = PathTemplate::new(&route.path)
+                    .map_err(|message| BuildError::InvalidPathTemplate { message })?;
+                let auth = HttpSourceAuth::try_from(route.auth.as_ref()).map_err(|message| {
+                    BuildError::InvalidRouteAuth {
+                        path: route.path.clone(),
+                        message,
+                    }
+                })?;
+                Ok(Route {
+                    template,
+                    encoding: route.encoding.unwrap_or(self.encoding),
+                    auth,
+                })
+            })
+            .collect::<Result<Vec<_>, BuildError>>()?;
+
+        let ip_filter = IpFilter::new(&self.allowed_ips, &self.denied_ips, &self.trusted_proxies)
+            .context(InvalidIpFilter)?;
+
+        let source = SimpleHttpSource {
+            encoding: self.encoding,
+            default_encoding: self.default_encoding,
+            framing_delimiter,
+            invalid_utf8: self.invalid_utf8,
+            on_empty_body: self.on_empty_body,
+            body_base64: self.body_base64,
+            charset,
+            csv_headers: self.csv_headers.clone(),
+            body_key: self.body_key.clone(),
+            raw_body_key: self.raw_body_key.clone(),
+            json_pointer: self.json_pointer.clone(),
+            json_schema,
+            openapi,
+            on_array_error: self.on_array_error,
+            flatten: self.flatten,
+            flatten_separator: self.flatten_separator.clone(),
+            flatten_arrays: self.flatten_arrays,
+            timestamp_field: self.timestamp_field.clone(),
+            timestamp_format: self.timestamp_format.clone(),
+            add_timestamp: self.add_timestamp,
+            timestamp_precedence: self.timestamp_precedence,
+            received_at_key: self.received_at_key.clone(),
+            source_type: if self.add_source_type {
+                let source_type = self.source_type.as_deref().unwrap_or("http");
+                Some(Bytes::copy_from_slice(source_type.as_bytes()))
+            } else {
+                None
+            },
+            protobuf,
+            headers: header_keys(&self.headers),
+            skip_missing_headers: self.skip_missing_headers,
+            json_headers: header_keys(&self.json_headers),
+            header_patterns,
+            all_headers_key: self.all_headers_key.clone(),
+            cookies_key: self.cookies_key.clone(),
+            query_parameters: self.query_parameters.clone(),
+            skip_missing_query_parameters: self.skip_missing_query_parameters,
+            raw_query_key: self.raw_query_key.clone(),
+            ip_filter,
+            remote_ip_key: self.remote_ip_key.clone(),
+            sni_key: self.sni_key.clone(),
+            client_cn_key: self.client_cn_key.clone(),
+            client_sans_key: self.client_sans_key.clone(),
+            methods,
+            allowed_content_types: self.allowed_content_types.clone(),
+            method_key: self.method_key.clone(),
+            path_template,
+            routes,
+            route_key: self.route_key.clone(),
+            path_segments_key: self.path_segments_key.clone(),
+            trailing_slash: self.trailing_slash,
+            healthcheck_path: self
+                .healthcheck_path
+                .as_deref()
+                .map(|path| self.trailing_slash.apply(absolute_path(path))),
+            websocket_path: self
+                .websocket_path
+                .as_deref()
+                .map(|path| self.trailing_slash.apply(absolute_path(path))),
+            server_sent_events: self.server_sent_events,
+            sse_event_key: self.sse_event_key.clone(),
+            sse_id_key: self.sse_id_key.clone(),
+            sse_comment_key: self.sse_comment_key.clone(),
+            response_code,
+            response_body: self.response_body.clone().map(Bytes::from),
+            response_content_type,
+            response_headers,
+            response_gzip_threshold: self.response_gzip_threshold,
+            echo_request_id_header,
+            request_id_key: self.request_id_key.clone(),
+            max_body_size: self.max_body_size,
+            max_events_per_request: self.max_events_per_request,
+            when_full: self.when_full,
+            shutdown_timeout_secs: self.shutdown_timeout_secs,
+            rate_limiter: self.rate_limit.as_ref().map(RateLimiter::new).transpose()?,
+            cors: self.cors.clone(),
+            idempotency: self.idempotency.clone(),
+        };
+        source.run(self.address.clone(), "", &tls, &self.auth, out, shutdown)
+    }
+
+    fn output_type(&self) -> DataType {
+        let encodings = if self.routes.is_empty() {
+            vec![self.encoding]
+        } else {
+            self.routes
+                .iter()
+                .map(|route| route.encoding.unwrap_or(self.encoding))
+                .collect()
+        };
+        if encodings.iter().all(|encoding| encoding.is_metric()) {
+            DataType::Metric
+        } else if encodings.iter().any(|encoding| encoding.is_metric()) {
+            DataType::Any
+        } else {
+            DataType::Log
+        }
+    }
+
+    fn source_type(&self) -> &'static str {
+        "http"
+    }
+
+    fn resources(&self) -> Vec<Resource> {
+        match &self.address {
+            HttpListenAddr::SocketAddr(address) => vec![Resource::tcp(*address)],
+            #[cfg(unix)]
+            HttpListenAddr::UnixPath(path) => vec![Resource::UnixSocket(path.clone())],
+        }
+    }
+}
+
+fn add_headers(
+    mut events: Vec<Event>,
+    headers_config: &[String],
+    json_headers: &[String],
+    header_patterns: &[Regex],
+    skip_missing: bool,
+    all_headers_key: Option<&str>,
+    headers: HeaderMap,
+) -> Vec<Event> {
+    // Headers matching a pattern are added under their lowercased names, unless
+    // they're listed explicitly, in which case they're added as configured below.
+    if !header_patterns.is_empty() {
+        for name in headers.keys() {
+            let listed = headers_config
+                .iter()
+                .chain(json_headers)
+                .any(|header_name| header_name.eq_ignore_ascii_case(name.as_str()));
+            if listed
+                || !header_patterns
+                    .iter()
+                    .any(|pattern| pattern.is_match(name.as_str()))
+            {
+                continue;
+            }
+            if let Some(value) = header_values(headers.get_all(name), false) {
+                for event in events.iter_mut() {
+                    event.as_mut_log().insert(name.as_str(), value.clone());
+                }
+            }
+        }
+    }
+
+    let headers_config = headers_config
+        .iter()
+        .map(|header_name| (header_name, false))
+        .chain(json_headers.iter().map(|header_name| (header_name, true)));
+    for (header_name, json) in headers_config {
+        let value = match header_values(headers.get_all(header_name), json) {
+            Some(value) => value,
+            None if skip_missing => continue,
+            None => Value::Null,
+        };
+
+        for event in events.iter_mut() {
+            event
+                .as_mut_log()
+                .insert(header_name as &str, value.clone());
+        }
+    }
+
+    if let Some(key) = all_headers_key {
+        let all_headers = headers
+            .keys()
+            .filter_map(|name| {
+                Some((
+                    name.to_string(),
+                    header_values(headers.get_all(name), false)?,
+                ))
+            })
+            .collect::<BTreeMap<_, _>>();
+        let all_headers = Value::Map(all_headers);
+        for event in events.iter_mut() {
+            event.as_mut_log().insert(key, all_headers.clone());
+        }
+    }
+
+    events
+}
+
+/// Returns a header's value, or an array of its values when it was sent more than once.
+/// With `json`, values are decoded as JSON, falling back to the raw value.
+fn header_values(values: GetAll<'_, HeaderValue>, json: bool) -> Option<Value> {
+    let mut values = values
+        .iter()
+        .map(|value| {
+            let decoded = if json {
+                serde_json::from_slice(value.as_bytes())
+                    .ok()
+                    .map(Value::from)
+            } else {
+                None
+            };
+            decoded.unwrap_or_else(|| Value::from(Bytes::copy_from_slice(value.as_bytes())))
+        })
+        .collect::<Vec<_>>();
+    match values.len() {
+        0 => None,
+        1 => Some(values.remove(0)),
+        _ => Some(Value::Array(values)),
+    }
+}
+
+/// Returns a map of the cookies in the `Cookie` headers, skipping malformed ones.
+fn parse_cookies(headers: &HeaderMap) -> Option<Value> {
+    let cookies = headers
+        .get_all(COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .filter_map(|cookie| {
+            let cookie = cookie.trim();
+            let equals = cookie.find('=')?;
+            let name = cookie[..equals].trim();
+            let value = cookie[equals + 1..].trim();
+            let value = match value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) {
+                Some(unquoted) => unquoted,
+                None => value,
+            };
+            if name.is_empty() {
+                None
+            } else {
+                Some((name.to_owned(), Value::from(value)))
+            }
+        })
+        .collect::<BTreeMap<_, _>>();
+    if cookies.is_empty() {
+        None
+    } else {
+        Some(Value::Map(cookies))
+    }
+}
+
+/// Uses the value of `field` as the timestamp of events which have it, keeping
+/// the time of receipt when it's missing or can't be parsed.
+fn set_timestamps(events: &mut [Event], field: &str, format: &TimestampFormat) {
+    for event in events.iter_mut() {
+        let log = event.as_mut_log();
+        let timestamp = match log.get(field).map(|value| format.parse(value)) {
+            Some(Ok(timestamp)) => timestamp,
+            Some(Err(error)) => {
+                emit!(HTTPTimestampParseError {
+                    field,
+                    error: &error
+                });
+                continue;
+            }
+            None => continue,
+        };
+        log.insert(log_schema().timestamp_key(), timestamp);
+    }
+}
+
+/// Parses a JSON body the way the `json` and `ndjson` encodings do, with
+/// simd-json when built with the `sources-http-simd-json` feature.
+#[cfg(feature = "sources-http-simd-json")]
+pub fn parse_json(json: &[u8]) -> Result<JsonValue, simd_json::Error> {
+    // Parsing happens in place, so works on a copy of the body
+    simd_json::serde::from_slice(&mut json.to_vec())
+}
+
+/// Parses a JSON body the way the `json` and `ndjson` encodings do, with
+/// simd-json when built with the `sources-http-simd-json` feature.
+#[cfg(not(feature = "sources-http-simd-json"))]
+pub fn parse_json(json: &[u8]) -> Result<JsonValue, serde_json::Error> {
+    serde_json::from_slice(json)
+}
+
+fn strip_bom(body: Bytes) -> Bytes {
+    if body.starts_with(b"\xEF\xBB\xBF") {
+        body.slice(3..)
+    } else {
+        body
+    }
+}
+
+fn absolute_path(path: &str) -> String {
+    if path.starts_with('/') {
+        path.to_owned()
+    } else {
+        format!("/{}", path)
+    }
+}
+
+/// Transcodes the body from `charset` to UTF-8.
+fn transcode(body: &[u8], charset: &'static encoding_rs::Encoding) -> Result<Bytes, ErrorMessage> {
+    match charset.decode_without_bom_handling_and_without_replacement(body) {
+        Some(Cow::Borrowed(_)) => Ok(Bytes::copy_from_slice(body)),
+        Some(Cow::Owned(body)) => Ok(Bytes::from(body)),
+        None => Err(ErrorMessage::new(
+            StatusCode::BAD_REQUEST,
+            format!("Body is not valid {}", charset.name()),
+        )),
+    }
+}
+
+fn decode_base64(body: &[u8]) -> Result<Bytes, ErrorMessage> {
+    let body = body
+        .iter()
+        .copied()
+        .filter(|byte| !byte.is_ascii_whitespace())
+        .collect::<Vec<_>>();
+    base64::decode(&body).map(Bytes::from).map_err(|error| {
+        ErrorMessage::new(
+            StatusCode::BAD_REQUEST,
+            format!("Failed decoding base64 body: {}", error),
+        )
+    })
+}
+
+/// The body as a string, or base64 encoded when it isn't valid UTF-8.
+fn raw_body_value(body: &Bytes) -> Value {
+    match std::str::from_utf8(body) {
+        Ok(_) => Value::from(body.clone()),
+        Err(_) => Value::from(base64::encode(body)),
+    }
+}
+
+/// Splits the body into its non-empty lines, as slices of the body rather than copies.
+/// When splitting on newlines, a `\r` before the newline is dropped as well.
+fn body_to_lines(body: Bytes, delimiter: u8) -> impl Iterator<Item = Bytes> {
+    let mut start = 0;
+    std::iter::from_fn(move || {
+        if start >= body.len() {
+            return None;
+        }
+        let end = body[start..]
+            .iter()
+            .position(|b| *b == delimiter)
+            .map_or(body.len(), |position| start + position);
+        let line = if delimiter == b'\n' && body[start..end].ends_with(b"\r") {
+            body.slice(start..end - 1)
+        } else {
+            body.slice(start..end)
+        };
+        start = end + 1;
+        Some(line)
+    })
+    .filter(|line| !line.is_empty())
+}
+
+fn form_parse(body: Bytes) -> Event {
+    let mut fields = HashMap::<String, Value>::new();
+    for (key, value) in url::form_urlencoded::parse(&body) {
+        let value = Value::from(value.into_owned());
+        match fields.get_mut(key.as_ref()) {
+            // repeated keys are collected into an array
+            Some(Value::Array(values)) => values.push(value),
+            Some(existing) => {
+                let first = std::mem::replace(existing, Value::Null);
+                *existing = Value::Array(vec![first, value]);
+            }
+            None => {
+                fields.insert(key.into_owned(), value);
+            }
+        }
+    }
+
+    let mut event = Event::new_empty_log();
+    let log = event.as_mut_log();
+    for (k, v) in fields {
+        log.insert_flat(k, v);
+    }
+    event
+}
+
+fn xml_parse_document(body: Bytes) -> Result<Event, ErrorMessage> {
+    let config = ParserConfig::new()
+        .trim_whitespace(true)
+        .cdata_to_characters(true);
+
+    // Each open element keeps its name, children, and text.
+    let mut stack: Vec<(String, BTreeMap<String, Value>, String)> = Vec::new();
+    let mut root = None;
+    for xml_event in EventReader::new_with_config(body.as_ref(), config) {
+        match xml_event.map_err(|error| xml_error(format!("{}", error)))? {
+            XmlEvent::StartElement {
+                name, attributes, ..
+            } => {
+                let mut children = BTreeMap::new();
+                if !attributes.is_empty() {
+                    let attributes = attributes
+                        .into_iter()
+                        .map(|attribute| (attribute.name.local_name, Value::from(attribute.value)))
+                        .collect::<BTreeMap<_, _>>();
+                    children.insert("@attributes".to_owned(), Value::Map(attributes));
+                }
+                stack.push((name.local_name, children, String::new()));
+            }
+            XmlEvent::Characters(text) => {
+                if let Some((_, _, element_text)) = stack.last_mut() {
+                    element_text.push_str(&text);
+                }
+            }
+            XmlEvent::EndElement { .. } => {
+                let (name, mut children, text) = stack
+                    .pop()
+                    .ok_or_else(|| xml_error("Unexpected end element".to_owned()))?;
+                let value = match (children.is_empty(), text.is_empty()) {
+                    (true, true) => Value::Null,
+                    (true, false) => Value::from(text),
+                    (false, empty_text) => {
+                        if !empty_text {
+                            children.insert("#text".to_owned(), Value::from(text));
+                        }
+                        Value::Map(children)
+                    }
+                };
+                match stack.last_mut() {
+                    Some((_, parent, _)) => xml_insert_child(parent, name, value),
+                    None => root = Some((name, value)),
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let (name, value) = root.ok_or_else(|| xml_error("Missing root element".to_owned()))?;
+    let mut event = Event::new_empty_log();
+    let log = event.as_mut_log();
+    log.insert_flat(name, value);
+    Ok(event)
+}
+
+/// Inserts an element into its parent, turning repeated siblings into an array.
+fn xml_insert_child(parent: &mut BTreeMap<String, Value>, name: String, value: Value) {
+    match parent.get_mut(&name) {
+        Some(Value::Array(values)) => values.push(value),
+        Some(existing) => {
+            let first = std::mem::replace(existing, Value::Null);
+            *existing = Value::Array(vec![first, value]);
+        }
+        None => {
+            parent.insert(name, value);
+        }
+    }
+}
+
+fn xml_error(s: String) -> ErrorMessage {
+    ErrorMessage::new(StatusCode::BAD_REQUEST, format!("Bad XML: {}", s))
+}
+
+fn logfmt_parse_line(line: &str) -> Result<Event, ErrorMessage> {
+    let mut event = Event::new_empty_log();
+    let log = event.as_mut_log();
+
+    let mut chars = line.chars().peekable();
+    loop {
+        while chars.peek().map_or(false, |c| c.is_whitespace()) {
+            chars.next();
+        }
+        if chars.peek().is_none() {
+            break;
+        }
+
+        let mut key = String::new();
+        while let Some(c) = chars.peek().filter(|c| **c != '=' && !c.is_whitespace()) {
+            key.push(*c);
+            chars.next();
+        }
+        if key.is_empty() {
+            return Err(logfmt_error(format!("Missing key in {:?}", line)));
+        }
+
+        let value = if chars.peek() == Some(&'=') {
+            chars.next();
+            if chars.peek() == Some(&'"') {
+                chars.next();
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some('n') => value.push('\n'),
+                            Some('t') => value.push('\t'),
+                            Some(c) => value.push(c),
+                            None => break,
+                        },
+                        Some(c) => value.push(c),
+                        None => {
+                            return Err(logfmt_error(format!("Unterminated quote in {:?}", line)))
+                        }
+                    }
+                }
+                Value::from(value)
+            } else {
+                let mut value = String::new();
+                while let Some(c) = chars.peek().filter(|c| !c.is_whitespace()) {
+                    value.push(*c);
+                    chars.next();
+                }
+                logfmt_bare_value(value)
+            }
+        } else {
+            // keys without values are flags
+            Value::Boolean(true)
+        };
+        log.insert_flat(key, value);
+    }
+
+    Ok(event)
+}
+
+fn logfmt_bare_value(value: String) -> Value {
+    if let Ok(b) = value.parse::<bool>() {
+        Value::Boolean(b)
+    } else if let Ok(i) = value.parse::<i64>() {
+        Value::Integer(i)
+    } else if let Ok(f) = value.parse::<f64>() {
+        Value::Float(f)
+    } else {
+        Value::from(value)
+    }
+}
+
+fn logfmt_error(s: String) -> ErrorMessage {
+    ErrorMessage::new(StatusCode::BAD_REQUEST, format!("Bad logfmt: {}", s))
+}
+
+fn csv_parse_records(
+    body: Bytes,
+    headers: &Option<Vec<String>>,
+) -> Result<Vec<Event>, ErrorMessage> {
+    csv::ReaderBuilder::new()
+        .has_headers(false)
+        .from_reader(body.as_ref())
+        .into_records()
+        .map(|record| {
+            let record = record.map_err(|error| csv_error(format!("{}", error)))?;
+
+            let mut event = Event::new_empty_log();
+            let log = event.as_mut_log();
+            for (index, value) in record.iter().enumerate() {
+                let key = headers
+                    .as_ref()
+                    .and_then(|headers| headers.get(index))
+                    .cloned()
+                    .unwrap_or_else(|| format!("col{}", index));
+                log.insert_flat(key, value);
+            }
+            Ok(event)
+        })
+        .collect()
+}
+
+fn csv_error(s: String) -> ErrorMessage {
+    ErrorMessage::new(StatusCode::BAD_REQUEST, format!("Bad CSV: {}", s))
+}
+
+fn avro_parse_container(body: Bytes) -> Result<Vec<Event>, ErrorMessage> {
+    avro_rs::Reader::new(body.as_ref())
+        .map_err(|error| avro_error(format!("Error reading container: {}", error)))?
+        .map(|record| {
+            let record =
+                record.map_err(|error| avro_error(format!("Error reading record: {}", error)))?;
+            avro_parse_record(record)
+        })
+        .collect()
+}
+
+fn avro_parse_record(value: AvroValue) -> Result<Event, ErrorMessage> {
+    let mut event = Event::new_empty_log();
+    let log = event.as_mut_log();
+    match value {
+        AvroValue::Record(fields) => {
+            for (k, v) in fields {
+                log.insert_flat(k, avro_value_to_value(v)?);
+            }
+            Ok(event)
+        }
+        _ => Err(avro_error(format!("Expected Record, got {:?}", value))),
+    }
+}
+
+fn avro_value_to_value(value: AvroValue) -> Result<Value, ErrorMessage> {
+    Ok(match value {
+        AvroValue::Null => Value::Null,
+        AvroValue::Boolean(b) => Value::Boolean(b),
+        AvroValue::Int(i) | AvroValue::Date(i) | AvroValue::TimeMillis(i) => Value::from(i),
+        AvroValue::Long(i) | AvroValue::TimeMicros(i) => Value::from(i),
+        AvroValue::TimestampMillis(millis) => Utc
+            .timestamp_millis_opt(millis)
+            .single()
+            .map(Value::from)
+            .ok_or_else(|| avro_error(format!("Timestamp {} out of range", millis)))?,
+        AvroValue::TimestampMicros(micros) => Utc
+            .timestamp_opt(
+                micros.div_euclid(1_000_000),
+                (micros.rem_euclid(1_000_000) * 1_000) as u32,
+            )
+            .single()
+            .map(Value::from)
+            .ok_or_else(|| avro_error(format!("Timestamp {} out of range", micros)))?,
+        AvroValue::Float(f) => Value::from(f),
+        AvroValue::Double(f) => Value::from(f),
+        AvroValue::Bytes(bytes) | AvroValue::Fixed(_, bytes) => Value::from(Bytes::from(bytes)),
+        AvroValue::String(s) | AvroValue::Enum(_, s) => Value::from(s),
+        AvroValue::Uuid(uuid) => Value::from(uuid.to_string()),
+        AvroValue::Union(value) => avro_value_to_value(*value)?,
+        AvroValue::Array(values) => values
+            .into_iter()
+            .map(avro_value_to_value)
+            .collect::<Result<Value, ErrorMessage>>()?,
+        AvroValue::Map(map) => map
+            .into_iter()
+            .map(|(k, v)| Ok((k, avro_value_to_value(v)?)))
+            .collect::<Result<Value, ErrorMessage>>()?,
+        AvroValue::Record(fields) => fields
+            .into_iter()
+            .map(|(k, v)| Ok((k, avro_value_to_value(v)?)))
+            .collect::<Result<Value, ErrorMessage>>()?,
+        // Logical types without a natural counterpart are not representable
+        AvroValue::Decimal(_) | AvroValue::Duration(_) => Value::Null,
+    })
+}
+
+fn msgpack_parse_map(value: MsgPackValue) -> Result<Event, ErrorMessage> {
+    let mut event = Event::new_empty_log();
+    let log = event.as_mut_log();
+    match value {
+        MsgPackValue::Map(entries) => {
+            for (k, v) in entries {
+                log.insert_flat(msgpack_key_to_string(k), msgpack_value_to_value(v));
+            }
+            Ok(event)
+        }
+        _ => Err(msgpack_error(format!(
+            "Expected Map, got {}",
+            msgpack_value_to_type_string(&value)
+        ))),
+    }
+}
+
+fn msgpack_parse_array_of_map(value: MsgPackValue) -> Result<Vec<Event>, ErrorMessage> {
+    match value {
+        MsgPackValue::Array(v) => v
+            .into_iter()
+            .map(msgpack_parse_map)
+            .collect::<Result<_, _>>(),
+        MsgPackValue::Map(entries) => {
+            //treat like an array of one map
+            Ok(vec![msgpack_parse_map(MsgPackValue::Map(entries))?])
+        }
+        _ => Err(msgpack_error(format!(
+            "Expected Array or Map, got {}.",
+            msgpack_value_to_type_string(&value)
+        ))),
+    }
+}
+
+fn msgpack_key_to_string(key: MsgPackValue) -> String {
+    match key {
+        MsgPackValue::String(s) => s
+            .into_str()
+            .unwrap_or_else(|| String::from(std::char::REPLACEMENT_CHARACTER)),
+        other => other.to_string(),
+    }
+}
+
+fn msgpack_value_to_value(value: MsgPackValue) -> Value {
+    match value {
+        MsgPackValue::Nil => Value::Null,
+        MsgPackValue::Boolean(b) => Value::Boolean(b),
+        MsgPackValue::Integer(i) => match i.as_i64() {
+            Some(i) => Value::Integer(i),
+            None => Value::Float(i.as_f64().unwrap_or_default()),
+        },
+        MsgPackValue::F32(f) => Value::from(f),
+        MsgPackValue::F64(f) => Value::from(f),
+        MsgPackValue::String(s) => Value::from(Bytes::from(s.into_bytes())),
+        MsgPackValue::Binary(bytes) | MsgPackValue::Ext(_, bytes) => {
+            Value::from(Bytes::from(bytes))
+        }
+        MsgPackValue::Array(values) => values.into_iter().map(msgpack_value_to_value).collect(),
+        MsgPackValue::Map(entries) => entries
+            .into_iter()
+            .map(|(k, v)| (msgpack_key_to_string(k), msgpack_value_to_value(v)))
+            .collect(),
+    }
+}
+
+fn msgpack_error(s: String) -> ErrorMessage {
+    ErrorMessage::new(StatusCode::BAD_REQUEST, format!("Bad MessagePack: {}", s))
+}
+
+fn msgpack_value_to_type_string(value: &MsgPackValue) -> &'static str {
+    match value {
+        MsgPackValue::Nil => "Nil",
+        MsgPackValue::Boolean(_) => "Boolean",
+        MsgPackValue::Integer(_) => "Integer",
+        MsgPackValue::F32(_) | MsgPackValue::F64(_) => "Float",
+        MsgPackValue::String(_) => "String",
+        MsgPackValue::Binary(_) => "Binary",
+        MsgPackValue::Array(_) => "Array",
+        MsgPackValue::Map(_) => "Map",
+        MsgPackValue::Ext(_, _) => "Ext",
+    }
+}
+
+fn avro_error(s: String) -> ErrorMessage {
+    ErrorMessage::new(StatusCode::BAD_REQUEST, format!("Bad Avro: {}", s))
+}
+
+fn json_parse_object(value: JsonValue, body_key: Option<&str>) -> Result<Event, ErrorMessage> {
+    let mut event = Event::new_empty_log();
+    let log = event.as_mut_log();
+    match value {
+        JsonValue::Object(map) => {
+            match body_key {
+                Some(key) => {
+                    log.insert(key, JsonValue::Object(map));
+                }
+                None => {
+                    for (k, v) in map {
+                        log.insert_flat(k, v);
+                    }
+                }
+            }
+            Ok(event)
+        }
+        _ => Err(json_error(format!(
+            "Expected Object, got {}",
+            json_value_to_type_string(&value)
+        ))),
+    }
+}
+
+/// Replaces nested objects, and arrays if `arrays` is set, with fields named
+/// after their path, such as `a.b.c` or `a.0`. Empty ones are kept as they are.
+fn flatten_object(
+    object: Map<String, JsonValue>,
+    separator: &str,
+    arrays: bool,
+) -> Map<String, JsonValue> {
+    fn flatten_into(
+        flattened: &mut Map<String, JsonValue>,
+        key: String,
+        value: JsonValue,
+        separator: &str,
+        arrays: bool,
+    ) {
+        match value {
+            JsonValue::Object(object) if !object.is_empty() => {
+                for (name, value) in object {
+                    let key = format!("{}{}{}", key, separator, name);
+                    flatten_into(flattened, key, value, separator, arrays);
+                }
+            }
+            JsonValue::Array(values) if arrays && !values.is_empty() => {
+                for (index, value) in values.into_iter().enumerate() {
+                    let key = format!("{}{}{}", key, separator, index);
+                    flatten_into(flattened, key, value, separator, arrays);
+                }
+            }
+            value => {
+                flattened.insert(key, value);
+            }
+        }
+    }
+
+    let mut flattened = Map::new();
+    for (key, value) in object {
+        flatten_into(&mut flattened, key, value, separator, arrays);
+    }
+    flattened
+}
+
+/// Takes the array or object at an RFC 6901 JSON pointer, such as
+/// `/data/events`, out of an envelope.
+fn json_pointer_target(mut value: JsonValue, pointer: &str) -> Result<JsonValue, ErrorMessage> {
+    match value.pointer_mut(pointer).map(JsonValue::take) {
+        Some(target @ JsonValue::Array(_)) | Some(target @ JsonValue::Object(_)) => Ok(target),
+        Some(target) => Err(json_error(format!(
+            "Expected Array or Object at {}, got {}",
+            pointer,
+            json_value_to_type_string(&target)
+        ))),
+        None => Err(json_error(format!("Nothing found at {}", pointer))),
+    }
+}
+
+fn json_parse_array_of_object(
+    value: JsonValue,
+    body_key: Option<&str>,
+    on_error: ArrayErrorMode,
+) -> Result<Vec<Event>, ErrorMessage> {
+    match value {
+        JsonValue::Array(v) if on_error == ArrayErrorMode::SkipInvalid => {
+            let count = v.len();
+            let events = v
+                .into_iter()
+                .filter_map(|value| json_parse_object(value, body_key).ok())
+                .collect::<Vec<_>>();
+            if events.len() < count {
+                emit!(HTTPInvalidEventsDropped {
+                    count: count - events.len(),
+                    reason: "not a JSON object",
+                });
+            }
+            Ok(events)
+        }
+        JsonValue::Array(v) => v
+            .into_iter()
+            .map(|value| json_parse_object(value, body_key))
+            .collect::<Result<_, _>>(),
+        JsonValue::Object(map) => {
+            //treat like an array of one object
+            Ok(vec![json_parse_object(JsonValue::Object(map), body_key)?])
+        }
+        _ => Err(json_error(format!(
+            "Expected Array or Object, got {}.",
+            json_value_to_type_string(&value)
+        ))),
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum HttpMetricKind {
+    Counter,
+    Gauge,
+}
+
+#[derive(Deserialize)]
+struct HttpMetric {
+    name: String,
+    kind: HttpMetricKind,
+    value: f64,
+    tags: Option<BTreeMap<String, String>>,
+    timestamp: Option<DateTime<Utc>>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum HttpMetrics {
+    One(HttpMetric),
+    Many(Vec<HttpMetric>),
+}
+
+fn metric_parse_body(body: &[u8]) -> Result<Vec<Event>, ErrorMessage> {
+    let metrics = match serde_json::from_slice(body).map_err(|error| {
+        ErrorMessage::new(StatusCode::BAD_REQUEST, format!("Bad metric: {}", error))
+    })? {
+        HttpMetrics::One(metric) => vec![metric],
+        HttpMetrics::Many(metrics) => metrics,
+    };
+
+    Ok(metrics
+        .into_iter()
+        .map(|metric| {
+            let (kind, value) = match metric.kind {
+                HttpMetricKind::Counter => (
+                    MetricKind::Incremental,
+                    MetricValue::Counter {
+                        value: metric.value,
+                    },
+                ),
+                HttpMetricKind::Gauge => (
+                    MetricKind::Absolute,
+                    MetricValue::Gauge {
+                        value: metric.value,
+                    },
+                ),
+            };
+            Metric::new(metric.name, kind, value)
+                .with_tags(metric.tags)
+                .with_timestamp(Some(metric.timestamp.unwrap_or_else(Utc::now)))
+                .into()
+        })
+        .collect())
+}
+
+fn json_error(s: String) -> ErrorMessage {
+    ErrorMessage::new(StatusCode::BAD_REQUEST, format!("Bad JSON: {}", s))
+}
+
+fn json_value_to_type_string(value: &JsonValue) -> &'static str {
+    match value {
+        JsonValue::Object(_) => "Object",
+        JsonValue::Array(_) => "Array",
+        JsonValue::String(_) => "String",
+        JsonValue::Number(_) => "Number",
+        JsonValue::Bool(_) => "Bool",
+        JsonValue::Null => "Null",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        avro_value_to_value, body_to_lines, parse_json, ArrayErrorMode, AvroValue, CorsConfig,
+        EmptyBodyMode, Encoding, HttpSourceAuthConfig, IdempotencyConfig, InvalidUtf8,
+        RateLimitConfig, RouteConfig, SchemaMode, SimpleHttpConfig, TimestampFormat,
+        TimestampPrecedence, TrailingSlash, WhenFull,
+    };
+
+    use crate::shutdown::ShutdownSignal;
+    use crate::{
+        config::{log_schema, GlobalOptions, SourceConfig},
+        event::{Event, MetricKind, MetricValue, Value},
+        test_util::{collect_n, collect_ready, next_addr, trace_init, wait_for, wait_for_tcp},
+        tls::{TlsConfig, TlsOptions, TEST_PEM_CRT_PATH, TEST_PEM_KEY_PATH},
+        Pipeline,
+    };
+    use bytes::Bytes;
+    use chrono::{TimeZone, Utc};
+    use flate2::{
+        read::GzDecoder,
+        write::{DeflateEncoder, GzEncoder},
+        Compression,
+    };
+    use futures::{SinkExt, StreamExt};
+    use http::{HeaderMap, Method};
+    use pretty_assertions::assert_eq;
+    use std::collections::BTreeMap;
+    use std::io::{Read, Write};
+    use std::net::SocketAddr;
+    use std::time::Duration;
+    use tokio::sync::mpsc;
+    use tokio_tungstenite::tungstenite::Message;
+
+    #[test]
+    fn generate_config() {
+        crate::test_util::test_generate_config::<SimpleHttpConfig>();
+    }
+
+    async fn source(
+        encoding: Encoding,
+        headers: Vec<String>,
+        query_parameters: Vec<String>,
+    ) -> (mpsc::Receiver<Event>, SocketAddr) {
+        source_with_config(|config| {
+            config.encoding = encoding;
+            config.headers = headers;
+            config.query_parameters = query_parameters;
+        })
+        .await
+    }
+
+    async fn source_with_config(
+        configure: impl FnOnce(&mut SimpleHttpConfig),
+    ) -> (mpsc::Receiver<Event>, SocketAddr) {
+        let (sender, recv) = Pipeline::new_test();
+        let address = next_addr();
+        let mut config: SimpleHttpConfig =
+            toml::from_str(&format!(r#"address = "{}""#, address)).unwrap();
+        configure(&mut config);
+        tokio::spawn(async move {
+            config
+                .build(
+                    "default",
+                    &GlobalOptions::default(),
+                    ShutdownSignal::noop(),
+                    sender,
+                )
+                .await
+                .unwrap()
+                .await
+                .unwrap();
+        });
+        wait_for_tcp(address).await;
+        (recv, address)
+    }
+
+    async fn send(address: SocketAddr, body: &str) -> u16 {
+        reqwest::Client::new()
+            .post(&format!("http://{}/", address))
+            .body(body.to_owned())
+            .send()
+            .await
+            .unwrap()
+            .status()
+            .as_u16()
+    }
+
+    async fn send_with_headers(address: SocketAddr, body: &str, headers: HeaderMap) -> u16 {
+        reqwest::Client::new()
+            .post(&format!("http://{}/", address))
+            .headers(headers)
+            .body(body.to_owned())
+            .send()
+            .await
+            .unwrap()
+            .status()
+            .as_u16()
+    }
+
+    async fn send_with_query(address: SocketAddr, body: &str, query: &str) -> u16 {
+        reqwest::Client::new()
+            .post(&format!("http://{}?{}", address, query))
+            .body(body.to_owned())
+            .send()
+            .await
+            .unwrap()
+            .status()
+            .as_u16()
+    }
+
+    async fn send_with_path(address: SocketAddr, body: &str, path: &str) -> u16 {
+        reqwest::Client::new()
+            .post(&format!("http://{}{}", address, path))
+            .body(body.to_owned())
+            .send()
+            .await
+            .unwrap()
+            .status()
+            .as_u16()
+    }
+
+    async fn send_bytes(address: SocketAddr, body: Vec<u8>, headers: HeaderMap) -> u16 {
+        reqwest::Client::new()
+            .post(&format!("http://{}/", address))
+            .headers(headers)
+            .body(body)
+            .send()
+            .await
+            .unwrap()
+            .status()
+            .as_u16()
+    }
+
+    #[tokio::test]
+    async fn http_multiline_text() {
+        trace_init();
+
+        let body = "test body\n\ntest body 2";
+
+        let (rx, addr) = source(Encoding::default(), vec![], vec![]).await;
+
+        assert_eq!(200, send(addr, body).await);
+
+        let mut events = collect_n(rx, 2).await;
+        {
+            let event = events.remove(0);
+            let log = event.as_log();
+            assert_eq!(log[log_schema().message_key()], "test body".into());
+            assert!(log.get(log_schema().timestamp_key()).is_some());
+            assert_eq!(log[log_schema().source_type_key()], "http".into());
+        }
+        {
+            let event = events.remove(0);
+            let log = event.as_log();
+            assert_eq!(log[log_schema().message_key()], "test body 2".into());
+            assert!(log.get(log_schema().timestamp_key()).is_some());
+            assert_eq!(log[log_schema().source_type_key()], "http".into());
+        }
+    }
+
+    #[tokio::test]
+    async fn http_multiline_text2() {
+        trace_init();
+
+        //same as above test but with a newline at the end
+        let body = "test body\n\ntest body 2\n";
+
+        let (rx, addr) = source(Encoding::default(), vec![], vec![]).await;
+
+        assert_eq!(200, send(addr, body).await);
+
+        let mut events = collect_n(rx, 2).await;
+        {
+            let event = events.remove(0);
+            let log = event.as_log();
+            assert_eq!(log[log_schema().message_key()], "test body".into());
+            assert!(log.get(log_schema().timestamp_key()).is_some());
+            assert_eq!(log[log_schema().source_type_key()], "http".into());
+        }
+        {
+            let event = events.remove(0);
+            let log = event.as_log();
+            assert_eq!(log[log_schema().message_key()], "test body 2".into());
+            assert!(log.get(log_schema().timestamp_key()).is_some());
+            assert_eq!(log[log_schema().source_type_key()], "http".into());
+        }
+    }
+
+    #[tokio::test]
+    async fn http_json_parsing() {
+        trace_init();
+
+        let (rx, addr) = source(Encoding::Json, vec![], vec![]).await;
+
+        assert_eq!(400, send(addr, "{").await); //malformed
+        assert_eq!(400, send(addr, r#"{"key"}"#).await); //key without value
+
+        assert_eq!(200, send(addr, "{}").await); //can be one object or array of objects
+        assert_eq!(200, send(addr, "[{},{},{}]").await);
+
+        let mut events = collect_n(rx, 2).await;
+        assert!(events
+            .remove(1)
+            .as_log()
+            .get(log_schema().timestamp_key())
+            .is_some());
+        assert!(events
+            .remove(0)
+            .as_log()
+            .get(log_schema().timestamp_key())
+            .is_some());
+    }
+
+    #[tokio::test]
+    async fn http_json_values() {
+        trace_init();
+
+        let (rx, addr) = source(Encoding::Json, vec![], vec![]).await;
+
+        assert_eq!(200, send(addr, r#"[{"key":"value"}]"#).await);
+        assert_eq!(200, send(addr, r#"{"key2":"value2"}"#).await);
+
+        let mut events = collect_n(rx, 2).await;
+        {
+            let event = events.remove(0);
+            let log = event.as_log();
+            assert_eq!(log["key"], "value".into());
+            assert!(log.get(log_schema().timestamp_key()).is_some());
+            assert_eq!(log[log_schema().source_type_key()], "http".into());
+        }
+        {
+            let event = events.remove(0);
+            let log = event.as_log();
+            assert_eq!(log["key2"], "value2".into());
+            assert!(log.get(log_schema().timestamp_key()).is_some());
+            assert_eq!(log[log_schema().source_type_key()], "http".into());
+        }
+    }
+
+    #[tokio::test]
+    async fn http_json_numbers() {
+        trace_init();
+
+        let (rx, addr) = source(Encoding::Json, vec![], vec![]).await;
+
+        assert_eq!(
+            200,
+            send(
+                addr,
+                r#"{"id":9007199254740993,"big":18446744073709551615,"ratio":1.0,"nested":{"id":-9007199254740993}}"#
+            )
+            .await
+        );
+
+        let mut events = collect_n(rx, 1).await;
+        let event = events.remove(0);
+        let log = event.as_log();
+        assert_eq!(log["id"], Value::Integer(9_007_199_254_740_993));
+        // Integers too large for an i64 are the nearest float
+        assert_eq!(log["big"], Value::Float(u64::MAX as f64));
+        assert_eq!(log["ratio"], Value::Float(1.0));
+        assert_eq!(log["nested.id"], Value::Integer(-9_007_199_254_740_993));
+    }
+
+    #[tokio::test]
+    async fn http_json_dotted_keys() {
+        trace_init();
+
+        let (rx, addr) = source(Encoding::Json, vec![], vec![]).await;
+
+        assert_eq!(200, send(addr, r#"[{"dotted.key":"value"}]"#).await);
+        assert_eq!(
+            200,
+            send(addr, r#"{"nested":{"dotted.key2":"value2"}}"#).await
+        );
+
+        let mut events = collect_n(rx, 2).await;
+        {
+            let event = events.remove(0);
+            let log = event.as_log();
+            assert_eq!(log.get_flat("dotted.key").unwrap(), &Value::from("value"));
+        }
+        {
+            let event = events.remove(0);
+            let log = event.as_log();
+            let mut map = BTreeMap::new();
+            map.insert("dotted.key2".to_string(), Value::from("value2"));
+            assert_eq!(log["nested"], map.into());
+        }
+    }
+
+    #[tokio::test]
+    async fn http_ndjson() {
+        trace_init();
+
+        let (rx, addr) = source(Encoding::Ndjson, vec![], vec![]).await;
+
+        assert_eq!(400, send(addr, r#"[{"key":"value"}]"#).await); //one object per line
+
+        assert_eq!(
+            200,
+            send(addr, "{\"key1\":\"value1\"}\n\n{\"key2\":\"value2\"}").await
+        );
+
+        let mut events = collect_n(rx, 2).await;
+        {
+            let event = events.remove(0);
+            let log = event.as_log();
+            assert_eq!(log["key1"], "value1".into());
+            assert!(log.get(log_schema().timestamp_key()).is_some());
+            assert_eq!(log[log_schema().source_type_key()], "http".into());
+        }
+        {
+            let event = events.remove(0);
+            let log = event.as_log();
+            assert_eq!(log["key2"], "value2".into());
+            assert!(log.get(log_schema().timestamp_key()).is_some());
+            assert_eq!(log[log_schema().source_type_key()], "http".into());
+        }
+    }
+
+    #[tokio::test]
+    async fn http_csv() {
+        trace_init();
+
+        let (rx, addr) = source_with_config(|config| {
+            config.encoding = Encoding::Csv;
+            config.csv_headers = Some(vec!["name".to_string(), "amount".to_string()]);
+        })
+        .await;
+
+        assert_eq!(400, send(addr, "a,b\nc,d,e\n").await); //unequal row lengths
+
+        assert_eq!(200, send(addr, "alice,10\n\"bob, jr.\",\"20\n30\"\n").await);
+
+        let mut events = collect_n(rx, 2).await;
+        {
+            let event = events.remove(0);
+            let log = event.as_log();
+            assert_eq!(log["name"], "alice".into());
+            assert_eq!(log["amount"], "10".into());
+            assert!(log.get(log_schema().timestamp_key()).is_some());
+            assert_eq!(log[log_schema().source_type_key()], "http".into());
+        }
+        {
+            let event = events.remove(0);
+            let log = event.as_log();
+            assert_eq!(log["name"], "bob, jr.".into());
+            assert_eq!(log["amount"], "20\n30".into());
+        }
+    }
+
+    #[tokio::test]
+    async fn http_csv_without_headers() {
+        trace_init();
+
+        let (rx, addr) = source(Encoding::Csv, vec![], vec![]).await;
+
+        assert_eq!(200, send(addr, "a,b\n").await);
+
+        let mut events = collect_n(rx, 1).await;
+        {
+            let event = events.remove(0);
+            let log = event.as_log();
+            assert_eq!(log["col0"], "a".into());
+            assert_eq!(log["col1"], "b".into());
+        }
+    }
+
+    #[tokio::test]
+    async fn http_avro() {
+        trace_init();
+
+        let schema = avro_rs::Schema::parse_str(
+            r#"{
+                "type": "record",
+                "name": "test",
+                "fields": [
+                    {"name": "key", "type": "string"},
+                    {"name": "count", "type": ["null", "long"]},
+                    {"name": "tags", "type": {"type": "map", "values": "string"}}
+                ]
+            }"#,
+        )
+        .unwrap();
+        let mut writer = avro_rs::Writer::new(&schema, Vec::new());
+        for (key, count) in &[("value1", Some(1i64)), ("value2", None)] {
+            let mut record = avro_rs::types::Record::new(writer.schema()).unwrap();
+            record.put("key", *key);
+            record.put("count", *count);
+            record.put(
+                "tags",
+                avro_rs::types::Value::Map(
+                    vec![("env".to_string(), avro_rs::types::Value::from("prod"))]
+                        .into_iter()
+                        .collect(),
+                ),
+            );
+            writer.append(record).unwrap();
+        }
+        let body = writer.into_inner().unwrap();
+
+        let (rx, addr) = source(Encoding::Avro, vec![], vec![]).await;
+
+        assert_eq!(400, send(addr, "not avro").await);
+        assert_eq!(200, send_bytes(addr, body, HeaderMap::new()).await);
+
+        let mut events = collect_n(rx, 2).await;
+        {
+            let event = events.remove(0);
+            let log = event.as_log();
+            assert_eq!(log["key"], "value1".into());
+            assert_eq!(log["count"], 1.into());
+            assert_eq!(log["tags.env"], "prod".into());
+            assert!(log.get(log_schema().timestamp_key()).is_some());
+            assert_eq!(log[log_schema().source_type_key()], "http".into());
+        }
+        {
+            let event = events.remove(0);
+            let log = event.as_log();
+            assert_eq!(log["key"], "value2".into());
+            assert_eq!(log["count"], Value::Null);
+        }
+    }
+
+    #[test]
+    fn avro_rejects_out_of_range_timestamps() {
+        for value in vec![
+            AvroValue::TimestampMillis(i64::MAX),
+            AvroValue::TimestampMicros(i64::MIN),
+            AvroValue::Array(vec![AvroValue::TimestampMillis(i64::MIN)]),
+        ] {
+            assert_eq!(avro_value_to_value(value).unwrap_err().code(), 400);
+        }
+        assert_eq!(
+            avro_value_to_value(AvroValue::TimestampMillis(1_612_325_106_000)).unwrap(),
+            Value::from(Utc.ymd(2021, 2, 3).and_hms(4, 5, 6))
+        );
+    }
+
+    #[tokio::test]
+    async fn http_msgpack() {
+        trace_init();
+
+        let encode = |value: rmpv::Value| {
+            let mut body = Vec::new();
+            rmpv::encode::write_value(&mut body, &value).unwrap();
+            body
+        };
+
+        let (rx, addr) = source(Encoding::MessagePack, vec![], vec![]).await;
+
+        assert_eq!(
+            400,
+            send_bytes(addr, encode(rmpv::Value::from("string")), HeaderMap::new()).await
+        );
+
+        let object = |key: &str, value: &str| {
+            rmpv::Value::Map(vec![(rmpv::Value::from(key), rmpv::Value::from(value))])
+        };
+        assert_eq!(
+            200,
+            send_bytes(addr, encode(object("key1", "value1")), HeaderMap::new()).await
+        );
+        assert_eq!(
+            200,
+            send_bytes(
+                addr,
+                encode(rmpv::Value::Array(vec![object("key2", "value2")])),
+                HeaderMap::new()
+            )
+            .await
+        );
+
+        let mut events = collect_n(rx, 2).await;
+        {
+            let event = events.remove(0);
+            let log = event.as_log();
+            assert_eq!(log["key1"], "value1".into());
+            assert!(log.get(log_schema().timestamp_key()).is_some());
+            assert_eq!(log[log_schema().source_type_key()], "http".into());
+        }
+        {
+            let event = events.remove(0);
+            let log = event.as_log();
+            assert_eq!(log["key2"], "value2".into());
+            assert!(log.get(log_schema().timestamp_key()).is_some());
+        }
+    }
+
+    #[tokio::test]
+    async fn http_protobuf_requires_descriptor() {
+        let config: SimpleHttpConfig = toml::from_str(
+            r#"
+            address = "127.0.0.1:9000"
+            encoding = "protobuf"
+            "#,
+        )
+        .unwrap();
+
+        let result = config
+            .build(
+                "default",
+                &GlobalOptions::default(),
+                ShutdownSignal::noop(),
+                Pipeline::new_test().0,
+            )
+            .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn http_rejects_unsupported_default_encoding() {
+        for default_encoding in &["es_bulk", "influx_line", "prometheus_remote_write"] {
+            let config: SimpleHttpConfig = toml::from_str(&format!(
+                r#"
+                address = "127.0.0.1:9000"
+                encoding = "auto"
+                default_encoding = "{}"
+                "#,
+                default_encoding
+            ))
+            .unwrap();
+
+            let result = config
+                .build(
+                    "default",
+                    &GlobalOptions::default(),
+                    ShutdownSignal::noop(),
+                    Pipeline::new_test().0,
+                )
+                .await;
+            assert!(result.is_err());
+        }
+    }
+
+    #[tokio::test]
+    async fn http_auto_encoding() {
+        trace_init();
+
+        let (rx, addr) = source(Encoding::Auto, vec![], vec![]).await;
+
+        let mut headers = HeaderMap::new();
+        headers.insert("Content-Type", "application/json".parse().unwrap());
+        assert_eq!(
+            200,
+            send_with_headers(addr, r#"[{"key":"value"}]"#, headers).await
+        );
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "Content-Type",
+            "application/x-ndjson; charset=utf-8".parse().unwrap(),
+        );
+        assert_eq!(
+            200,
+            send_with_headers(addr, "{\"key\":\"value2\"}\n", headers).await
+        );
+
+        // missing header falls back to the default encoding
+        assert_eq!(200, send(addr, r#"{"key":"value3"}"#).await);
+
+        let mut events = collect_n(rx, 3).await;
+        assert_eq!(events.remove(0).as_log()["key"], "value".into());
+        assert_eq!(events.remove(0).as_log()["key"], "value2".into());
+        assert_eq!(
+            events.remove(0).as_log()[log_schema().message_key()],
+            r#"{"key":"value3"}"#.into()
+        );
+    }
+
+    #[tokio::test]
+    async fn http_form() {
+        trace_init();
+
+        let (rx, addr) = source(Encoding::Form, vec![], vec![]).await;
+
+        assert_eq!(
+            200,
+            send(addr, "key=value+1&tag=a&tag=b&escaped%20key=%26%3D").await
+        );
+        assert_eq!(200, send(addr, "").await);
+
+        let mut events = collect_n(rx, 2).await;
+        {
+            let event = events.remove(0);
+            let log = event.as_log();
+            assert_eq!(log["key"], "value 1".into());
+            assert_eq!(log["tag"], Value::from(vec!["a", "b"]));
+            assert_eq!(log.get_flat("escaped key").unwrap(), &Value::from("&="));
+            assert!(log.get(log_schema().timestamp_key()).is_some());
+            assert_eq!(log[log_schema().source_type_key()], "http".into());
+        }
+        {
+            let event = events.remove(0);
+            let log = event.as_log();
+            assert!(log.get(log_schema().timestamp_key()).is_some());
+            assert_eq!(log.keys().count(), 2);
+        }
+    }
+
+    #[tokio::test]
+    async fn http_multipart() {
+        trace_init();
+
+        let (rx, addr) = source(Encoding::default(), vec![], vec![]).await;
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "Content-Type",
+            "multipart/form-data; boundary=xyz".parse().unwrap(),
+        );
+        let body = "--xyz\r\n\
+                    Content-Disposition: form-data; name=\"meta\"\r\n\
+                    Content-Type: application/json\r\n\
+                    \r\n\
+                    {\"key\":\"value\"}\r\n\
+                    --xyz\r\n\
+                    Content-Disposition: form-data; name=\"note\"\r\n\
+                    \r\n\
+                    some text\r\n\
+                    --xyz\r\n\
+                    Content-Disposition: form-data; name=\"upload\"; filename=\"a.bin\"\r\n\
+                    Content-Type: application/octet-stream\r\n\
+                    \r\n\
+                    raw\r\n\
+                    --xyz--\r\n";
+
+        assert_eq!(200, send_with_headers(addr, body, headers).await);
+
+        let mut events = collect_n(rx, 3).await;
+        {
+            let event = events.remove(0);
+            let log = event.as_log();
+            assert_eq!(log["meta.key"], "value".into());
+            assert!(log.get(log_schema().timestamp_key()).is_some());
+            assert_eq!(log[log_schema().source_type_key()], "http".into());
+        }
+        {
+            let event = events.remove(0);
+            let log = event.as_log();
+            assert_eq!(log["note"], "some text".into());
+        }
+        {
+            let event = events.remove(0);
+            let log = event.as_log();
+            assert_eq!(log["upload.filename"], "a.bin".into());
+            assert_eq!(log["upload.content"], "raw".into());
+        }
+    }
+
+    #[tokio::test]
+    async fn http_xml() {
+        trace_init();
+
+        let (rx, addr) = source(Encoding::Xml, vec![], vec![]).await;
+
+        assert_eq!(400, send(addr, "<root><unclosed></root>").await);
+
+        assert_eq!(
+            200,
+            send(
+                addr,
+                r#"<?xml version="1.0"?>
+                <order id="42">
+                    <item>one</item>
+                    <item>two</item>
+                    <note lang="en">hello</note>
+                    <empty/>
+                </order>"#
+            )
+            .await
+        );
+
+        let mut events = collect_n(rx, 1).await;
+        {
+            let event = events.remove(0);
+            let log = event.as_log();
+            assert_eq!(log["order.@attributes.id"], "42".into());
+            assert_eq!(log["order.item"], Value::from(vec!["one", "two"]));
+            assert_eq!(log["order.note.@attributes.lang"], "en".into());
+            assert_eq!(log["order.note.#text"], "hello".into());
+            assert_eq!(log["order.empty"], Value::Null);
+            assert!(log.get(log_schema().timestamp_key()).is_some());
+            assert_eq!(log[log_schema().source_type_key()], "http".into());
+        }
+    }
+
+    #[tokio::test]
+    async fn http_logfmt() {
+        trace_init();
+
+        let (rx, addr) = source(Encoding::Logfmt, vec![], vec![]).await;
+
+        assert_eq!(400, send(addr, r#"msg="unterminated"#).await);
+
+        assert_eq!(
+            200,
+            send(
+                addr,
+                "level=info msg=\"hello world\" count=3 ratio=0.5 ok=true quoted=\"3\" debug\n\nlevel=warn\n"
+            )
+            .await
+        );
+
+        let mut events = collect_n(rx, 2).await;
+        {
+            let event = events.remove(0);
+            let log = event.as_log();
+            assert_eq!(log["level"], "info".into());
+            assert_eq!(log["msg"], "hello world".into());
+            assert_eq!(log["count"], 3.into());
+            assert_eq!(log["ratio"], 0.5.into());
+            assert_eq!(log["ok"], true.into());
+            assert_eq!(log["quoted"], "3".into());
+            assert_eq!(log["debug"], true.into());
+            assert!(log.get(log_schema().timestamp_key()).is_some());
+            assert_eq!(log[log_schema().source_type_key()], "http".into());
+        }
+        {
+            let event = events.remove(0);
+            let log = event.as_log();
+            assert_eq!(log["level"], "warn".into());
+        }
+    }
+
+    #[tokio::test]
+    async fn http_framing_delimiter() {
+        trace_init();
+
+        let (rx, addr) = source_with_config(|config| {
+            config.encoding = Encoding::Ndjson;
+            config.framing_delimiter = Some('\x1e');
+        })
+        .await;
+
+        assert_eq!(
+            200,
+            send(
+                addr,
+                "\x1e{\"key1\":\"value1\"}\n\x1e{\"key2\":\"value2\"}\n"
+            )
+            .await
+        );
+
+        let mut events = collect_n(rx, 2).await;
+        assert_eq!(events.remove(0).as_log()["key1"], "value1".into());
+        assert_eq!(events.remove(0).as_log()["key2"], "value2".into());
+    }
+
+    #[tokio::test]
+    async fn http_ndjson_streamed_lines() {
+        trace_init();
+
+        let (rx, addr) = source_with_config(|config| {
+            config.encoding = Encoding::Ndjson;
+        })
+        .await;
+
+        let send_chunks = |chunks: &'static [&'static str]| {
+            let (mut sender, body) = hyper::Body::channel();
+            let request = http::Request::post(format!("http://{}/", addr))
+                .body(body)
+                .unwrap();
+            let response = tokio::spawn(hyper::Client::new().request(request));
+            async move {
+                for chunk in chunks {
+                    sender.send_data(Bytes::from(*chunk)).await.unwrap();
+                }
+                drop(sender);
+                response.await.unwrap().unwrap().status().as_u16()
+            }
+        };
+
+        // Lines split across chunks, with the last one unterminated
+        assert_eq!(
+            200,
+            send_chunks(&[
+                "{\"key\":\"o",
+                "ne\"}\r\n{\"key\":",
+                "\"two\"}\n\n{\"key\":\"three\"}"
+            ])
+            .await
+        );
+        // A bad line rejects the request, though the lines before it were sent
+        assert_eq!(
+            400,
+            send_chunks(&["{\"key\":\"four\"}\nnot json\n", "{\"key\":\"five\"}\n"]).await
+        );
+
+        let events = collect_ready(rx).await;
+        assert_eq!(events.len(), 4);
+        assert_eq!(events[0].as_log()["key"], "one".into());
+        assert_eq!(events[1].as_log()["key"], "two".into());
+        assert_eq!(events[2].as_log()["key"], "three".into());
+        assert_eq!(events[3].as_log()["key"], "four".into());
+    }
+
+    #[tokio::test]
+    async fn http_ndjson_lines_sent_before_body_ends() {
+        trace_init();
+
+        let (mut rx, addr) = source_with_config(|config| {
+            config.encoding = Encoding::Ndjson;
+        })
+        .await;
+
+        let (mut sender, body) = hyper::Body::channel();
+        let request = http::Request::post(format!("http://{}/", addr))
+            .body(body)
+            .unwrap();
+        let response = tokio::spawn(hyper::Client::new().request(request));
+
+        sender
+            .send_data(Bytes::from("{\"key\":\"one\"}\n{\"key\":"))
+            .await
+            .unwrap();
+        let event = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+            .await
+            .expect("The first line wasn't sent before the body ended")
+            .unwrap();
+        assert_eq!(event.as_log()["key"], "one".into());
+
+        sender.send_data(Bytes::from("\"two\"}\n")).await.unwrap();
+        drop(sender);
+        assert_eq!(200, response.await.unwrap().unwrap().status().as_u16());
+
+        let events = collect_n(rx, 1).await;
+        assert_eq!(events[0].as_log()["key"], "two".into());
+    }
+
+    #[tokio::test]
+    async fn http_response_code() {
+        trace_init();
+
+        let (rx, addr) = source_with_config(|config| {
+            config.response_code = 202;
+        })
+        .await;
+
+        assert_eq!(202, send(addr, "test body\n").await);
+
+        let mut events = collect_n(rx, 1).await;
+        assert_eq!(
+            events.remove(0).as_log()[log_schema().message_key()],
+            "test body".into()
+        );
+    }
+
+    #[tokio::test]
+    async fn http_response_body() {
+        trace_init();
+
+        let (_rx, addr) = source_with_config(|config| {
+            config.response_body = Some(r#"{"ok":true}"#.to_owned());
+            config.response_content_type = Some("application/json".to_owned());
+        })
+        .await;
+
+        let response = reqwest::Client::new()
+            .post(&format!("http://{}/", addr))
+            .body("test body")
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(200, response.status().as_u16());
+        assert_eq!(
+            response.headers()["content-type"].to_str().unwrap(),
+            "application/json"
+        );
+        assert_eq!(response.text().await.unwrap(), r#"{"ok":true}"#);
+    }
+
+    #[tokio::test]
+    async fn http_response_gzip() {
+        trace_init();
+
+        let body = "x".repeat(100);
+        let (_rx, addr) = source_with_config(|config| {
+            config.response_body = Some(body.clone());
+            config.response_gzip_threshold = Some(50);
+        })
+        .await;
+
+        let response = reqwest::Client::new()
+            .post(&format!("http://{}/", addr))
+            .header("Accept-Encoding", "gzip")
+            .body("test body")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(200, response.status().as_u16());
+        assert_eq!(
+            response.headers()["content-encoding"].to_str().unwrap(),
+            "gzip"
+        );
+        let compressed = response.bytes().await.unwrap();
+        let mut decompressed = String::new();
+        GzDecoder::new(&compressed[..])
+            .read_to_string(&mut decompressed)
+            .unwrap();
+        assert_eq!(decompressed, body);
+
+        // Clients not accepting gzip get the body as is
+        let response = reqwest::Client::new()
+            .post(&format!("http://{}/", addr))
+            .body("test body")
+            .send()
+            .await
+            .unwrap();
+        assert!(response.headers().get("content-encoding").is_none());
+        assert_eq!(response.text().await.unwrap(), body);
+    }
+
+    #[tokio::test]
+    async fn http_response_gzip_below_threshold() {
+        trace_init();
+
+        let (_rx, addr) = source_with_config(|config| {
+            config.response_body = Some(r#"{"ok":true}"#.to_owned());
+            config.response_gzip_threshold = Some(50);
+        })
+        .await;
+
+        let response = reqwest::Client::new()
+            .post(&format!("http://{}/", addr))
+            .header("Accept-Encoding", "gzip")
+            .body("test body")
+            .send()
+            .await
+            .unwrap();
+        assert!(response.headers().get("content-encoding").is_none());
+        assert_eq!(response.text().await.unwrap(), r#"{"ok":true}"#);
+    }
+
+    #[tokio::test]
+    async fn http_response_headers() {
+        trace_init();
+
+        let (_rx, addr) = source_with_config(|config| {
+            config
+                .response_headers
+                .insert("X-Vector-Received".to_owned(), "true".to_owned());
+        })
+        .await;
+
+        let response = reqwest::Client::new()
+            .post(&format!("http://{}/", addr))
+            .body("test body")
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(200, response.status().as_u16());
+        assert_eq!(
+            response.headers()["x-vector-received"].to_str().unwrap(),
+            "true"
+        );
+    }
+
+    #[tokio::test]
+    async fn http_echo_request_id() {
+        trace_init();
+
+        let (rx, addr) = source_with_config(|config| {
+            config.echo_request_id_header = Some("X-Request-Id".to_owned());
+            config.request_id_key = Some("request_id".to_owned());
+        })
+        .await;
+
+        let response = reqwest::Client::new()
+            .post(&format!("http://{}/", addr))
+            .header("X-Request-Id", "abc-123")
+            .body("test body")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(
+            response.headers()["x-request-id"].to_str().unwrap(),
+            "abc-123"
+        );
+
+        let response = reqwest::Client::new()
+            .post(&format!("http://{}/", addr))
+            .body("test body")
+            .send()
+            .await
+            .unwrap();
+        assert!(response.headers().get("x-request-id").is_none());
+
+        let mut events = collect_n(rx, 2).await;
+        assert_eq!(events.remove(0).as_log()["request_id"], "abc-123".into());
+        assert!(events.remove(0).as_log().get("request_id").is_none());
+    }
+
+    #[tokio::test]
+    async fn http_invalid_response_headers() {
+        for (name, value) in &[("Content-Length", "10"), ("Bad Name", "value")] {
+            let mut config: SimpleHttpConfig =
+                toml::from_str(r#"address = "127.0.0.1:0""#).unwrap();
+            config
+                .response_headers
+                .insert(name.to_string(), value.to_string());
+
+            assert!(config
+                .build(
+                    "default",
+                    &GlobalOptions::default(),
+                    ShutdownSignal::noop(),
+                    Pipeline::new_test().0,
+                )
+                .await
+                .is_err());
+        }
+    }
+
+    #[tokio::test]
+    async fn http_invalid_response_code() {
+        let config: SimpleHttpConfig = toml::from_str(
+            r#"address = "127.0.0.1:0"
+response_code = 404"#,
+        )
+        .unwrap();
+
+        assert!(config
+            .build(
+                "default",
+                &GlobalOptions::default(),
+                ShutdownSignal::noop(),
+                Pipeline::new_test().0,
+            )
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn http_denied_ip() {
+        trace_init();
+
+        let (rx, addr) = source_with_config(|config| {
+            config.denied_ips = vec!["127.0.0.0/8".to_owned()];
+        })
+        .await;
+
+        assert_eq!(403, send(addr, "test body\n").await);
+
+        drop(rx);
+    }
+
+    #[tokio::test]
+    async fn http_brotli_gzip() {
+        trace_init();
+
+        let body = "test body";
+
+        let mut encoder = brotli::CompressorWriter::new(Vec::new(), 4096, 11, 22);
+        encoder.write_all(body.as_bytes()).unwrap();
+        let body = encoder.into_inner();
+
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(body.as_slice()).unwrap();
+        let body = encoder.finish().unwrap();
+
+        let mut headers = HeaderMap::new();
+        headers.insert("Content-Encoding", "br, gzip".parse().unwrap());
+
+        let (rx, addr) = source(Encoding::default(), vec![], vec![]).await;
+
+        assert_eq!(200, send_bytes(addr, body, headers).await);
+
+        let mut headers = HeaderMap::new();
+        headers.insert("Content-Encoding", "compress".parse().unwrap());
+        assert_eq!(415, send_bytes(addr, b"test".to_vec(), headers).await);
+
+        let mut events = collect_n(rx, 1).await;
+        {
+            let event = events.remove(0);
+            let log = event.as_log();
+            assert_eq!(log[log_schema().message_key()], "test body".into());
+            assert!(log.get(log_schema().timestamp_key()).is_some());
+            assert_eq!(log[log_schema().source_type_key()], "http".into());
+        }
+    }
+
+    #[tokio::test]
+    async fn http_gzip_zstd() {
+        trace_init();
+
+        let body = "test body";
+
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(body.as_bytes()).unwrap();
+        let body = encoder.finish().unwrap();
+
+        let body = zstd::stream::encode_all(body.as_slice(), 0).unwrap();
+
+        let mut headers = HeaderMap::new();
+        headers.insert("Content-Encoding", "gzip, zstd".parse().unwrap());
+
+        let (rx, addr) = source(Encoding::default(), vec![], vec![]).await;
+
+        assert_eq!(200, send_bytes(addr, body.clone(), headers.clone()).await);
+
+        // truncated frame
+        assert_eq!(
+            422,
+            send_bytes(addr, body[..body.len() - 4].to_vec(), headers).await
+        );
+
+        let mut events = collect_n(rx, 1).await;
+        {
+            let event = events.remove(0);
+            let log = event.as_log();
+            assert_eq!(log[log_schema().message_key()], "test body".into());
+            assert!(log.get(log_schema().timestamp_key()).is_some());
+            assert_eq!(log[log_schema().source_type_key()], "http".into());
+        }
+    }
+
+    #[tokio::test]
+    async fn http_snappy() {
+        trace_init();
+
+        let mut headers = HeaderMap::new();
+        headers.insert("Content-Encoding", "snappy".parse().unwrap());
+
+        let (rx, addr) = source(Encoding::default(), vec![], vec![]).await;
+
+        let raw = snap::raw::Encoder::new().compress_vec(b"raw body").unwrap();
+        assert_eq!(200, send_bytes(addr, raw, headers.clone()).await);
+
+        let mut encoder = snap::write::FrameEncoder::new(Vec::new());
+        encoder.write_all(b"framed body").unwrap();
+        let framed = encoder.into_inner().unwrap();
+        assert_eq!(200, send_bytes(addr, framed.clone(), headers.clone()).await);
+
+        // corrupt stream
+        let mut corrupt = framed;
+        let len = corrupt.len();
+        corrupt[len - 2] ^= 0xff;
+        assert_eq!(422, send_bytes(addr, corrupt, headers).await);
+
+        let events = collect_n(rx, 2).await;
+        assert_eq!(
+            events[0].as_log()[log_schema().message_key()],
+            "raw body".into()
+        );
+        assert_eq!(
+            events[1].as_log()[log_schema().message_key()],
+            "framed body".into()
+        );
+    }
+
+    #[tokio::test]
+    async fn http_max_body_size() {
+        trace_init();
+
+        let (rx, addr) = source_with_config(|config| {
+            config.max_body_size = Some(64);
+        })
+        .await;
+
+        assert_eq!(200, send(addr, "test body").await);
+        assert_eq!(413, send(addr, &"a".repeat(65)).await);
+
+        // Compresses to well under the limit, but expands past it
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all("a".repeat(4096).as_bytes()).unwrap();
+        let body = encoder.finish().unwrap();
+        assert!(body.len() < 64);
+
+        let mut headers = HeaderMap::new();
+        headers.insert("Content-Encoding", "gzip".parse().unwrap());
+        assert_eq!(413, send_bytes(addr, body, headers).await);
+
+        let mut events = collect_n(rx, 1).await;
+        assert_eq!(
+            events.remove(0).as_log()[log_schema().message_key()],
+            "test body".into()
+        );
+    }
+
+    #[tokio::test]
+    async fn http_rate_limit() {
+        trace_init();
+
+        let (_rx, addr) = source_with_config(|config| {
+            config.rate_limit = Some(RateLimitConfig {
+                requests_per_second: 0.01,
+                burst: Some(1.0),
+            });
+        })
+        .await;
+
+        assert_eq!(200, send(addr, "test body").await);
+
+        let response = reqwest::Client::new()
+            .post(&format!("http://{}/", addr))
+            .body("test body")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(429, response.status().as_u16());
+        assert!(response.headers().contains_key("retry-after"));
+    }
+
+    #[tokio::test]
+    async fn http_get() {
+        trace_init();
+
+        let (rx, addr) = source_with_config(|config| {
+            config.methods = vec!["POST".to_owned(), "get".to_owned()];
+            config.query_parameters = vec!["page".to_owned()];
+        })
+        .await;
+
+        let status = reqwest::Client::new()
+            .get(&format!("http://{}/?page=home", addr))
+            .send()
+            .await
+            .unwrap()
+            .status();
+        assert_eq!(200, status.as_u16());
+
+        let status = reqwest::Client::new()
+            .put(&format!("http://{}/", addr))
+            .body("test body")
+            .send()
+            .await
+            .unwrap()
+            .status();
+        assert_eq!(405, status.as_u16());
+
+        let mut events = collect_n(rx, 1).await;
+        let event = events.remove(0);
+        let log = event.as_log();
+        assert_eq!(log["page"], "home".into());
+        assert!(log.get(log_schema().timestamp_key()).is_some());
+        assert_eq!(log[log_schema().source_type_key()], "http".into());
+    }
+
+    #[tokio::test]
+    async fn http_method_key() {
+        trace_init();
+
+        let (rx, addr) = source_with_config(|config| {
+            config.methods = vec!["POST".to_owned(), "PUT".to_owned(), "PATCH".to_owned()];
+            config.method_key = Some("method".to_owned());
+        })
+        .await;
+
+        for method in &[Method::PUT, Method::PATCH] {
+            let status = reqwest::Client::new()
+                .request(method.clone(), &format!("http://{}/", addr))
+                .body("test body")
+                .send()
+                .await
+                .unwrap()
+                .status();
+            assert_eq!(200, status.as_u16());
+        }
+
+        let mut events = collect_n(rx, 2).await;
+        assert_eq!(events.remove(0).as_log()["method"], "PUT".into());
+        assert_eq!(events.remove(0).as_log()["method"], "PATCH".into());
+    }
+
+    #[tokio::test]
+    async fn http_healthcheck_path() {
+        trace_init();
+
+        let (rx, addr) = source_with_config(|config| {
+            config.healthcheck_path = Some("health".to_owned());
+            config.auth = Some(HttpSourceAuthConfig::basic("user", "pass"));
+        })
+        .await;
+
+        for request in vec![
+            reqwest::Client::new().get(&format!("http://{}/health", addr)),
+            reqwest::Client::new().post(&format!("http://{}/health", addr)),
+        ] {
+            let response = request.send().await.unwrap();
+            assert_eq!(200, response.status().as_u16());
+            assert!(response.text().await.unwrap().is_empty());
+        }
+
+        assert_eq!(401, send(addr, "test body").await);
+
+        drop(rx);
+    }
+
+    #[tokio::test]
+    async fn http_trailing_slash() {
+        trace_init();
+
+        for trailing_slash in &[TrailingSlash::Strict, TrailingSlash::Lenient] {
+            let trailing_slash = *trailing_slash;
+            let (rx, addr) = source_with_config(|config| {
+                config.healthcheck_path = Some("/health/".to_owned());
+                config.trailing_slash = trailing_slash;
+            })
+            .await;
+
+            let status = reqwest::Client::new()
+                .get(&format!("http://{}/health", addr))
+                .send()
+                .await
+                .unwrap()
+                .status();
+            match trailing_slash {
+                TrailingSlash::Strict => assert_eq!(404, status.as_u16()),
+                TrailingSlash::Lenient => assert_eq!(200, status.as_u16()),
+            }
+            for path in &["/health/", "/health//"] {
+                let status = reqwest::Client::new()
+                    .get(&format!("http://{}{}", addr, path))
+                    .send()
+                    .await
+                    .unwrap()
+                    .status();
+                match (trailing_slash, *path) {
+                    (TrailingSlash::Strict, "/health//") => assert_eq!(404, status.as_u16()),
+                    _ => assert_eq!(200, status.as_u16()),
+                }
+            }
+
+            drop(rx);
+        }
+    }
+
+    #[tokio::test]
+    async fn http_websocket() {
+        trace_init();
+
+        let (rx, addr) = source_with_config(|config| {
+            config.encoding = Encoding::Json;
+            config.websocket_path = Some("stream".to_owned());
+        })
+        .await;
+
+        let (mut socket, _) = tokio_tungstenite::connect_async(format!("ws://{}/stream", addr))
+            .await
+            .unwrap();
+        socket
+            .send(Message::text(r#"{"key":"one"}"#))
+            .await
+            .unwrap();
+        socket
+            .send(Message::binary(&br#"[{"key":"two"},{"key":"three"}]"#[..]))
+            .await
+            .unwrap();
+        socket.send(Message::text("not json")).await.unwrap();
+
+        let reply = socket.next().await.unwrap().unwrap();
+        assert!(reply.to_text().unwrap().contains("400"));
+        socket.close(None).await.unwrap();
+
+        let events = collect_n(rx, 3).await;
+        assert_eq!(events[0].as_log()["key"], "one".into());
+        assert_eq!(events[1].as_log()["key"], "two".into());
+        assert_eq!(events[2].as_log()["key"], "three".into());
+
+        // Plain requests are still accepted
+        assert_eq!(200, send(addr, r#"{"key":"four"}"#).await);
+    }
+
+    #[tokio::test]
+    async fn http_websocket_auth() {
+        trace_init();
+
+        let (rx, addr) = source_with_config(|config| {
+            config.websocket_path = Some("/stream".to_owned());
+            config.auth = Some(HttpSourceAuthConfig::basic("user", "pass"));
+        })
+        .await;
+
+        assert!(
+            tokio_tungstenite::connect_async(format!("ws://{}/stream", addr))
+                .await
+                .is_err()
+        );
+
+        let request = http::Request::builder()
+            .uri(format!("ws://{}/stream", addr))
+            .header(
+                "Authorization",
+                format!("Basic {}", base64::encode("user:pass")),
+            )
+            .body(())
+            .unwrap();
+        let (mut socket, _) = tokio_tungstenite::connect_async(request).await.unwrap();
+        socket.send(Message::text("test body")).await.unwrap();
+
+        let events = collect_n(rx, 1).await;
+        assert_eq!(
+            events[0].as_log()[log_schema().message_key()],
+            "test body".into()
+        );
+    }
+
+    #[tokio::test]
+    async fn http_websocket_route_auth() {
+        trace_init();
+
+        let (rx, addr) = source_with_config(|config| {
+            config.websocket_path = Some("/stream".to_owned());
+            config.routes = vec![RouteConfig {
+                path: "/stream".to_owned(),
+                encoding: None,
+                auth: Some(HttpSourceAuthConfig::basic("user", "pass")),
+            }];
+        })
+        .await;
+
+        assert!(
+            tokio_tungstenite::connect_async(format!("ws://{}/stream", addr))
+                .await
+                .is_err()
+        );
+
+        let request = http::Request::builder()
+            .uri(format!("ws://{}/stream", addr))
+            .header(
+                "Authorization",
+                format!("Basic {}", base64::encode("user:pass")),
+            )
+            .body(())
+            .unwrap();
+        let (mut socket, _) = tokio_tungstenite::connect_async(request).await.unwrap();
+        socket.send(Message::text("test body")).await.unwrap();
+
+        let events = collect_n(rx, 1).await;
+        assert_eq!(
+            events[0].as_log()[log_schema().message_key()],
+            "test body".into()
+        );
+    }
+
+    #[tokio::test]
+    async fn http_server_sent_events() {
+        trace_init();
+
+        let (mut rx, addr) = source_with_config(|config| {
+            config.encoding = Encoding::Json;
+            config.server_sent_events = true;
+            config.sse_event_key = Some("sse_event".to_owned());
+            config.sse_id_key = Some("sse_id".to_owned());
+            config.sse_comment_key = Some("sse_comments".to_owned());
+        })
+        .await;
+
+        let (mut sender, body) = hyper::Body::channel();
+        let request = http::Request::post(format!("http://{}/", addr))
+            .header("Content-Type", "text/event-stream")
+            .body(body)
+            .unwrap();
+        let response = tokio::spawn(hyper::Client::new().request(request));
+
+        // Events are emitted before the request ends
+        sender
+            .send_data(Bytes::from(
+                ": first\nevent: greeting\nid: 1\ndata: {\"key\":\"one\"}\n\ndata: {\"key\"",
+            ))
+            .await
+            .unwrap();
+        let event = rx.recv().await.unwrap();
+        assert_eq!(event.as_log()["key"], "one".into());
+        assert_eq!(event.as_log()["sse_event"], "greeting".into());
+        assert_eq!(event.as_log()["sse_id"], "1".into());
+        assert_eq!(
+            event.as_log()["sse_comments"],
+            Value::Array(vec!["first".into()])
+        );
+
+        sender
+            .send_data(Bytes::from(":\"two\"}\n\ndata: not json\n\n"))
+            .await
+            .unwrap();
+        drop(sender);
+        assert_eq!(200, response.await.unwrap().unwrap().status().as_u16());
+
+        let events = collect_n(rx, 1).await;
+        assert_eq!(events[0].as_log()["key"], "two".into());
+        assert!(events[0].as_log().get("sse_event").is_none());
+    }
+
+    #[tokio::test]
+    async fn http_server_sent_events_shutdown() {
+        trace_init();
+
+        let address = next_addr();
+        let (sender, mut rx) = Pipeline::new_test();
+        let (trigger, shutdown, _) = ShutdownSignal::new_wired();
+        let mut config: SimpleHttpConfig =
+            toml::from_str(&format!(r#"address = "{}""#, address)).unwrap();
+        config.server_sent_events = true;
+        let source = config
+            .build("default", &GlobalOptions::default(), shutdown, sender)
+            .await
+            .unwrap();
+        let source = tokio::spawn(source);
+        wait_for_tcp(address).await;
+
+        let (mut body_sender, body) = hyper::Body::channel();
+        let request = http::Request::post(format!("http://{}/", address))
+            .header("Content-Type", "text/event-stream")
+            .body(body)
+            .unwrap();
+        let response = tokio::spawn(hyper::Client::new().request(request));
+        body_sender
+            .send_data(Bytes::from("data: test body\n\n"))
+            .await
+            .unwrap();
+        let event = rx.recv().await.unwrap();
+        assert_eq!(
+            event.as_log()[log_schema().message_key()],
+            "test body".into()
+        );
+
+        // The idle stream is ended, rather than holding up shutdown
+        drop(trigger);
+        let status = tokio::time::timeout(Duration::from_secs(5), response)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap()
+            .status();
+        assert_eq!(200, status.as_u16());
+        tokio::time::timeout(Duration::from_secs(5), source)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        drop(body_sender);
+    }
+
+    #[tokio::test]
+    async fn http_cors() {
+        trace_init();
+
+        let (rx, addr) = source_with_config(|config| {
+            config.cors = Some(CorsConfig {
+                allowed_origins: vec!["https://example.com".to_owned()],
+                allowed_methods: vec!["POST".to_owned()],
+                allowed_headers: vec!["content-type".to_owned()],
+                max_age_secs: None,
+            });
+        })
+        .await;
+
+        let response = reqwest::Client::new()
+            .request(Method::OPTIONS, &format!("http://{}/", addr))
+            .header("Origin", "https://example.com")
+            .header("Access-Control-Request-Method", "POST")
+            .header("Access-Control-Request-Headers", "content-type")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(200, response.status().as_u16());
+        assert_eq!(
+            "https://example.com",
+            response.headers()["access-control-allow-origin"]
+        );
+
+        let response = reqwest::Client::new()
+            .post(&format!("http://{}/", addr))
+            .header("Origin", "https://example.com")
+            .body("test body")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(200, response.status().as_u16());
+        assert_eq!(
+            "https://example.com",
+            response.headers()["access-control-allow-origin"]
+        );
+
+        let mut events = collect_n(rx, 1).await;
+        assert_eq!(
+            events.remove(0).as_log()[log_schema().message_key()],
+            "test body".into()
+        );
+    }
+
+    #[tokio::test]
+    async fn http_idempotency() {
+        trace_init();
+
+        let (rx, addr) = source_with_config(|config| {
+            config.idempotency = Some(IdempotencyConfig {
+                header: "Idempotency-Key".to_owned(),
+                max_keys: 10,
+                ttl_secs: 60,
+            });
+        })
+        .await;
+
+        let headers = |key: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert("Idempotency-Key", key.parse().unwrap());
+            headers
+        };
+        assert_eq!(200, send_with_headers(addr, "one", headers("a")).await);
+        assert_eq!(200, send_with_headers(addr, "repeated", headers("a")).await);
+        assert_eq!(200, send_with_headers(addr, "two", headers("b")).await);
+        assert_eq!(200, send(addr, "three").await);
+
+        let events = collect_n(rx, 3).await;
+        let messages = events
+            .iter()
+            .map(|event| event.as_log()[log_schema().message_key()].to_string_lossy())
+            .collect::<Vec<_>>();
+        assert_eq!(messages, vec!["one", "two", "three"]);
+    }
+
+    #[tokio::test]
+    async fn http_headers() {
+        trace_init();
+
+        let mut headers = HeaderMap::new();
+        headers.insert("User-Agent", "test_client".parse().unwrap());
+        headers.insert("Upgrade-Insecure-Requests", "false".parse().unwrap());
+
+        let (rx, addr) = source(
+            Encoding::Ndjson,
+            vec![
+                "User-Agent".to_string(),
+                "Upgrade-Insecure-Requests".to_string(),
+                "AbsentHeader".to_string(),
+            ],
+            vec![],
+        )
+        .await;
+
+        assert_eq!(
+            200,
+            send_with_headers(addr, "{\"key1\":\"value1\"}", headers).await
+        );
+
+        let mut events = collect_n(rx, 1).await;
+        {
+            let event = events.remove(0);
+            let log = event.as_log();
+            assert_eq!(log["key1"], "value1".into());
+            assert_eq!(log["User-Agent"], "test_client".into());
+            assert_eq!(log["Upgrade-Insecure-Requests"], "false".into());
+            assert_eq!(log["AbsentHeader"], Value::Null);
+            assert!(log.get(log_schema().timestamp_key()).is_some());
+            assert_eq!(log[log_schema().source_type_key()], "http".into());
+        }
+    }
+
+    #[tokio::test]
+    async fn http_skip_missing_headers() {
+        trace_init();
+
+        let (rx, addr) = source_with_config(|config| {
+            config.headers = vec!["User-Agent".to_owned(), "AbsentHeader".to_owned()];
+            config.skip_missing_headers = true;
+            config.query_parameters = vec!["source".to_owned(), "absent".to_owned()];
+            config.skip_missing_query_parameters = true;
+        })
+        .await;
+
+        let mut headers = HeaderMap::new();
+        headers.insert("User-Agent", "test_client".parse().unwrap());
+        assert_eq!(
+            200,
+            send_with_headers(addr, "test body", headers.clone()).await
+        );
+        assert_eq!(
+            200,
+            send_with_query(addr, "test body", "source=staging").await
+        );
+
+        let events = collect_n(rx, 2).await;
+        let log = events[0].as_log();
+        assert_eq!(log["User-Agent"], "test_client".into());
+        assert!(log.get("AbsentHeader").is_none());
+        assert!(log.get("absent").is_none());
+        let log = events[1].as_log();
+        assert_eq!(log["source"], "staging".into());
+        assert!(log.get("absent").is_none());
+    }
+
+    #[tokio::test]
+    async fn http_json_headers() {
+        trace_init();
+
+        let (rx, addr) = source_with_config(|config| {
+            config.json_headers = vec!["X-Context".to_owned(), "X-Invalid".to_owned()];
+        })
+        .await;
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "X-Context",
+            r#"{"tenant":"acme","retries":2}"#.parse().unwrap(),
+        );
+        headers.insert("X-Invalid", "{not json".parse().unwrap());
+        assert_eq!(200, send_with_headers(addr, "test body", headers).await);
+
+        let mut events = collect_n(rx, 1).await;
+        let event = events.remove(0);
+        let log = event.as_log();
+        assert_eq!(log["X-Context.tenant"], "acme".into());
+        assert_eq!(log["X-Context.retries"], Value::Integer(2));
+        assert_eq!(log["X-Invalid"], "{not json".into());
+    }
+
+    #[tokio::test]
+    async fn http_header_patterns() {
+        trace_init();
+
+        let (rx, addr) = source_with_config(|config| {
+            config.headers = vec!["X-Custom-Listed".to_owned()];
+            config.header_patterns = vec!["^X-Custom-".to_owned()];
+        })
+        .await;
+
+        let mut headers = HeaderMap::new();
+        headers.insert("X-Custom-Tenant", "acme".parse().unwrap());
+        headers.append("X-Custom-Tag", "a".parse().unwrap());
+        headers.append("X-Custom-Tag", "b".parse().unwrap());
+        headers.insert("X-Custom-Listed", "listed".parse().unwrap());
+        headers.insert("X-Other", "other".parse().unwrap());
+        assert_eq!(200, send_with_headers(addr, "test body", headers).await);
+
+        let mut events = collect_n(rx, 1).await;
+        let event = events.remove(0);
+        let log = event.as_log();
+        assert_eq!(log["x-custom-tenant"], "acme".into());
+        assert_eq!(
+            log["x-custom-tag"],
+            Value::Array(vec!["a".into(), "b".into()])
+        );
+        assert_eq!(log["X-Custom-Listed"], "listed".into());
+        assert!(log.get("x-custom-listed").is_none());
+        assert!(log.get("x-other").is_none());
+    }
+
+    #[tokio::test]
+    async fn http_lowercase_header_keys() {
+        trace_init();
+
+        let (rx, addr) = source_with_config(|config| {
+            config.headers = vec!["User-Agent".to_owned()];
+            config.json_headers = vec!["X-Context".to_owned()];
+            config.lowercase_header_keys = true;
+        })
+        .await;
+
+        let mut headers = HeaderMap::new();
+        headers.insert("USER-AGENT", "test_client".parse().unwrap());
+        headers.insert("x-context", r#"{"tenant":"acme"}"#.parse().unwrap());
+        assert_eq!(200, send_with_headers(addr, "test body", headers).await);
+
+        let mut events = collect_n(rx, 1).await;
+        let event = events.remove(0);
+        let log = event.as_log();
+        assert_eq!(log["user-agent"], "test_client".into());
+        assert_eq!(log["x-context.tenant"], "acme".into());
+        assert!(log.get("User-Agent").is_none());
+        assert!(log.get("X-Context").is_none());
+    }
+
+    #[tokio::test]
+    async fn http_invalid_header_patterns() {
+        let mut config: SimpleHttpConfig = toml::from_str(r#"address = "127.0.0.1:0""#).unwrap();
+        config.header_patterns = vec!["X-(".to_owned()];
+
+        assert!(config
+            .build(
+                "default",
+                &GlobalOptions::default(),
+                ShutdownSignal::noop(),
+                Pipeline::new_test().0,
+            )
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn http_cookies_key() {
+        trace_init();
+
+        let (rx, addr) = source_with_config(|config| {
+            config.cookies_key = Some("cookies".to_owned());
+        })
+        .await;
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "Cookie",
+            r#"session=abc123; theme="dark"; malformed; =empty; lang=en=GB"#
+                .parse()
+                .unwrap(),
+        );
+        assert_eq!(200, send_with_headers(addr, "test body", headers).await);
+        assert_eq!(200, send(addr, "test body").await);
+
+        let events = collect_n(rx, 2).await;
+        let log = events[0].as_log();
+        assert_eq!(log["cookies.session"], "abc123".into());
+        assert_eq!(log["cookies.theme"], "dark".into());
+        assert_eq!(log["cookies.lang"], "en=GB".into());
+        assert!(matches!(&log["cookies"], Value::Map(cookies) if cookies.len() == 3));
+        assert!(events[1].as_log().get("cookies").is_none());
+    }
+
+    #[tokio::test]
+    async fn http_multi_valued_headers() {
+        trace_init();
+
+        let (rx, addr) = source(
+            Encoding::Text,
+            vec!["X-Forwarded-For".to_string(), "User-Agent".to_string()],
+            vec![],
+        )
+        .await;
+
+        let mut headers = HeaderMap::new();
+        headers.insert("User-Agent", "test_client".parse().unwrap());
+        headers.append("X-Forwarded-For", "10.0.0.1".parse().unwrap());
+        headers.append("X-Forwarded-For", "10.0.0.2".parse().unwrap());
+        assert_eq!(200, send_with_headers(addr, "test body", headers).await);
+
+        let mut events = collect_n(rx, 1).await;
+        let event = events.remove(0);
+        let log = event.as_log();
+        assert_eq!(log["User-Agent"], "test_client".into());
+        assert_eq!(
+            log["X-Forwarded-For"],
+            Value::Array(vec!["10.0.0.1".into(), "10.0.0.2".into()])
+        );
+    }
+
+    #[tokio::test]
+    async fn http_remote_ip_key() {
+        trace_init();
+
+        let (rx, addr) = source_with_config(|config| {
+            config.remote_ip_key = Some("remote_ip".to_owned());
+            config.trusted_proxies = vec!["127.0.0.1/32".to_owned()];
+        })
+        .await;
+
+        let mut headers = HeaderMap::new();
+        headers.insert("X-Forwarded-For", "10.1.2.3, 127.0.0.1".parse().unwrap());
+        assert_eq!(200, send_with_headers(addr, "test body", headers).await);
+        assert_eq!(200, send(addr, "test body").await);
+
+        let events = collect_n(rx, 2).await;
+        assert_eq!(events[0].as_log()["remote_ip"], "10.1.2.3".into());
+        assert_eq!(events[1].as_log()["remote_ip"], "127.0.0.1".into());
+    }
+
+    #[tokio::test]
+    async fn http_sni_key() {
+        trace_init();
+
+        let (rx, addr) = source_with_config(|config| {
+            config.sni_key = Some("sni".to_owned());
+            config.tls = Some(TlsConfig {
+                enabled: Some(true),
+                options: TlsOptions {
+                    crt_file: Some(TEST_PEM_CRT_PATH.into()),
+                    key_file: Some(TEST_PEM_KEY_PATH.into()),
+                    ..Default::default()
+                },
+            });
+        })
+        .await;
+
+        let status = reqwest::Client::builder()
+            .danger_accept_invalid_certs(true)
+            .build()
+            .unwrap()
+            .post(&format!("https://localhost:{}/", addr.port()))
+            .body("test body")
+            .send()
+            .await
+            .unwrap()
+            .status()
+            .as_u16();
+        assert_eq!(200, status);
+
+        let events = collect_n(rx, 1).await;
+        assert_eq!(events[0].as_log()["sni"], "localhost".into());
+    }
+
+    #[tokio::test]
+    async fn http_verify_client_requires_tls() {
+        let config: SimpleHttpConfig = toml::from_str(
+            r#"
+            address = "127.0.0.1:9000"
+            verify_client = true
+            client_cn_key = "client_cn"
+            "#,
+        )
+        .unwrap();
+
+        let result = config
+            .build(
+                "default",
+                &GlobalOptions::default(),
+                ShutdownSignal::noop(),
+                Pipeline::new_test().0,
+            )
+            .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn http_wrong_path() {
+        trace_init();
+
+        let (_rx, addr) = source_with_config(|_| {}).await;
+
+        let send_to = |method: Method, path: &'static str| async move {
+            reqwest::Client::new()
+                .request(method, &format!("http://{}{}", addr, path))
+                .body("test body")
+                .send()
+                .await
+                .unwrap()
+                .status()
+                .as_u16()
+        };
+        assert_eq!(404, send_to(Method::POST, "/wrong").await);
+        assert_eq!(404, send_to(Method::PUT, "/wrong").await);
+        assert_eq!(405, send_to(Method::PUT, "/").await);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn http_unix_socket() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        trace_init();
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("http.sock");
+        let (sender, rx) = Pipeline::new_test();
+        let (trigger, shutdown, _) = ShutdownSignal::new_wired();
+        let config: SimpleHttpConfig =
+            toml::from_str(&format!(r#"address = "{}""#, path.display())).unwrap();
+        let source = config
+            .build("default", &GlobalOptions::default(), shutdown, sender)
+            .await
+            .unwrap();
+        tokio::spawn(source);
+        let socket = path.clone();
+        wait_for(move || {
+            let exists = socket.exists();
+            async move { exists }
+        })
+        .await;
+
+        let mut stream = tokio::net::UnixStream::connect(&path).await.unwrap();
+        stream
+            .write_all(
+                b"POST / HTTP/1.1\r\nHost: localhost\r\nContent-Length: 9\r\nConnection: close\r\n\r\ntest body",
+            )
+            .await
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+
+        let mut events = collect_n(rx, 1).await;
+        let event = events.remove(0);
+        assert_eq!(
+            event.as_log()[log_schema().message_key()],
+            "test body".into()
+        );
+
+        drop(trigger);
+        let socket = path.clone();
+        wait_for(move || {
+            let removed = !socket.exists();
+            async move { removed }
+        })
+        .await;
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn unix_socket_address() {
+        assert!(toml::from_str::<SimpleHttpConfig>(r#"address = "http.sock""#).is_err());
+        let config = toml::from_str::<SimpleHttpConfig>(r#"address = "/run/http.sock""#).unwrap();
+        assert_eq!(
+            config.resources(),
+            vec![crate::config::Resource::UnixSocket("/run/http.sock".into())]
+        );
+    }
+
+    #[tokio::test]
+    async fn http_path_template() {
+        trace_init();
+
+        let (rx, addr) = source_with_config(|config| {
+            config.path_template = Some("/ingest/:env/:service".to_owned());
+        })
+        .await;
+
+        let send_to = |path: &'static str| async move {
+            reqwest::Client::new()
+                .post(&format!("http://{}{}", addr, path))
+                .body("test body")
+                .send()
+                .await
+                .unwrap()
+                .status()
+                .as_u16()
+        };
+        assert_eq!(404, send_to("/").await);
+        assert_eq!(404, send_to("/ingest/prod").await);
+        assert_eq!(200, send_to("/ingest/prod/api").await);
+
+        let mut events = collect_n(rx, 1).await;
+        let event = events.remove(0);
+        let log = event.as_log();
+        assert_eq!(log["env"], "prod".into());
+        assert_eq!(log["service"], "api".into());
+    }
+
+    #[tokio::test]
+    async fn http_route_key() {
+        trace_init();
+
+        let (rx, addr) = source_with_config(|config| {
+            config.path_template = Some("/ingest/:env".to_owned());
+            config.route_key = Some("route".to_owned());
+        })
+        .await;
+
+        assert_eq!(
+            200,
+            reqwest::Client::new()
+                .post(&format!("http://{}/ingest/prod", addr))
+                .body("test body")
+                .send()
+                .await
+                .unwrap()
+                .status()
+                .as_u16()
+        );
+
+        let mut events = collect_n(rx, 1).await;
+        assert_eq!(events.remove(0).as_log()["route"], "/ingest/:env".into());
+
+        let (rx, addr) = source_with_config(|config| {
+            config.route_key = Some("route".to_owned());
+        })
+        .await;
+
+        assert_eq!(200, send(addr, "test body").await);
+
+        let mut events = collect_n(rx, 1).await;
+        assert_eq!(events.remove(0).as_log()["route"], "/".into());
+    }
+
+    #[tokio::test]
+    async fn http_path_segments_key() {
+        trace_init();
+
+        let (rx, addr) = source_with_config(|config| {
+            config.path_template = Some("/event/:name".to_owned());
+            config.path_segments_key = Some("segments".to_owned());
+        })
+        .await;
+
+        assert_eq!(200, send_with_path(addr, "test body", "/event/path1").await);
+
+        let mut events = collect_n(rx, 1).await;
+        assert_eq!(
+            events.remove(0).as_log()["segments"],
+            Value::Array(vec!["event".into(), "path1".into()])
+        );
+    }
+
+    #[tokio::test]
+    async fn http_routes() {
+        trace_init();
+
+        let (rx, addr) = source_with_config(|config| {
+            config.routes = vec![
+                RouteConfig {
+                    path: "/logs/:app".to_owned(),
+                    encoding: Some(Encoding::Ndjson),
+                    auth: None,
+                },
+                RouteConfig {
+                    path: "/metrics".to_owned(),
+                    encoding: Some(Encoding::Metric),
+                    auth: None,
+                },
+            ];
+            config.route_key = Some("route".to_owned());
+        })
+        .await;
+
+        assert_eq!(404, send_with_path(addr, "test body", "/").await);
+        assert_eq!(404, send_with_path(addr, "test body", "/other").await);
+        assert_eq!(
+            200,
+            send_with_path(addr, r#"{"key":"value"}"#, "/logs/api").await
+        );
+        assert_eq!(
+            200,
+            send_with_path(
+                addr,
+                r#"{"name":"queue_depth","kind":"gauge","value":7.5}"#,
+                "/metrics"
+            )
+            .await
+        );
+
+        let events = collect_n(rx, 2).await;
+        let log = events[0].as_log();
+        assert_eq!(log["key"], "value".into());
+        assert_eq!(log["app"], "api".into());
+        assert_eq!(log["route"], "/logs/:app".into());
+        let metric = events[1].as_metric();
+        assert_eq!(metric.name(), "queue_depth");
+        assert_eq!(metric.data.value, MetricValue::Gauge { value: 7.5 });
+    }
+
+    #[tokio::test]
+    async fn http_route_auth() {
+        trace_init();
+
+        let (rx, addr) = source_with_config(|config| {
+            config.routes = vec![
+                RouteConfig {
+                    path: "/public".to_owned(),
+                    encoding: None,
+                    auth: None,
+                },
+                RouteConfig {
+                    path: "/ingest".to_owned(),
+                    encoding: None,
+                    auth: Some(HttpSourceAuthConfig::basic("user", "pass")),
+                },
+            ];
+        })
+        .await;
+
+        assert_eq!(401, send_with_path(addr, "test body", "/ingest").await);
+        let status = reqwest::Client::new()
+            .post(&format!("http://{}/ingest", addr))
+            .basic_auth("user", Some("pass"))
+            .body("private")
+            .send()
+            .await
+            .unwrap()
+            .status()
+            .as_u16();
+        assert_eq!(200, status);
+        assert_eq!(200, send_with_path(addr, "public", "/public").await);
+
+        let events = collect_n(rx, 2).await;
+        assert_eq!(
+            events[0].as_log()[log_schema().message_key()],
+            "private".into()
+        );
+        assert_eq!(
+            events[1].as_log()[log_schema().message_key()],
+            "public".into()
+        );
+    }
+
+    #[tokio::test]
+    async fn http_route_without_auth_is_open() {
+        trace_init();
+
+        let (rx, addr) = source_with_config(|config| {
+            config.auth = Some(HttpSourceAuthConfig::basic("user", "pass"));
+            config.routes = vec![
+                RouteConfig {
+                    path: "/health".to_owned(),
+                    encoding: None,
+                    auth: None,
+                },
+                RouteConfig {
+                    path: "/ingest".to_owned(),
+                    encoding: None,
+                    auth: Some(HttpSourceAuthConfig::basic("ingest", "token")),
+                },
+            ];
+        })
+        .await;
+
+        let send_as = |path: &'static str, username: &'static str, password: &'static str| async move {
+            reqwest::Client::new()
+                .post(&format!("http://{}{}", addr, path))
+                .basic_auth(username, Some(password))
+                .body(path)
+                .send()
+                .await
+                .unwrap()
+                .status()
+                .as_u16()
+        };
+        assert_eq!(200, send_with_path(addr, "/health", "/health").await);
+        assert_eq!(401, send_with_path(addr, "/ingest", "/ingest").await);
+        assert_eq!(401, send_as("/ingest", "user", "pass").await);
+        assert_eq!(200, send_as("/ingest", "ingest", "token").await);
+
+        let events = collect_n(rx, 2).await;
+        assert_eq!(
+            events[0].as_log()[log_schema().message_key()],
+            "/health".into()
+        );
+        assert_eq!(
+            events[1].as_log()[log_schema().message_key()],
+            "/ingest".into()
+        );
+    }
+
+    #[tokio::test]
+    async fn http_raw_query_key() {
+        trace_init();
+
+        let (rx, addr) = source_with_config(|config| {
+            config.query_parameters = vec!["source".to_owned()];
+            config.raw_query_key = Some("query".to_owned());
+        })
+        .await;
+
+        assert_eq!(
+            200,
+            send_with_query(addr, "test body", "source=staging&region=gb%20north").await
+        );
+
+        let mut events = collect_n(rx, 1).await;
+        let event = events.remove(0);
+        let log = event.as_log();
+        assert_eq!(log["source"], "staging".into());
+        assert_eq!(log["query"], "source=staging&region=gb%20north".into());
+    }
+
+    #[tokio::test]
+    async fn http_timestamp_field() {
+        trace_init();
+
+        let (rx, addr) = source_with_config(|config| {
+            config.encoding = Encoding::Json;
+            config.timestamp_field = Some("time".to_owned());
+        })
+        .await;
+
+        assert_eq!(
+            200,
+            send(
+                addr,
+                r#"[{"time":"2021-02-03T04:05:06Z"},{"time":"yesterday"},{"key":"value"}]"#
+            )
+            .await
+        );
+
+        let events = collect_n(rx, 3).await;
+        assert_eq!(
+            events[0].as_log()[log_schema().timestamp_key()],
+            Utc.ymd(2021, 2, 3).and_hms(4, 5, 6).into()
+        );
+        for event in &events[1..] {
+            let timestamp = event.as_log()[log_schema().timestamp_key()].clone();
+            match timestamp {
+                Value::Timestamp(timestamp) => {
+                    assert!(timestamp > Utc.ymd(2021, 2, 4).and_hms(0, 0, 0))
+                }
+                value => panic!("Unexpected timestamp {:?}", value),
+            }
+        }
+    }
+
+    #[test]
+    fn invalid_utf8() {
+        let valid = Bytes::from("caf\u{e9}");
+        let invalid = Bytes::from_static(b"caf\xe9");
+
+        for mode in &[
+            InvalidUtf8::Error,
+            InvalidUtf8::Replace,
+            InvalidUtf8::Passthrough,
+        ] {
+            assert_eq!(mode.apply(valid.clone()).unwrap(), valid);
+        }
+        assert_eq!(
+            InvalidUtf8::Error
+                .apply(invalid.clone())
+                .unwrap_err()
+                .code(),
+            400
+        );
+        assert_eq!(
+            InvalidUtf8::Replace.apply(invalid.clone()).unwrap(),
+            Bytes::from("caf\u{fffd}")
+        );
+        assert_eq!(
+            InvalidUtf8::Passthrough.apply(invalid.clone()).unwrap(),
+            invalid
+        );
+    }
+
+    #[test]
+    fn json_parsing() {
+        let json = br#"{"int":1,"float":1.5,"string":"caf\u00e9","array":[true,null],"map":{}}"#;
+        assert_eq!(
+            parse_json(json).unwrap(),
+            serde_json::from_slice::<serde_json::Value>(json).unwrap()
+        );
+        assert!(parse_json(b"{\"unterminated\":").is_err());
+    }
+
+    #[test]
+    fn lines() {
+        let lines = body_to_lines(Bytes::from("one\n\ntwo\nthree"), b'\n').collect::<Vec<_>>();
+        assert_eq!(lines, vec!["one", "two", "three"]);
+        assert_eq!(body_to_lines(Bytes::from("\n\n"), b'\n').count(), 0);
+    }
+
+    #[test]
+    fn lines_crlf() {
+        let lines =
+            body_to_lines(Bytes::from("one\r\n\r\ntwo\nthree\r"), b'\n').collect::<Vec<_>>();
+        assert_eq!(lines, vec!["one", "two", "three"]);
+        let lines = body_to_lines(Bytes::from("one\r;two"), b';').collect::<Vec<_>>();
+        assert_eq!(lines, vec!["one\r", "two"]);
+    }
+
+    #[tokio::test]
+    async fn http_ndjson_crlf() {
+        trace_init();
+
+        let (rx, addr) = source(Encoding::Ndjson, vec![], vec![]).await;
+
+        assert_eq!(
+            200,
+            send(addr, "{\"key\":\"one\"}\r\n{\"key\":\"two\"}\n").await
+        );
+
+        let events = collect_n(rx, 2).await;
+        assert_eq!(events[0].as_log()["key"], "one".into());
+        assert_eq!(events[1].as_log()["key"], "two".into());
+    }
+
+    #[tokio::test]
+    async fn http_charset() {
+        trace_init();
+
+        let (rx, addr) = source_with_config(|config| {
+            config.charset = Some("windows-1252".to_owned());
+        })
+        .await;
+
+        let response = reqwest::Client::new()
+            .post(&format!("http://{}/", addr))
+            .body(b"caf\xe9 \x80".to_vec())
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(200, response.status().as_u16());
+
+        let events = collect_n(rx, 1).await;
+        assert_eq!(
+            events[0].as_log()[log_schema().message_key()],
+            "caf\u{e9} \u{20ac}".into()
+        );
+    }
+
+    #[tokio::test]
+    async fn http_json_bom() {
+        trace_init();
+
+        let (rx, addr) = source(Encoding::Json, vec![], vec![]).await;
+
+        assert_eq!(200, send(addr, "\u{feff}{\"key\":\"value\"}").await);
+
+        let events = collect_n(rx, 1).await;
+        assert_eq!(events[0].as_log()["key"], "value".into());
+    }
+
+    #[tokio::test]
+    async fn http_when_full_reject() {
+        trace_init();
+
+        let (sender, mut rx) = Pipeline::new_with_buffer(1, vec![]);
+        let address = next_addr();
+        let mut config: SimpleHttpConfig =
+            toml::from_str(&format!(r#"address = "{}""#, address)).unwrap();
+        config.when_full = WhenFull::Reject;
+        let source = config
+            .build(
+                "default",
+                &GlobalOptions::default(),
+                ShutdownSignal::noop(),
+                sender,
+            )
+            .await
+            .unwrap();
+        tokio::spawn(source);
+        wait_for_tcp(address).await;
+
+        assert_eq!(200, send(address, "first").await);
+        let response = reqwest::Client::new()
+            .post(&format!("http://{}/", address))
+            .body("second")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(503, response.status().as_u16());
+        assert_eq!("1", response.headers()["retry-after"]);
+
+        let event = rx.recv().await.unwrap();
+        assert_eq!(event.as_log()[log_schema().message_key()], "first".into());
+        assert_eq!(200, send(address, "third").await);
+    }
+
+    #[tokio::test]
+    async fn http_when_full_reject_mid_request() {
+        trace_init();
+
+        let (sender, rx) = Pipeline::new_with_buffer(2, vec![]);
+        let address = next_addr();
+        let mut config: SimpleHttpConfig =
+            toml::from_str(&format!(r#"address = "{}""#, address)).unwrap();
+        config.encoding = Encoding::Json;
+        config.when_full = WhenFull::Reject;
+        let source = config
+            .build(
+                "default",
+                &GlobalOptions::default(),
+                ShutdownSignal::noop(),
+                sender,
+            )
+            .await
+            .unwrap();
+        tokio::spawn(source);
+        wait_for_tcp(address).await;
+
+        let response = reqwest::Client::new()
+            .post(&format!("http://{}/", address))
+            .body(r#"[{"key":1},{"key":2},{"key":3}]"#)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(503, response.status().as_u16());
+        assert_eq!("1", response.headers()["retry-after"]);
+
+        let events = collect_n(rx, 2).await;
+        assert_eq!(events[0].as_log()["key"], 1.into());
+        assert_eq!(events[1].as_log()["key"], 2.into());
+    }
+
+    #[tokio::test]
+    async fn http_max_events_per_request() {
+        trace_init();
+
+        let (rx, addr) = source_with_config(|config| {
+            config.encoding = Encoding::Json;
+            config.max_events_per_request = Some(2);
+        })
+        .await;
+
+        assert_eq!(413, send(addr, r#"[{"key":1},{"key":2},{"key":3}]"#).await);
+        assert_eq!(200, send(addr, r#"[{"key":4},{"key":5}]"#).await);
+
+        let events = collect_n(rx, 2).await;
+        assert_eq!(events[0].as_log()["key"], 4.into());
+        assert_eq!(events[1].as_log()["key"], 5.into());
+    }
+
+    #[tokio::test]
+    async fn http_json_skip_invalid() {
+        trace_init();
+
+        let (rx, addr) = source_with_config(|config| {
+            config.encoding = Encoding::Json;
+            config.on_array_error = ArrayErrorMode::SkipInvalid;
+        })
+        .await;
+
+        assert_eq!(
+            200,
+            send(
+                addr,
+                r#"[{"key":"value1"},"not an object",{"key":"value2"}]"#
+            )
+            .await
+        );
+
+        let events = collect_n(rx, 2).await;
+        assert_eq!(events[0].as_log()["key"], "value1".into());
+        assert_eq!(events[1].as_log()["key"], "value2".into());
+    }
+
+    #[tokio::test]
+    async fn http_json_schema() {
+        trace_init();
+
+        let mut schema = tempfile::NamedTempFile::new().unwrap();
+        write!(
+            schema,
+            r#"{{"type":"object","properties":{{"level":{{"type":"string"}}}},"required":["level"]}}"#
+        )
+        .unwrap();
+
+        let (rx, addr) = source_with_config(|config| {
+            config.encoding = Encoding::Json;
+            config.json_schema_path = Some(schema.path().into());
+        })
+        .await;
+        assert_eq!(400, send(addr, r#"[{"level":"info"},{"level":1}]"#).await);
+        assert_eq!(200, send(addr, r#"{"level":"info"}"#).await);
+        let events = collect_n(rx, 1).await;
+        assert_eq!(events[0].as_log()["level"], "info".into());
+
+        let (rx, addr) = source_with_config(|config| {
+            config.encoding = Encoding::Ndjson;
+            config.json_schema_path = Some(schema.path().into());
+            config.schema_mode = SchemaMode::DropInvalid;
+        })
+        .await;
+        assert_eq!(
+            200,
+            send(addr, "{\"level\":1}\n{\"level\":\"error\"}").await
+        );
+        let events = collect_n(rx, 1).await;
+        assert_eq!(events[0].as_log()["level"], "error".into());
+    }
+
+    #[tokio::test]
+    async fn http_openapi() {
+        trace_init();
+
+        let mut document = tempfile::NamedTempFile::new().unwrap();
+        write!(
+            document,
+            r#"{{"openapi":"3.0.0","paths":{{"/events":{{"post":{{"requestBody":{{"content":{{"application/json":{{"schema":{{"type":"object","required":["level"]}}}}}}}}}}}}}}}}"#
+        )
+        .unwrap();
+
+        let (rx, addr) = source_with_config(|config| {
+            config.encoding = Encoding::Json;
+            config.openapi_path = Some(document.path().into());
+        })
+        .await;
+        assert_eq!(
+            400,
+            send_with_path(addr, r#"{"message":"no level"}"#, "/events").await
+        );
+        assert_eq!(
+            404,
+            send_with_path(addr, r#"{"level":"info"}"#, "/other").await
+        );
+        assert_eq!(
+            200,
+            send_with_path(addr, r#"{"level":"info"}"#, "/events").await
+        );
+        let events = collect_n(rx, 1).await;
+        assert_eq!(events[0].as_log()["level"], "info".into());
+    }
+
+    #[tokio::test]
+    async fn http_body_key() {
+        trace_init();
+
+        let (rx, addr) = source_with_config(|config| {
+            config.encoding = Encoding::Json;
+            config.headers = vec!["User-Agent".to_owned()];
+            config.body_key = Some("payload".to_owned());
+        })
+        .await;
+
+        assert_eq!(
+            200,
+            send(addr, r#"{"User-Agent":"body","nested":{"key":"value"}}"#).await
+        );
+
+        let events = collect_n(rx, 1).await;
+        let log = events[0].as_log();
+        assert_eq!(log["payload.User-Agent"], "body".into());
+        assert_eq!(log["payload.nested.key"], "value".into());
+        assert!(log.get("nested").is_none());
+    }
+
+    #[tokio::test]
+    async fn http_json_pointer() {
+        trace_init();
+
+        let (rx, addr) = source_with_config(|config| {
+            config.encoding = Encoding::Json;
+            config.json_pointer = Some("/data/events".to_owned());
+        })
+        .await;
+
+        assert_eq!(400, send(addr, r#"[{"key":"value"}]"#).await);
+        assert_eq!(400, send(addr, r#"{"data":{"events":"none"}}"#).await);
+        assert_eq!(
+            200,
+            send(addr, r#"{"data":{"events":[{"key":"one"},{"key":"two"}]}}"#).await
+        );
+
+        let events = collect_n(rx, 2).await;
+        assert_eq!(events[0].as_log()["key"], "one".into());
+        assert_eq!(events[1].as_log()["key"], "two".into());
+        assert!(events[0].as_log().get("data").is_none());
+    }
+
+    #[tokio::test]
+    async fn http_flatten() {
+        trace_init();
+
+        let body = r#"{"a":{"b":{"c":1}},"tags":["x","y"],"empty":{}}"#;
+
+        let (rx, addr) = source_with_config(|config| {
+            config.encoding = Encoding::Ndjson;
+            config.flatten = true;
+        })
+        .await;
+        assert_eq!(200, send(addr, body).await);
+
+        let events = collect_n(rx, 1).await;
+        let log = events[0].as_log();
+        assert_eq!(log.get_flat("a.b.c"), Some(&Value::Integer(1)));
+        assert!(log.get_flat("a").is_none());
+        assert!(matches!(log.get_flat("tags"), Some(Value::Array(tags)) if tags.len() == 2));
+        assert!(matches!(log.get_flat("empty"), Some(Value::Map(empty)) if empty.is_empty()));
+
+        let (rx, addr) = source_with_config(|config| {
+            config.encoding = Encoding::Json;
+            config.flatten = true;
+            config.flatten_separator = "_".to_owned();
+            config.flatten_arrays = true;
+        })
+        .await;
+        assert_eq!(200, send(addr, &format!("[{}]", body)).await);
+
+        let events = collect_n(rx, 1).await;
+        let log = events[0].as_log();
+        assert_eq!(log["a_b_c"], Value::Integer(1));
+        assert_eq!(log["tags_0"], "x".into());
+        assert_eq!(log["tags_1"], "y".into());
+    }
+
+    #[tokio::test]
+    async fn http_allowed_content_types() {
+        trace_init();
+
+        let (rx, addr) = source_with_config(|config| {
+            config.encoding = Encoding::Json;
+            config.allowed_content_types = vec!["application/json".to_owned()];
+        })
+        .await;
+
+        let content_type = |content_type: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert("Content-Type", content_type.parse().unwrap());
+            headers
+        };
+        assert_eq!(415, send(addr, r#"{"key":"value"}"#).await);
+        assert_eq!(
+            415,
+            send_with_headers(addr, r#"{"key":"value"}"#, content_type("text/plain")).await
+        );
+        assert_eq!(
+            200,
+            send_with_headers(
+                addr,
+                r#"{"key":"value"}"#,
+                content_type("application/json; charset=utf-8")
+            )
+            .await
+        );
+
+        let events = collect_n(rx, 1).await;
+        assert_eq!(events[0].as_log()["key"], "value".into());
+    }
+
+    #[tokio::test]
+    async fn http_on_empty_body() {
+        trace_init();
+
+        let (rx, addr) = source_with_config(|config| {
+            config.encoding = Encoding::Json;
+            config.on_empty_body = EmptyBodyMode::Accept;
+        })
+        .await;
+        assert_eq!(200, send(addr, "").await);
+
+        let events = collect_n(rx, 1).await;
+        let log = events[0].as_log();
+        assert!(log.get(log_schema().timestamp_key()).is_some());
+        assert!(log.get(log_schema().message_key()).is_none());
+
+        let (_rx, addr) = source_with_config(|config| {
+            config.encoding = Encoding::Ndjson;
+            config.on_empty_body = EmptyBodyMode::Reject;
+        })
+        .await;
+        assert_eq!(400, send(addr, "").await);
+        assert_eq!(200, send(addr, r#"{"key":"value"}"#).await);
+    }
+
+    #[tokio::test]
+    async fn http_source_type() {
+        trace_init();
+
+        let (rx, addr) = source_with_config(|config| {
+            config.source_type = Some("github_webhook".to_owned());
+        })
+        .await;
+        assert_eq!(200, send(addr, "test body").await);
+        let events = collect_n(rx, 1).await;
+        assert_eq!(
+            events[0].as_log()[log_schema().source_type_key()],
+            "github_webhook".into()
+        );
+
+        let (rx, addr) = source_with_config(|config| config.add_source_type = false).await;
+        assert_eq!(200, send(addr, "test body").await);
+        let events = collect_n(rx, 1).await;
+        assert!(events[0]
+            .as_log()
+            .get(log_schema().source_type_key())
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn http_without_timestamp() {
+        trace_init();
+
+        let (rx, addr) = source_with_config(|config| {
+            config.encoding = Encoding::Ndjson;
+            config.add_timestamp = false;
+        })
+        .await;
+
+        assert_eq!(
+            200,
+            send(addr, "{\"key\":\"value\"}\n{\"timestamp\":\"yesterday\"}").await
+        );
+
+        let events = collect_n(rx, 2).await;
+        assert!(events[0]
+            .as_log()
+            .get(log_schema().timestamp_key())
+            .is_none());
+        assert_eq!(
+            events[1].as_log()[log_schema().timestamp_key()],
+            "yesterday".into()
+        );
+
+        // Timestamps decoded from the body are kept
+        let (rx, addr) = source_with_config(|config| {
+            config.encoding = Encoding::Gelf;
+            config.add_timestamp = false;
+        })
+        .await;
+
+        assert_eq!(
+            200,
+            send(
+                addr,
+                r#"{"version":"1.1","host":"a","short_message":"one","timestamp":1612325106}"#
+            )
+            .await
+        );
+
+        let events = collect_n(rx, 1).await;
+        assert_eq!(
+            events[0].as_log()[log_schema().timestamp_key()],
+            Utc.ymd(2021, 2, 3).and_hms(4, 5, 6).into()
+        );
+    }
+
+    #[tokio::test]
+    async fn http_timestamp_precedence() {
+        trace_init();
+
+        let body = r#"[{"timestamp":"yesterday"},{"key":"value"}]"#;
+        for precedence in &[TimestampPrecedence::Body, TimestampPrecedence::Received] {
+            let precedence = *precedence;
+            let (rx, addr) = source_with_config(|config| {
+                config.encoding = Encoding::Json;
+                config.timestamp_precedence = precedence;
+            })
+            .await;
+
+            assert_eq!(200, send(addr, body).await);
+
+            let events = collect_n(rx, 2).await;
+            let timestamp = &events[0].as_log()[log_schema().timestamp_key()];
+            match precedence {
+                TimestampPrecedence::Body => assert_eq!(*timestamp, "yesterday".into()),
+                TimestampPrecedence::Received => {
+                    assert!(matches!(timestamp, Value::Timestamp(_)))
+                }
+            }
+            assert!(matches!(
+                events[1].as_log()[log_schema().timestamp_key()],
+                Value::Timestamp(_)
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn http_received_at_key() {
+        trace_init();
+
+        let (rx, addr) = source_with_config(|config| {
+            config.encoding = Encoding::Json;
+            config.timestamp_field = Some("time".to_owned());
+            config.received_at_key = Some("received_at".to_owned());
+        })
+        .await;
+
+        assert_eq!(200, send(addr, r#"{"time":"2021-02-03T04:05:06Z"}"#).await);
+
+        let events = collect_n(rx, 1).await;
+        let log = events[0].as_log();
+        assert_eq!(
+            log[log_schema().timestamp_key()],
+            Utc.ymd(2021, 2, 3).and_hms(4, 5, 6).into()
+        );
+        match log.get("received_at") {
+            Some(Value::Timestamp(received_at)) => {
+                assert!(*received_at > Utc.ymd(2021, 2, 3).and_hms(4, 5, 6))
+            }
+            other => panic!("unexpected received_at {:?}", other),
+        }
+    }
+
+    #[test]
+    fn timestamp_formats() {
+        let expected = Utc.ymd(2021, 2, 3).and_hms(4, 5, 6);
+        for (format, value) in vec![
+            ("rfc3339", Value::from("2021-02-03T04:05:06+00:00")),
+            ("unix", Value::Integer(1612325106)),
+            ("unix", Value::from("1612325106")),
+            ("unix_ms", Value::Integer(1612325106000)),
+            ("%Y-%m-%d %H:%M:%S", Value::from("2021-02-03 04:05:06")),
+            (
+                "%d/%m/%Y %H:%M:%S %z",
+                Value::from("03/02/2021 05:05:06 +0100"),
+            ),
+        ] {
+            let format = TimestampFormat::from(format.to_owned());
+            assert_eq!(format.parse(&value), Ok(expected), "{:?}", format);
+        }
+        assert!(TimestampFormat::Rfc3339.parse(&Value::Integer(0)).is_err());
+        assert!(TimestampFormat::UnixMs.parse(&Value::from("soon")).is_err());
+    }
+
+    #[tokio::test]
+    async fn http_metric() {
+        trace_init();
+
+        let (rx, addr) = source_with_config(|config| config.encoding = Encoding::Metric).await;
+
+        assert_eq!(
+            400,
+            send(addr, r#"{"name":"requests","kind":"histogram"}"#).await
+        );
+        assert_eq!(
+            200,
+            send(
+                addr,
+                r#"[{"name":"requests","kind":"counter","value":2,"tags":{"host":"a"}},
+                    {"name":"queue_depth","kind":"gauge","value":7.5}]"#
+            )
+            .await
+        );
+
+        let events = collect_n(rx, 2).await;
+        let counter = events[0].as_metric();
+        assert_eq!(counter.name(), "requests");
+        assert_eq!(counter.data.kind, MetricKind::Incremental);
+        assert_eq!(counter.data.value, MetricValue::Counter { value: 2.0 });
+        assert_eq!(counter.tags().unwrap()["host"], "a");
+        let gauge = events[1].as_metric();
+        assert_eq!(gauge.name(), "queue_depth");
+        assert_eq!(gauge.data.kind, MetricKind::Absolute);
+        assert_eq!(gauge.data.value, MetricValue::Gauge { value: 7.5 });
+    }
+
+    #[tokio::test]
+    async fn http_gelf() {
+        trace_init();
+
+        let (rx, addr) = source_with_config(|config| config.encoding = Encoding::Gelf).await;
+
+        assert_eq!(400, send(addr, r#"{"version":"1.1","host":"a"}"#).await);
+        assert_eq!(
+            200,
+            send(
+                addr,
+                r#"{"version":"1.1","host":"example.org","short_message":"hi","level":3,"_app":"web"}"#
+            )
+            .await
+        );
+
+        let events = collect_n(rx, 1).await;
+        let log = events[0].as_log();
+        assert_eq!(log[log_schema().message_key()], "hi".into());
+        assert_eq!(log[log_schema().host_key()], "example.org".into());
+        assert_eq!(log["level"], Value::Integer(3));
+        assert_eq!(log["app"], "web".into());
+        assert_eq!(log[log_schema().source_type_key()], "http".into());
+    }
+
+    #[tokio::test]
+    async fn http_datadog_logs() {
+        trace_init();
+
+        let (rx, addr) = source_with_config(|config| {
+            config.encoding = Encoding::DatadogLogs;
+            config.auth = Some(
+                toml::from_str(
+                    r#"strategy = "api_key"
+api_key_header = "DD-API-KEY"
+keys = [{ key = "api-key" }]"#,
+                )
+                .unwrap(),
+            );
+        })
+        .await;
+
+        let body = r#"[{"message":"one","ddsource":"app","ddtags":"env:prod","hostname":"a","service":"web"}]"#;
+        let mut headers = HeaderMap::new();
+        headers.insert("DD-API-KEY", "wrong".parse().unwrap());
+        assert_eq!(401, send_with_headers(addr, body, headers.clone()).await);
+        headers.insert("DD-API-KEY", "api-key".parse().unwrap());
+        assert_eq!(
+            200,
+            reqwest::Client::new()
+                .post(&format!("http://{}/api/v2/logs", addr))
+                .headers(headers)
+                .body(body)
+                .send()
+                .await
+                .unwrap()
+                .status()
+                .as_u16()
+        );
+
+        let events = collect_n(rx, 1).await;
+        let log = events[0].as_log();
+        assert_eq!(log[log_schema().message_key()], "one".into());
+        assert_eq!(log[log_schema().host_key()], "a".into());
+        assert_eq!(log["ddsource"], "app".into());
+        assert_eq!(log["service"], "web".into());
+        assert_eq!(log["ddtags.env"], "prod".into());
+    }
+
+    #[tokio::test]
+    async fn http_es_bulk() {
+        trace_init();
+
+        let (rx, addr) = source_with_config(|config| config.encoding = Encoding::EsBulk).await;
+
+        assert_eq!(
+            400,
+            send_with_path(addr, r#"{"upsert":{}}"#, "/_bulk").await
+        );
+        assert_eq!(
+            200,
+            send_with_path(
+                addr,
+                "{\"index\":{\"_id\":\"1\"}}\n{\"message\":\"one\"}\n{\"delete\":{\"_id\":\"2\"}}\n",
+                "/logs/_bulk"
+            )
+            .await
+        );
+
+        let events = collect_n(rx, 1).await;
+        let log = events[0].as_log();
+        assert_eq!(log["message"], "one".into());
+        assert_eq!(log["_index"], "logs".into());
+        assert_eq!(log["_id"], "1".into());
+        assert_eq!(log[log_schema().source_type_key()], "http".into());
+    }
+
+    #[tokio::test]
+    async fn http_splunk_hec() {
+        trace_init();
+
+        let (rx, addr) = source_with_config(|config| {
+            config.encoding = Encoding::SplunkHec;
+            config.auth = Some(
+                toml::from_str(
+                    r#"strategy = "api_key"
+api_key_header = "Authorization"
+api_key_prefix = "Splunk "
+keys = [{ key = "token" }]"#,
+                )
+                .unwrap(),
+            );
+        })
+        .await;
+
+        let send = |token: &'static str, body: &'static str| {
+            reqwest::Client::new()
+                .post(&format!("http://{}/services/collector/event", addr))
+                .header("Authorization", format!("Splunk {}", token))
+                .body(body)
+                .send()
+        };
+
+        let response = send("wrong", r#"{"event":"one"}"#).await.unwrap();
+        assert_eq!(401, response.status().as_u16());
+        let response = send("token", r#"{"time":1}"#).await.unwrap();
+        assert_eq!(400, response.status().as_u16());
+        let response = send(
+            "token",
+            r#"{"event":"one","host":"a","time":1612325106}{"event":{"key":"two"}}"#,
+        )
+        .await
+        .unwrap();
+        assert_eq!(200, response.status().as_u16());
+        assert_eq!(
+            response.text().await.unwrap(),
+            r#"{"text":"Success","code":0}"#
+        );
+
+        let events = collect_n(rx, 2).await;
+        assert_eq!(events[0].as_log()[log_schema().message_key()], "one".into());
+        assert_eq!(events[0].as_log()[log_schema().host_key()], "a".into());
+        assert_eq!(
+            events[0].as_log()[log_schema().timestamp_key()],
+            Utc.ymd(2021, 2, 3).and_hms(4, 5, 6).into()
+        );
+        assert_eq!(events[1].as_log()["key"], "two".into());
+    }
+
+    #[tokio::test]
+    async fn http_splunk_hec_route() {
+        trace_init();
+
+        let (rx, addr) = source_with_config(|config| {
+            config.routes = vec![
+                RouteConfig {
+                    path: "/services/collector/event".to_owned(),
+                    encoding: Some(Encoding::SplunkHec),
+                    auth: None,
+                },
+                RouteConfig {
+                    path: "/logs".to_owned(),
+                    encoding: None,
+                    auth: None,
+                },
+            ];
+        })
+        .await;
+
+        let send_to = |path: &'static str, body: &'static str| {
+            reqwest::Client::new()
+                .post(&format!("http://{}{}", addr, path))
+                .body(body)
+                .send()
+        };
+
+        let response = send_to("/services/collector/event", r#"{"event":"one"}"#)
+            .await
+            .unwrap();
+        assert_eq!(200, response.status().as_u16());
+        assert_eq!(
+            response.text().await.unwrap(),
+            r#"{"text":"Success","code":0}"#
+        );
+        let response = send_to("/logs", "two").await.unwrap();
+        assert_eq!(200, response.status().as_u16());
+        assert_eq!(response.text().await.unwrap(), "");
+
+        let events = collect_n(rx, 2).await;
+        assert_eq!(events[0].as_log()[log_schema().message_key()], "one".into());
+        assert_eq!(events[1].as_log()[log_schema().message_key()], "two".into());
+    }
+
+    #[tokio::test]
+    async fn http_loki() {
+        trace_init();
+
+        let (rx, addr) = source_with_config(|config| config.encoding = Encoding::Loki).await;
+
+        assert_eq!(
+            400,
+            send_with_path(addr, r#"{"streams":[{"stream":{}}]}"#, "/loki/api/v1/push").await
+        );
+        assert_eq!(
+            200,
+            send_with_path(
+                addr,
+                r#"{"streams":[{"stream":{"job":"app"},"values":[["1612325106000000000","one"],["1612325107000000000","two"]]}]}"#,
+                "/loki/api/v1/push"
+            )
+            .await
+        );
+
+        let events = collect_n(rx, 2).await;
+        assert_eq!(events[0].as_log()[log_schema().message_key()], "one".into());
+        assert_eq!(events[0].as_log()["job"], "app".into());
+        assert_eq!(
+            events[1].as_log()[log_schema().timestamp_key()],
+            Utc.ymd(2021, 2, 3).and_hms(4, 5, 7).into()
+        );
+    }
+
+    #[tokio::test]
+    async fn http_influx_line() {
+        trace_init();
+
+        let (rx, addr) = source_with_config(|config| config.encoding = Encoding::InfluxLine).await;
+
+        assert_eq!(400, send(addr, "cpu usage=high").await);
+        assert_eq!(
+            200,
+            send_with_path(
+                addr,
+                "cpu,host=a usage=0.5,cores=4i 1612325106000000000\nmem free=10u",
+                "/write?db=telegraf",
+            )
+            .await
+        );
+
+        let events = collect_n(rx, 3).await;
+        let usage = events[0].as_metric();
+        assert_eq!(usage.namespace(), Some("cpu"));
+        assert_eq!(usage.name(), "usage");
+        assert_eq!(usage.data.kind, MetricKind::Absolute);
+        assert_eq!(usage.data.value, MetricValue::Gauge { value: 0.5 });
+        assert_eq!(usage.tags().unwrap()["host"], "a");
+        assert_eq!(usage.tags().unwrap()["db"], "telegraf");
+        assert_eq!(
+            usage.data.timestamp,
+            Some(Utc.ymd(2021, 2, 3).and_hms(4, 5, 6))
+        );
+        assert_eq!(events[1].as_metric().name(), "cores");
+        assert_eq!(
+            events[2].as_metric().data.value,
+            MetricValue::Gauge { value: 10.0 }
+        );
+    }
+
+    #[tokio::test]
+    async fn http_prometheus_remote_write() {
+        use prometheus_parser::proto;
+        use prost::Message;
+
+        trace_init();
+
+        let (rx, addr) = source_with_config(|config| {
+            config.encoding = Encoding::PrometheusRemoteWrite;
+        })
+        .await;
+
+        let request = proto::WriteRequest {
+            timeseries: vec![proto::TimeSeries {
+                labels: vec![
+                    proto::Label {
+                        name: "__name__".to_owned(),
+                        value: "up".to_owned(),
+                    },
+                    proto::Label {
+                        name: "job".to_owned(),
+                        value: "node".to_owned(),
+                    },
+                ],
+                samples: vec![proto::Sample {
+                    value: 1.0,
+                    timestamp: 1_612_325_106_000,
+                }],
+            }],
+            ..Default::default()
+        };
+        let mut body = Vec::new();
+        request.encode(&mut body).unwrap();
+        let body = snap::raw::Encoder::new().compress_vec(&body).unwrap();
+
+        let mut headers = HeaderMap::new();
+        headers.insert("Content-Encoding", "snappy".parse().unwrap());
+        headers.insert(
+            "X-Prometheus-Remote-Write-Version",
+            "0.1.0".parse().unwrap(),
+        );
+        assert_eq!(200, send_bytes(addr, body.clone(), headers).await);
+        assert_eq!(200, send_bytes(addr, body, HeaderMap::new()).await);
+        assert_eq!(400, send(addr, "not snappy").await);
+
+        let events = collect_n(rx, 2).await;
+        for event in events {
+            let metric = event.as_metric();
+            assert_eq!(metric.name(), "up");
+            assert_eq!(metric.data.value, MetricValue::Gauge { value: 1.0 });
+            assert_eq!(metric.tags().unwrap()["job"], "node");
+            assert_eq!(
+                metric.data.timestamp,
+                Some(Utc.ymd(2021, 2, 3).and_hms(4, 5, 6))
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn http_raw_body_key() {
+        trace_init();
+
+        let (rx, addr) = source_with_config(|config| {
+            config.encoding = Encoding::Ndjson;
+            config.raw_body_key = Some("raw_body".to_owned());
+        })
+        .await;
+
+        let body = "{\"key\":1}\n{\"key\":2}";
+        assert_eq!(200, send(addr, body).await);
+
+        let mut headers = HeaderMap::new();
+        headers.insert("Content-Type", "text/plain".parse().unwrap());
+        let (text_rx, text_addr) = source_with_config(|config| {
+            config.raw_body_key = Some("raw_body".to_owned());
+        })
+        .await;
+        assert_eq!(
+            200,
+            send_bytes(text_addr, b"caf\xe9".to_vec(), headers).await
+        );
+
+        let events = collect_n(rx, 2).await;
+        for event in events {
+            assert_eq!(event.as_log()["raw_body"], body.into());
+        }
+        let events = collect_n(text_rx, 1).await;
+        assert_eq!(events[0].as_log()["raw_body"], "Y2Fm6Q==".into());
+    }
+
+    #[tokio::test]
+    async fn http_body_base64() {
+        trace_init();
+
+        let (rx, addr) = source_with_config(|config| {
+            config.encoding = Encoding::Json;
+            config.body_base64 = true;
+        })
+        .await;
+
+        assert_eq!(400, send(addr, "not base64!").await);
+        assert_eq!(
+            200,
+            send(
+                addr,
+                &format!("{}\n", base64::encode(r#"{"key1":"value1"}"#))
+            )
+            .await
+        );
+
+        let mut events = collect_n(rx, 1).await;
+        assert_eq!(events.remove(0).as_log()["key1"], "value1".into());
+    }
+
+    #[tokio::test]
+    async fn http_all_headers_key() {
+        trace_init();
+
+        let (rx, addr) = source_with_config(|config| {
+            config.all_headers_key = Some("request_headers".to_owned());
+        })
+        .await;
+
+        let mut headers = HeaderMap::new();
+        headers.insert("User-Agent", "test_client".parse().unwrap());
+        headers.append("X-Tag", "a".parse().unwrap());
+        headers.append("X-Tag", "b".parse().unwrap());
+        assert_eq!(200, send_with_headers(addr, "test body", headers).await);
+
+        let mut events = collect_n(rx, 1).await;
+        let event = events.remove(0);
+        let log = event.as_log();
+        match &log["request_headers"] {
+            Value::Map(map) => {
+                assert_eq!(map["user-agent"], "test_client".into());
+                assert_eq!(map["x-tag"], Value::Array(vec!["a".into(), "b".into()]));
+            }
+            value => panic!("Unexpected value {:?}", value),
+        }
+    }
+
+    #[tokio::test]
+    async fn http_query() {
+        trace_init();
+        let (rx, addr) = source(
+            Encoding::Ndjson,
+            vec![],
+            vec![
+                "source".to_string(),
+                "region".to_string(),
+                "absent".to_string(),
+            ],
+        )
+        .await;
+
+        assert_eq!(
+            200,
+            send_with_query(addr, "{\"key1\":\"value1\"}", "source=staging&region=gb").await
+        );
+
+        let mut events = collect_n(rx, 1).await;
+        {
+            let event = events.remove(0);
+            let log = event.as_log();
+            assert_eq!(log["key1"], "value1".into());
+            assert_eq!(log["source"], "staging".into());
+            assert_eq!(log["region"], "gb".into());
+            assert_eq!(log["absent"], Value::Null);
+            assert!(log.get(log_schema().timestamp_key()).is_some());
+            assert_eq!(log[log_schema().source_type_key()], "http".into());
+        }
+    }
+
+    #[tokio::test]
+    async fn http_gzip_deflate() {
+        trace_init();
+
+        let body = "test body";
+
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(body.as_bytes()).unwrap();
+        let body = encoder.finish().unwrap();
+
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(body.as_slice()).unwrap();
+        let body = encoder.finish().unwrap();
+
+        let mut headers = HeaderMap::new();
+        headers.insert("Content-Encoding", "gzip, deflate".parse().unwrap());
+
+        let (rx, addr) = source(Encoding::default(), vec![], vec![]).await;
+
+        assert_eq!(200, send_bytes(addr, body, headers).await);
+
+        let mut events = collect_n(rx, 1).await;
+        {
+            let event = events.remove(0);
+            let log = event.as_log();
+            assert_eq!(log[log_schema().message_key()], "test body".into());
+            assert!(log.get(log_schema().timestamp_key()).is_some());
+            assert_eq!(log[log_schema().source_type_key()], "http".into());
+        }
+    }
+}
@@ -0,0 +1,185 @@
+use bytes::Bytes;
+
+/// A single part of a `multipart/form-data` body.
+#[derive(Debug, PartialEq)]
+pub struct Part {
+    pub name: Option<String>,
+    pub filename: Option<String>,
+    pub content_type: Option<String>,
+    pub body: Bytes,
+}
+
+/// Extracts the boundary from a `multipart/form-data` content type.
+pub fn boundary(content_type: &str) -> Option<String> {
+    let mut params = content_type.split(';');
+    let media_type = params.next()?.trim();
+    if !media_type.eq_ignore_ascii_case("multipart/form-data") {
+        return None;
+    }
+    params
+        .filter_map(parse_param)
+        .find(|(key, _)| key.eq_ignore_ascii_case("boundary"))
+        .map(|(_, value)| value)
+        .filter(|boundary| !boundary.is_empty())
+}
+
+pub fn parse(body: &Bytes, boundary: &str) -> Result<Vec<Part>, String> {
+    let delimiter = format!("--{}", boundary);
+    let delimiter = delimiter.as_bytes();
+
+    let mut position = find(body, delimiter, 0)
+        .ok_or_else(|| "Missing initial boundary".to_owned())?
+        + delimiter.len();
+    let mut parts = Vec::new();
+    loop {
+        let rest = &body[position..];
+        if rest.starts_with(b"--") {
+            // closing boundary
+            return Ok(parts);
+        }
+        position += line_break_len(rest).ok_or_else(|| "Malformed boundary".to_owned())?;
+
+        let end =
+            find(body, delimiter, position).ok_or_else(|| "Missing closing boundary".to_owned())?;
+        // The line break preceding the delimiter belongs to the delimiter.
+        let mut part_end = end;
+        if body[..part_end].ends_with(b"\n") {
+            part_end -= 1;
+            if body[..part_end].ends_with(b"\r") {
+                part_end -= 1;
+            }
+        }
+        if part_end < position {
+            return Err("Malformed part".to_owned());
+        }
+        parts.push(parse_part(body.slice(position..part_end))?);
+        position = end + delimiter.len();
+    }
+}
+
+fn parse_part(part: Bytes) -> Result<Part, String> {
+    let (headers_end, body_start) = find(&part, b"\r\n\r\n", 0)
+        .map(|index| (index, index + 4))
+        .or_else(|| find(&part, b"\n\n", 0).map(|index| (index, index + 2)))
+        .ok_or_else(|| "Missing part headers".to_owned())?;
+    let headers = std::str::from_utf8(&part[..headers_end])
+        .map_err(|_| "Part headers aren't valid UTF-8".to_owned())?;
+
+    let mut name = None;
+    let mut filename = None;
+    let mut content_type = None;
+    for line in headers.lines() {
+        let (header, value) = match line.find(':') {
+            Some(index) => (line[..index].trim(), line[index + 1..].trim()),
+            None => continue,
+        };
+        if header.eq_ignore_ascii_case("content-disposition") {
+            for (key, value) in value.split(';').skip(1).filter_map(parse_param) {
+                if key.eq_ignore_ascii_case("name") {
+                    name = Some(value);
+                } else if key.eq_ignore_ascii_case("filename") {
+                    filename = Some(value);
+                }
+            }
+        } else if header.eq_ignore_ascii_case("content-type") {
+            content_type = Some(value.to_owned());
+        }
+    }
+
+    Ok(Part {
+        name,
+        filename,
+        content_type,
+        body: part.slice(body_start..),
+    })
+}
+
+fn parse_param(param: &str) -> Option<(String, String)> {
+    let index = param.find('=')?;
+    let key = param[..index].trim();
+    let value = param[index + 1..].trim();
+    let value = value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .unwrap_or(value);
+    Some((key.to_owned(), value.to_owned()))
+}
+
+fn line_break_len(bytes: &[u8]) -> Option<usize> {
+    if bytes.starts_with(b"\r\n") {
+        Some(2)
+    } else if bytes.starts_with(b"\n") {
+        Some(1)
+    } else {
+        None
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8], from: usize) -> Option<usize> {
+    haystack
+        .get(from..)?
+        .windows(needle.len())
+        .position(|window| window == needle)
+        .map(|index| index + from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_boundary() {
+        assert_eq!(
+            boundary("multipart/form-data; boundary=\"abc 123\""),
+            Some("abc 123".to_owned())
+        );
+        assert_eq!(
+            boundary("Multipart/Form-Data;charset=utf-8;boundary=xyz"),
+            Some("xyz".to_owned())
+        );
+        assert_eq!(boundary("application/json; boundary=xyz"), None);
+        assert_eq!(boundary("multipart/form-data"), None);
+    }
+
+    #[test]
+    fn parses_parts() {
+        let body = Bytes::from(
+            "preamble\r\n--xyz\r\n\
+             Content-Disposition: form-data; name=\"meta\"\r\n\
+             Content-Type: application/json\r\n\
+             \r\n\
+             {\"a\":1}\r\n\
+             --xyz\r\n\
+             Content-Disposition: form-data; name=\"upload\"; filename=\"a.txt\"\r\n\
+             \r\n\
+             line 1\r\nline 2\r\n\
+             --xyz--\r\n",
+        );
+
+        let parts = parse(&body, "xyz").unwrap();
+
+        assert_eq!(
+            parts,
+            vec![
+                Part {
+                    name: Some("meta".to_owned()),
+                    filename: None,
+                    content_type: Some("application/json".to_owned()),
+                    body: Bytes::from("{\"a\":1}"),
+                },
+                Part {
+                    name: Some("upload".to_owned()),
+                    filename: Some("a.txt".to_owned()),
+                    content_type: None,
+                    body: Bytes::from("line 1\r\nline 2"),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_unterminated_body() {
+        let body = Bytes::from("--xyz\r\nContent-Disposition: form-data; name=\"a\"\r\n\r\nvalue");
+        assert!(parse(&body, "xyz").is_err());
+    }
+}
@@ -0,0 +1,322 @@
+use crate::sources::util::ErrorMessage;
+use jsonschema::JSONSchema;
+use serde_json::{json, Value as JsonValue};
+use snafu::{ResultExt, Snafu};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+use warp::http::{Method, StatusCode};
+
+#[derive(Debug, Snafu)]
+pub enum BuildError {
+    #[snafu(display("Could not read OpenAPI document {:?}: {}", path, source))]
+    ReadDocument {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[snafu(display("Could not parse OpenAPI document {:?}: {}", path, source))]
+    ParseDocument {
+        path: PathBuf,
+        source: serde_yaml::Error,
+    },
+    #[snafu(display("Invalid OpenAPI document {:?}: {}", path, message))]
+    InvalidDocument { path: PathBuf, message: String },
+}
+
+/// The request body an operation accepts.
+struct Operation {
+    schema: Option<JSONSchema>,
+    body_required: bool,
+}
+
+/// A path of the document, such as `/users/{id}`, whose `{}` enclosed
+/// segments match any single segment.
+struct PathItem {
+    template: String,
+    segments: Vec<Option<String>>,
+    operations: HashMap<Method, Operation>,
+}
+
+impl PathItem {
+    fn matches(&self, path: &str) -> bool {
+        let mut path_segments = segments(path);
+        self.segments
+            .iter()
+            .all(|segment| match (segment, path_segments.next()) {
+                (Some(literal), Some(value)) => literal == value,
+                (None, Some(_)) => true,
+                (_, None) => false,
+            })
+            && path_segments.next().is_none()
+    }
+}
+
+/// Validates request bodies against the JSON schema of the OpenAPI operation
+/// their path and method map to.
+pub struct OpenApiValidator {
+    paths: Vec<PathItem>,
+}
+
+impl OpenApiValidator {
+    /// Loads an OpenAPI 3 document, written as either JSON or YAML.
+    pub fn load(path: &Path) -> Result<Self, BuildError> {
+        let document = std::fs::read(path).context(ReadDocument { path })?;
+        let document: JsonValue =
+            serde_yaml::from_slice(&document).context(ParseDocument { path })?;
+        let invalid = |message: String| BuildError::InvalidDocument {
+            path: path.into(),
+            message,
+        };
+
+        let items = document
+            .get("paths")
+            .and_then(JsonValue::as_object)
+            .ok_or_else(|| invalid("missing paths".to_owned()))?;
+        let mut paths = items
+            .iter()
+            .map(|(template, item)| {
+                let operations = item
+                    .as_object()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|(method, operation)| {
+                        let method = method_of(method)?;
+                        let operation = Operation::new(&document, operation).map_err(|message| {
+                            invalid(format!("{} {}: {}", method, template, message))
+                        });
+                        Some(operation.map(|operation| (method, operation)))
+                    })
+                    .collect::<Result<_, _>>()?;
+                Ok(PathItem {
+                    template: template.clone(),
+                    segments: segments(template)
+                        .map(|segment| {
+                            if segment.starts_with('{') && segment.ends_with('}') {
+                                None
+                            } else {
+                                Some(segment.to_owned())
+                            }
+                        })
+                        .collect(),
+                    operations,
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        // Concrete paths take precedence over templated ones matching the same path
+        paths.sort_by_key(|item| item.segments.iter().filter(|s| s.is_none()).count());
+
+        Ok(Self { paths })
+    }
+
+    /// Validates a request body, rejecting requests whose path or method the
+    /// document doesn't describe, and bodies violating the operation's schema
+    /// with a 400 response naming the first failing field.
+    pub fn validate(&self, method: &Method, path: &str, body: &[u8]) -> Result<(), ErrorMessage> {
+        let item = self
+            .paths
+            .iter()
+            .find(|item| item.matches(path))
+            .ok_or_else(|| {
+                ErrorMessage::new(
+                    StatusCode::NOT_FOUND,
+                    format!("Path {} is not described by the OpenAPI document", path),
+                )
+            })?;
+        let operation = item.operations.get(method).ok_or_else(|| {
+            ErrorMessage::new(
+                StatusCode::METHOD_NOT_ALLOWED,
+                format!(
+                    "Method {} is not allowed for path {}",
+                    method, item.template
+                ),
+            )
+        })?;
+
+        if body.is_empty() {
+            return if operation.body_required {
+                Err(ErrorMessage::new(
+                    StatusCode::BAD_REQUEST,
+                    "Missing request body".to_owned(),
+                ))
+            } else {
+                Ok(())
+            };
+        }
+        let schema = match &operation.schema {
+            Some(schema) => schema,
+            None => return Ok(()),
+        };
+        let value: JsonValue = serde_json::from_slice(body).map_err(|error| {
+            ErrorMessage::new(
+                StatusCode::BAD_REQUEST,
+                format!("Request body is not valid JSON: {}", error),
+            )
+        })?;
+
+        let mut errors = match schema.validate(&value) {
+            Ok(()) => return Ok(()),
+            Err(errors) => errors,
+        };
+        let message = match errors.next() {
+            Some(error) => format!(
+                "Field {:?} failed OpenAPI schema validation: {}",
+                error.instance_path.to_string(),
+                error
+            ),
+            None => "Failed OpenAPI schema validation".to_owned(),
+        };
+        Err(ErrorMessage::new(StatusCode::BAD_REQUEST, message))
+    }
+}
+
+impl Operation {
+    fn new(document: &JsonValue, operation: &JsonValue) -> Result<Self, String> {
+        let request_body = match operation.get("requestBody") {
+            Some(request_body) => resolve(document, request_body)?,
+            None => {
+                return Ok(Self {
+                    schema: None,
+                    body_required: false,
+                })
+            }
+        };
+        let body_required = request_body
+            .get("required")
+            .and_then(JsonValue::as_bool)
+            .unwrap_or(false);
+        let content = request_body.get("content").and_then(JsonValue::as_object);
+        let schema = content
+            .and_then(|content| {
+                content.get("application/json").or_else(|| {
+                    content
+                        .iter()
+                        .find(|(media_type, _)| media_type.ends_with("+json"))
+                        .map(|(_, media)| media)
+                })
+            })
+            .and_then(|media| media.get("schema"));
+
+        // References to `#/components/...` resolve against the copy of the
+        // document's components carried alongside the operation's schema.
+        let components = document.get("components").cloned();
+        let schema = schema
+            .map(|schema| {
+                JSONSchema::compile(&json!({"allOf": [schema], "components": components}))
+                    .map_err(|error| error.to_string())
+            })
+            .transpose()?;
+        Ok(Self {
+            schema,
+            body_required,
+        })
+    }
+}
+
+/// Follows a `$ref` to another part of the document.
+fn resolve<'a>(document: &'a JsonValue, value: &'a JsonValue) -> Result<&'a JsonValue, String> {
+    match value.get("$ref").and_then(JsonValue::as_str) {
+        Some(reference) => reference
+            .strip_prefix('#')
+            .and_then(|pointer| document.pointer(pointer))
+            .ok_or_else(|| format!("unresolvable reference {:?}", reference)),
+        None => Ok(value),
+    }
+}
+
+/// The methods of a path item; its other fields, such as `parameters`, aren't operations.
+fn method_of(key: &str) -> Option<Method> {
+    match key {
+        "get" | "put" | "post" | "delete" | "options" | "head" | "patch" | "trace" => {
+            Method::from_bytes(key.to_ascii_uppercase().as_bytes()).ok()
+        }
+        _ => None,
+    }
+}
+
+fn segments(path: &str) -> impl Iterator<Item = &str> {
+    path.split('/').filter(|segment| !segment.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn validator() -> OpenApiValidator {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        write!(
+            file,
+            r#"
+openapi: 3.0.0
+info:
+  title: Webhooks
+  version: "1"
+paths:
+  /events/{tenant}:
+    post:
+      requestBody:
+        required: true
+        content:
+          application/json:
+            schema:
+              $ref: "#/components/schemas/Event"
+  /events/health:
+    get: {{}}
+components:
+  schemas:
+    Event:
+      type: object
+      properties:
+        level:
+          enum: [info, error]
+      required: [level]
+"#
+        )
+        .unwrap();
+        OpenApiValidator::load(file.path()).unwrap()
+    }
+
+    #[test]
+    fn validates_request_body() {
+        let validator = validator();
+
+        assert!(validator
+            .validate(&Method::POST, "/events/acme", br#"{"level":"info"}"#)
+            .is_ok());
+        let error = validator
+            .validate(&Method::POST, "/events/acme", br#"{"level":"debug"}"#)
+            .unwrap_err();
+        assert_eq!(error.code(), 400);
+        assert_eq!(
+            validator
+                .validate(&Method::POST, "/events/acme", b"")
+                .unwrap_err()
+                .code(),
+            400
+        );
+    }
+
+    #[test]
+    fn matches_operations() {
+        let validator = validator();
+
+        assert!(validator
+            .validate(&Method::GET, "/events/health", b"")
+            .is_ok());
+        assert_eq!(
+            validator
+                .validate(&Method::GET, "/events/acme", b"")
+                .unwrap_err()
+                .code(),
+            405
+        );
+        assert_eq!(
+            validator
+                .validate(&Method::POST, "/other", br#"{"level":"info"}"#)
+                .unwrap_err()
+                .code(),
+            404
+        );
+    }
+}
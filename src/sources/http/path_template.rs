@@ -0,0 +1,96 @@
+use crate::sources::util::ErrorMessage;
+use warp::http::StatusCode;
+
+#[derive(Clone, Debug, PartialEq)]
+enum Segment {
+    Literal(String),
+    Parameter(String),
+}
+
+/// A request path such as `/ingest/:env/:service`, whose `:` prefixed segments
+/// are extracted as named parameters.
+#[derive(Clone, Debug)]
+pub struct PathTemplate {
+    template: String,
+    segments: Vec<Segment>,
+}
+
+impl PathTemplate {
+    pub fn new(template: &str) -> Result<Self, String> {
+        let segments = segments(template)
+            .map(|segment| match segment.strip_prefix(':') {
+                Some("") => Err(format!("Unnamed parameter in path_template {:?}", template)),
+                Some(name) => Ok(Segment::Parameter(name.to_owned())),
+                None => Ok(Segment::Literal(segment.to_owned())),
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            template: template.to_owned(),
+            segments,
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.template
+    }
+
+    /// Returns the parameters of a matching path, or a 404 response otherwise.
+    pub fn parameters(&self, path: &str) -> Result<Vec<(String, String)>, ErrorMessage> {
+        let not_found = || {
+            ErrorMessage::new(
+                StatusCode::NOT_FOUND,
+                format!("Path {} does not match the path template", path),
+            )
+        };
+
+        let mut parameters = Vec::new();
+        let mut path_segments = segments(path);
+        for segment in &self.segments {
+            let value = path_segments.next().ok_or_else(not_found)?;
+            match segment {
+                Segment::Literal(literal) if literal == value => (),
+                Segment::Literal(_) => return Err(not_found()),
+                Segment::Parameter(name) => parameters.push((name.clone(), value.to_owned())),
+            }
+        }
+        match path_segments.next() {
+            Some(_) => Err(not_found()),
+            None => Ok(parameters),
+        }
+    }
+}
+
+fn segments(path: &str) -> impl Iterator<Item = &str> {
+    path.split('/').filter(|segment| !segment.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_unnamed_parameter() {
+        assert!(PathTemplate::new("/ingest/:").is_err());
+    }
+
+    #[test]
+    fn extracts_parameters() {
+        let template = PathTemplate::new("/ingest/:env/:service").unwrap();
+
+        assert_eq!(
+            template.parameters("/ingest/prod/api/").unwrap(),
+            vec![
+                ("env".to_owned(), "prod".to_owned()),
+                ("service".to_owned(), "api".to_owned())
+            ]
+        );
+        for path in &[
+            "/ingest/prod",
+            "/ingest/prod/api/extra",
+            "/other/prod/api",
+            "/",
+        ] {
+            assert_eq!(template.parameters(path).unwrap_err().code(), 404);
+        }
+    }
+}
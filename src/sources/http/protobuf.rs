@@ -0,0 +1,457 @@
+use crate::event::Value;
+use bytes::Buf;
+use prost::{
+    encoding::{decode_key, decode_varint, WireType},
+    Message,
+};
+use prost_types::{
+    field_descriptor_proto::{Label, Type},
+    DescriptorProto, EnumDescriptorProto, FieldDescriptorProto, FileDescriptorSet,
+};
+use snafu::{ResultExt, Snafu};
+use std::{
+    collections::{BTreeMap, HashMap},
+    convert::TryFrom,
+    path::{Path, PathBuf},
+};
+
+#[derive(Debug, Snafu)]
+pub enum BuildError {
+    #[snafu(display("Could not read descriptor set {:?}: {}", path, source))]
+    ReadDescriptorSet {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[snafu(display("Could not decode descriptor set {:?}: {}", path, source))]
+    DecodeDescriptorSet {
+        path: PathBuf,
+        source: prost::DecodeError,
+    },
+    #[snafu(display("Message type {:?} not found in descriptor set", message_type))]
+    UnknownMessageType { message_type: String },
+}
+
+#[derive(Debug, Snafu)]
+pub enum DecodeError {
+    #[snafu(display("{}", source))]
+    Wire { source: prost::DecodeError },
+    #[snafu(display("Buffer underflow"))]
+    Truncated,
+    #[snafu(display("Unexpected wire type {:?} for field {:?}", wire_type, field))]
+    UnexpectedWireType { field: String, wire_type: WireType },
+    #[snafu(display("Unexpected end group tag {}", tag))]
+    UnexpectedEndGroup { tag: u32 },
+    #[snafu(display("Message type {:?} not found in descriptor set", message_type))]
+    MissingMessageType { message_type: String },
+}
+
+/// Decodes protobuf messages without generated code, by walking the wire format
+/// and resolving each field against a `FileDescriptorSet`.
+#[derive(Debug)]
+pub struct ProtobufDecoder {
+    message_type: String,
+    messages: HashMap<String, DescriptorProto>,
+    enums: HashMap<String, EnumDescriptorProto>,
+}
+
+impl ProtobufDecoder {
+    pub fn load(path: &Path, message_type: &str) -> Result<Self, BuildError> {
+        let bytes = std::fs::read(path).context(ReadDescriptorSet { path })?;
+        let descriptor_set =
+            FileDescriptorSet::decode(bytes.as_slice()).context(DecodeDescriptorSet { path })?;
+        Self::new(descriptor_set, message_type)
+    }
+
+    pub fn new(descriptor_set: FileDescriptorSet, message_type: &str) -> Result<Self, BuildError> {
+        let mut messages = HashMap::new();
+        let mut enums = HashMap::new();
+        for file in descriptor_set.file {
+            let scope = match file.package() {
+                "" => String::new(),
+                package => format!(".{}", package),
+            };
+            index_types(
+                &scope,
+                file.message_type,
+                file.enum_type,
+                &mut messages,
+                &mut enums,
+            );
+        }
+
+        // Type names are referenced fully qualified, with a leading dot.
+        let message_type = format!(".{}", message_type.trim_start_matches('.'));
+        if !messages.contains_key(&message_type) {
+            return Err(BuildError::UnknownMessageType { message_type });
+        }
+
+        Ok(Self {
+            message_type,
+            messages,
+            enums,
+        })
+    }
+
+    pub fn decode(&self, buf: &[u8]) -> Result<BTreeMap<String, Value>, DecodeError> {
+        self.decode_message(&self.message_type, buf)
+    }
+
+    fn decode_message(
+        &self,
+        message_type: &str,
+        mut buf: &[u8],
+    ) -> Result<BTreeMap<String, Value>, DecodeError> {
+        let descriptor =
+            self.messages
+                .get(message_type)
+                .ok_or_else(|| DecodeError::MissingMessageType {
+                    message_type: message_type.to_owned(),
+                })?;
+
+        let mut fields = BTreeMap::new();
+        while buf.has_remaining() {
+            let (tag, wire_type) = decode_key(&mut buf).context(Wire)?;
+            let field = match descriptor
+                .field
+                .iter()
+                .find(|field| i32::try_from(tag).ok() == Some(field.number()))
+            {
+                Some(field) => field,
+                // Unknown fields are dropped.
+                None => {
+                    skip_field(wire_type, tag, &mut buf)?;
+                    continue;
+                }
+            };
+
+            let values = self.decode_field(field, wire_type, tag, &mut buf)?;
+            if field.label() == Label::Repeated {
+                let entry = fields
+                    .entry(field.name().to_owned())
+                    .or_insert_with(|| Value::Array(Vec::new()));
+                if let Value::Array(array) = entry {
+                    array.extend(values);
+                }
+            } else if let Some(value) = values.into_iter().last() {
+                fields.insert(field.name().to_owned(), value);
+            }
+        }
+
+        // Map fields are encoded as repeated entry messages with `key` and `value` fields.
+        for field in &descriptor.field {
+            if self.is_map_entry(field) {
+                if let Some(Value::Array(entries)) = fields.remove(field.name()) {
+                    let map = entries
+                        .into_iter()
+                        .filter_map(|entry| match entry {
+                            Value::Map(mut entry) => {
+                                let key = entry.remove("key")?.to_string_lossy();
+                                let value = entry.remove("value").unwrap_or(Value::Null);
+                                Some((key, value))
+                            }
+                            _ => None,
+                        })
+                        .collect::<BTreeMap<_, _>>();
+                    fields.insert(field.name().to_owned(), Value::Map(map));
+                }
+            }
+        }
+
+        Ok(fields)
+    }
+
+    fn decode_field(
+        &self,
+        field: &FieldDescriptorProto,
+        wire_type: WireType,
+        tag: u32,
+        buf: &mut &[u8],
+    ) -> Result<Vec<Value>, DecodeError> {
+        match (field.r#type(), wire_type) {
+            (Type::String, WireType::LengthDelimited)
+            | (Type::Bytes, WireType::LengthDelimited) => {
+                let data = take_length_delimited(buf)?;
+                Ok(vec![Value::from(bytes::Bytes::copy_from_slice(data))])
+            }
+            (Type::Message, WireType::LengthDelimited) => {
+                let data = take_length_delimited(buf)?;
+                Ok(vec![Value::Map(
+                    self.decode_message(field.type_name(), data)?,
+                )])
+            }
+            // Packed repeated scalars
+            (_, WireType::LengthDelimited) => {
+                let scalar_wire_type = match field.r#type() {
+                    Type::Double | Type::Fixed64 | Type::Sfixed64 => WireType::SixtyFourBit,
+                    Type::Float | Type::Fixed32 | Type::Sfixed32 => WireType::ThirtyTwoBit,
+                    Type::Group => return Err(unexpected_wire_type(field, wire_type)),
+                    _ => WireType::Varint,
+                };
+                let mut data = take_length_delimited(buf)?;
+                let mut values = Vec::new();
+                while data.has_remaining() {
+                    values.push(self.decode_scalar(field, scalar_wire_type, &mut data)?);
+                }
+                Ok(values)
+            }
+            // Groups are deprecated and not supported, so they are dropped like unknown fields.
+            (_, WireType::StartGroup) => {
+                skip_field(wire_type, tag, buf)?;
+                Ok(Vec::new())
+            }
+            (_, wire_type) => Ok(vec![self.decode_scalar(field, wire_type, buf)?]),
+        }
+    }
+
+    fn decode_scalar(
+        &self,
+        field: &FieldDescriptorProto,
+        wire_type: WireType,
+        buf: &mut &[u8],
+    ) -> Result<Value, DecodeError> {
+        let value = match (field.r#type(), wire_type) {
+            (field_type, WireType::Varint) => {
+                let value = decode_varint(buf).context(Wire)?;
+                match field_type {
+                    Type::Int64 => Value::Integer(value as i64),
+                    Type::Uint64 => match i64::try_from(value) {
+                        Ok(value) => Value::Integer(value),
+                        Err(_) => Value::Float(value as f64),
+                    },
+                    Type::Int32 => Value::Integer(value as i32 as i64),
+                    Type::Uint32 => Value::Integer(value as u32 as i64),
+                    Type::Sint32 | Type::Sint64 => {
+                        Value::Integer((value >> 1) as i64 ^ -((value & 1) as i64))
+                    }
+                    Type::Bool => Value::Boolean(value != 0),
+                    Type::Enum => self.enum_value(field.type_name(), value as i32),
+                    _ => return Err(unexpected_wire_type(field, wire_type)),
+                }
+            }
+            (Type::Fixed64, WireType::SixtyFourBit) => {
+                let value = take_fixed(buf, 8)?.get_u64_le();
+                match i64::try_from(value) {
+                    Ok(value) => Value::Integer(value),
+                    Err(_) => Value::Float(value as f64),
+                }
+            }
+            (Type::Sfixed64, WireType::SixtyFourBit) => {
+                Value::Integer(take_fixed(buf, 8)?.get_i64_le())
+            }
+            (Type::Double, WireType::SixtyFourBit) => {
+                Value::Float(take_fixed(buf, 8)?.get_f64_le())
+            }
+            (Type::Fixed32, WireType::ThirtyTwoBit) => {
+                Value::Integer(take_fixed(buf, 4)?.get_u32_le() as i64)
+            }
+            (Type::Sfixed32, WireType::ThirtyTwoBit) => {
+                Value::Integer(take_fixed(buf, 4)?.get_i32_le() as i64)
+            }
+            (Type::Float, WireType::ThirtyTwoBit) => Value::from(take_fixed(buf, 4)?.get_f32_le()),
+            _ => return Err(unexpected_wire_type(field, wire_type)),
+        };
+        Ok(value)
+    }
+
+    fn enum_value(&self, enum_type: &str, number: i32) -> Value {
+        self.enums
+            .get(enum_type)
+            .and_then(|descriptor| {
+                descriptor
+                    .value
+                    .iter()
+                    .find(|value| value.number() == number)
+            })
+            .map(|value| Value::from(value.name()))
+            .unwrap_or_else(|| Value::Integer(number as i64))
+    }
+
+    fn is_map_entry(&self, field: &FieldDescriptorProto) -> bool {
+        field.label() == Label::Repeated
+            && field.r#type() == Type::Message
+            && self
+                .messages
+                .get(field.type_name())
+                .and_then(|message| message.options.as_ref())
+                .map(|options| options.map_entry())
+                .unwrap_or(false)
+    }
+}
+
+fn index_types(
+    scope: &str,
+    message_types: Vec<DescriptorProto>,
+    enum_types: Vec<EnumDescriptorProto>,
+    messages: &mut HashMap<String, DescriptorProto>,
+    enums: &mut HashMap<String, EnumDescriptorProto>,
+) {
+    for enum_type in enum_types {
+        enums.insert(format!("{}.{}", scope, enum_type.name()), enum_type);
+    }
+    for mut message_type in message_types {
+        let name = format!("{}.{}", scope, message_type.name());
+        let nested_types = std::mem::take(&mut message_type.nested_type);
+        let nested_enums = std::mem::take(&mut message_type.enum_type);
+        index_types(&name, nested_types, nested_enums, messages, enums);
+        messages.insert(name, message_type);
+    }
+}
+
+fn take_length_delimited<'a>(buf: &mut &'a [u8]) -> Result<&'a [u8], DecodeError> {
+    let len = decode_varint(buf).context(Wire)? as usize;
+    take_fixed(buf, len)
+}
+
+fn take_fixed<'a>(buf: &mut &'a [u8], len: usize) -> Result<&'a [u8], DecodeError> {
+    if buf.len() < len {
+        return Err(DecodeError::Truncated);
+    }
+    let (data, rest) = buf.split_at(len);
+    *buf = rest;
+    Ok(data)
+}
+
+fn skip_field(wire_type: WireType, tag: u32, buf: &mut &[u8]) -> Result<(), DecodeError> {
+    match wire_type {
+        WireType::Varint => {
+            decode_varint(buf).context(Wire)?;
+        }
+        WireType::SixtyFourBit => {
+            take_fixed(buf, 8)?;
+        }
+        WireType::ThirtyTwoBit => {
+            take_fixed(buf, 4)?;
+        }
+        WireType::LengthDelimited => {
+            take_length_delimited(buf)?;
+        }
+        WireType::StartGroup => loop {
+            let (inner_tag, inner_wire_type) = decode_key(buf).context(Wire)?;
+            if inner_wire_type == WireType::EndGroup {
+                if inner_tag != tag {
+                    return Err(DecodeError::UnexpectedEndGroup { tag: inner_tag });
+                }
+                break;
+            }
+            skip_field(inner_wire_type, inner_tag, buf)?;
+        },
+        WireType::EndGroup => return Err(DecodeError::UnexpectedEndGroup { tag }),
+    }
+    Ok(())
+}
+
+fn unexpected_wire_type(field: &FieldDescriptorProto, wire_type: WireType) -> DecodeError {
+    DecodeError::UnexpectedWireType {
+        field: field.name().to_owned(),
+        wire_type,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prost_types::{FileDescriptorProto, MessageOptions};
+
+    fn field(name: &str, number: i32, field_type: Type, label: Label) -> FieldDescriptorProto {
+        FieldDescriptorProto {
+            name: Some(name.to_owned()),
+            number: Some(number),
+            label: Some(label as i32),
+            r#type: Some(field_type as i32),
+            ..Default::default()
+        }
+    }
+
+    fn message_field(
+        name: &str,
+        number: i32,
+        type_name: &str,
+        label: Label,
+    ) -> FieldDescriptorProto {
+        FieldDescriptorProto {
+            type_name: Some(type_name.to_owned()),
+            ..field(name, number, Type::Message, label)
+        }
+    }
+
+    fn decoder() -> ProtobufDecoder {
+        let descriptor_set = FileDescriptorSet {
+            file: vec![FileDescriptorProto {
+                name: Some("test.proto".to_owned()),
+                package: Some("test".to_owned()),
+                message_type: vec![DescriptorProto {
+                    name: Some("Event".to_owned()),
+                    field: vec![
+                        field("message", 1, Type::String, Label::Optional),
+                        field("count", 2, Type::Sint64, Label::Optional),
+                        field("values", 3, Type::Int32, Label::Repeated),
+                        message_field("inner", 4, ".test.Event.Inner", Label::Optional),
+                        message_field("labels", 5, ".test.Event.LabelsEntry", Label::Repeated),
+                    ],
+                    nested_type: vec![
+                        DescriptorProto {
+                            name: Some("Inner".to_owned()),
+                            field: vec![field("ratio", 1, Type::Double, Label::Optional)],
+                            ..Default::default()
+                        },
+                        DescriptorProto {
+                            name: Some("LabelsEntry".to_owned()),
+                            field: vec![
+                                field("key", 1, Type::String, Label::Optional),
+                                field("value", 2, Type::String, Label::Optional),
+                            ],
+                            options: Some(MessageOptions {
+                                map_entry: Some(true),
+                                ..Default::default()
+                            }),
+                            ..Default::default()
+                        },
+                    ],
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }],
+        };
+        ProtobufDecoder::new(descriptor_set, "test.Event").unwrap()
+    }
+
+    #[test]
+    fn unknown_message_type() {
+        let error = ProtobufDecoder::new(FileDescriptorSet::default(), "test.Missing").unwrap_err();
+        assert!(matches!(error, BuildError::UnknownMessageType { .. }));
+    }
+
+    #[test]
+    fn decodes_message() {
+        let mut buf = vec![
+            0x0a, 0x05, b'h', b'e', b'l', b'l', b'o', // message = "hello"
+            0x10, 0x03, // count = -2 (zigzag)
+            0x1a, 0x02, 0x01, 0x02, // values = [1, 2] (packed)
+            0x18, 0x03, // values += 3 (unpacked)
+            0x22, 0x09, 0x09, // inner.ratio = 0.5
+        ];
+        buf.extend_from_slice(&0.5f64.to_le_bytes());
+        buf.extend_from_slice(&[
+            0x2a, 0x08, 0x0a, 0x02, b'e', b'n', 0x12, 0x02, b'v', b'1', // labels = {en: v1}
+            0x78, 0x01, // unknown field 15 is dropped
+        ]);
+
+        let fields = decoder().decode(&buf).unwrap();
+
+        assert_eq!(fields["message"], Value::from("hello"));
+        assert_eq!(fields["count"], Value::Integer(-2));
+        assert_eq!(fields["values"], Value::from(vec![1, 2, 3]));
+        let mut inner = BTreeMap::new();
+        inner.insert("ratio".to_owned(), Value::Float(0.5));
+        assert_eq!(fields["inner"], Value::Map(inner));
+        let mut labels = BTreeMap::new();
+        labels.insert("en".to_owned(), Value::from("v1"));
+        assert_eq!(fields["labels"], Value::Map(labels));
+        assert!(!fields.contains_key("15"));
+    }
+
+    #[test]
+    fn truncated_message() {
+        assert!(decoder().decode(&[0x0a, 0x05, b'h']).is_err());
+    }
+}
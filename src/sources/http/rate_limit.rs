@@ -0,0 +1,171 @@
+use crate::sources::util::ErrorMessage;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    net::IpAddr,
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+use warp::http::{header::RETRY_AFTER, HeaderValue, StatusCode};
+
+/// Buckets are first pruned once this many clients are tracked.
+const PRUNE_THRESHOLD: usize = 10_000;
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct RateLimitConfig {
+    /// Sustained number of requests allowed per second from each client.
+    pub requests_per_second: f64,
+    /// Number of requests a client may make in a burst. Defaults to `requests_per_second`.
+    pub burst: Option<f64>,
+}
+
+/// Per client token buckets.
+#[derive(Clone, Debug)]
+pub struct RateLimiter {
+    rate: f64,
+    burst: f64,
+    buckets: Arc<Mutex<Buckets>>,
+}
+
+#[derive(Debug)]
+struct Buckets {
+    buckets: HashMap<IpAddr, Bucket>,
+    /// Number of buckets at which they're pruned next, which doubles what's
+    /// left after pruning, so pruning takes constant time per request.
+    prune_at: usize,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl RateLimiter {
+    pub fn new(config: &RateLimitConfig) -> Result<Self, String> {
+        let burst = config.burst.unwrap_or(config.requests_per_second);
+        let valid = config.requests_per_second > 0.0 && burst >= 1.0;
+        if !valid {
+            return Err(
+                "rate_limit requires a positive requests_per_second and a burst of at least 1"
+                    .to_owned(),
+            );
+        }
+
+        Ok(Self {
+            rate: config.requests_per_second,
+            burst,
+            buckets: Arc::new(Mutex::new(Buckets {
+                buckets: HashMap::new(),
+                prune_at: PRUNE_THRESHOLD,
+            })),
+        })
+    }
+
+    pub fn check(&self, client: IpAddr) -> Result<(), ErrorMessage> {
+        self.check_at(client, Instant::now())
+    }
+
+    fn check_at(&self, client: IpAddr, now: Instant) -> Result<(), ErrorMessage> {
+        let mut state = self.buckets.lock().unwrap();
+        let Buckets { buckets, prune_at } = &mut *state;
+        if buckets.len() >= *prune_at {
+            // Buckets which would have refilled can be recreated from scratch.
+            let refill_secs = self.burst / self.rate;
+            buckets
+                .retain(|_, bucket| now.duration_since(bucket.updated).as_secs_f64() < refill_secs);
+            *prune_at = (buckets.len() * 2).max(PRUNE_THRESHOLD);
+        }
+
+        let burst = self.burst;
+        let bucket = buckets.entry(client).or_insert(Bucket {
+            tokens: burst,
+            updated: now,
+        });
+        let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.rate).min(burst);
+        bucket.updated = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            let retry_after = ((1.0 - bucket.tokens) / self.rate).ceil() as u64;
+            Err(ErrorMessage::new(
+                StatusCode::TOO_MANY_REQUESTS,
+                format!("Rate limit exceeded for {}", client),
+            )
+            .with_header(RETRY_AFTER, HeaderValue::from(retry_after.max(1))))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn limiter(requests_per_second: f64, burst: Option<f64>) -> RateLimiter {
+        RateLimiter::new(&RateLimitConfig {
+            requests_per_second,
+            burst,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn rejects_invalid_config() {
+        assert!(RateLimiter::new(&RateLimitConfig {
+            requests_per_second: 0.0,
+            burst: None,
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn limits_per_client() {
+        let limiter = limiter(1.0, Some(2.0));
+        let now = Instant::now();
+        let client = "10.0.0.1".parse().unwrap();
+
+        assert!(limiter.check_at(client, now).is_ok());
+        assert!(limiter.check_at(client, now).is_ok());
+        assert_eq!(limiter.check_at(client, now).unwrap_err().code(), 429);
+
+        // Other clients are unaffected
+        assert!(limiter.check_at("10.0.0.2".parse().unwrap(), now).is_ok());
+
+        // Tokens are refilled over time
+        assert!(limiter
+            .check_at(client, now + Duration::from_secs(1))
+            .is_ok());
+    }
+
+    #[test]
+    fn prunes_refilled_buckets() {
+        let limiter = limiter(1.0, None);
+        let now = Instant::now();
+        let client = |i: usize| IpAddr::from((i as u32).to_be_bytes());
+        let tracked = || limiter.buckets.lock().unwrap().buckets.len();
+
+        for i in 0..PRUNE_THRESHOLD {
+            assert!(limiter.check_at(client(i), now).is_ok());
+        }
+        // None have refilled yet, so none are pruned, and pruning waits for twice as many
+        let soon = now + Duration::from_millis(500);
+        assert!(limiter.check_at(client(PRUNE_THRESHOLD), soon).is_ok());
+        assert_eq!(tracked(), PRUNE_THRESHOLD + 1);
+        assert_eq!(
+            limiter.buckets.lock().unwrap().prune_at,
+            PRUNE_THRESHOLD * 2
+        );
+
+        for i in PRUNE_THRESHOLD + 1..PRUNE_THRESHOLD * 2 {
+            assert!(limiter.check_at(client(i), soon).is_ok());
+        }
+        // The first buckets have refilled by now
+        let later = now + Duration::from_secs(1);
+        assert!(limiter.check_at(client(0), later).is_ok());
+        assert_eq!(tracked(), PRUNE_THRESHOLD + 1);
+    }
+}
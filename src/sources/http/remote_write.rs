@@ -0,0 +1,197 @@
+use crate::{
+    event::{Event, Metric, MetricKind, MetricValue},
+    sources::util::ErrorMessage,
+};
+use bytes::Bytes;
+use chrono::{TimeZone, Utc};
+use prometheus_parser::proto;
+use prost::Message;
+use std::collections::BTreeMap;
+use warp::http::{HeaderMap, StatusCode};
+
+const VERSION_HEADER: &str = "x-prometheus-remote-write-version";
+
+/// Decodes a Prometheus remote write request, a snappy compressed protobuf
+/// `WriteRequest`. Each sample of each time series becomes a gauge named after
+/// the series' `__name__` label, with its other labels as tags.
+///
+/// Bodies are compressed whether or not `Content-Encoding` says so, so they are
+/// decompressed here unless that already happened. Only version 0.1 of the
+/// protocol is accepted.
+pub fn decode(
+    body: Bytes,
+    headers: &HeaderMap,
+    max_body_size: Option<usize>,
+) -> Result<Vec<Event>, ErrorMessage> {
+    if let Some(version) = headers.get(VERSION_HEADER) {
+        let version = version.to_str().unwrap_or_default();
+        if version != "0.1" && !version.starts_with("0.1.") {
+            return Err(bad_request(format!("Unsupported version {:?}", version)));
+        }
+    }
+    let decompressed = headers
+        .get("content-encoding")
+        .map_or(false, |encoding| encoding == "snappy");
+    let body = if decompressed {
+        body
+    } else {
+        decompress(&body, max_body_size)?
+    };
+    let request = proto::WriteRequest::decode(body)
+        .map_err(|error| bad_request(format!("Invalid write request: {}", error)))?;
+
+    let mut events = Vec::new();
+    for series in request.timeseries {
+        let mut name = None;
+        let mut tags = BTreeMap::new();
+        for label in series.labels {
+            if label.name == "__name__" {
+                name = Some(label.value);
+            } else {
+                tags.insert(label.name, label.value);
+            }
+        }
+        let name = name.ok_or_else(|| bad_request("Series without a name".to_owned()))?;
+        for sample in series.samples {
+            let timestamp = Utc
+                .timestamp_millis_opt(sample.timestamp)
+                .single()
+                .ok_or_else(|| {
+                    bad_request(format!("Timestamp {} out of range", sample.timestamp))
+                })?;
+            events.push(
+                Metric::new(
+                    name.clone(),
+                    MetricKind::Absolute,
+                    MetricValue::Gauge {
+                        value: sample.value,
+                    },
+                )
+                .with_tags(Some(tags.clone()))
+                .with_timestamp(Some(timestamp))
+                .into(),
+            );
+        }
+    }
+    Ok(events)
+}
+
+fn decompress(body: &[u8], max_body_size: Option<usize>) -> Result<Bytes, ErrorMessage> {
+    let len = snap::raw::decompress_len(body)
+        .map_err(|error| bad_request(format!("Invalid snappy body: {}", error)))?;
+    if let Some(limit) = max_body_size {
+        if len > limit {
+            return Err(ErrorMessage::new(
+                StatusCode::PAYLOAD_TOO_LARGE,
+                format!("Request body exceeds the limit of {} bytes", limit),
+            ));
+        }
+    }
+    snap::raw::Decoder::new()
+        .decompress_vec(body)
+        .map(Bytes::from)
+        .map_err(|error| bad_request(format!("Invalid snappy body: {}", error)))
+}
+
+fn bad_request(message: String) -> ErrorMessage {
+    ErrorMessage::new(
+        StatusCode::BAD_REQUEST,
+        format!("Bad remote write request: {}", message),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn label(name: &str, value: &str) -> proto::Label {
+        proto::Label {
+            name: name.to_owned(),
+            value: value.to_owned(),
+        }
+    }
+
+    fn encode(timeseries: Vec<proto::TimeSeries>) -> Bytes {
+        let request = proto::WriteRequest {
+            timeseries,
+            ..Default::default()
+        };
+        let mut body = Vec::new();
+        request.encode(&mut body).unwrap();
+        snap::raw::Encoder::new()
+            .compress_vec(&body)
+            .unwrap()
+            .into()
+    }
+
+    #[test]
+    fn decodes_series() {
+        let body = encode(vec![proto::TimeSeries {
+            labels: vec![label("__name__", "up"), label("job", "node")],
+            samples: vec![
+                proto::Sample {
+                    value: 1.0,
+                    timestamp: 1_612_325_106_000,
+                },
+                proto::Sample {
+                    value: 0.0,
+                    timestamp: 1_612_325_107_500,
+                },
+            ],
+        }]);
+        let mut headers = HeaderMap::new();
+        headers.insert(VERSION_HEADER, "0.1.0".parse().unwrap());
+
+        let events = decode(body, &headers, None).unwrap();
+
+        assert_eq!(events.len(), 2);
+        let metric = events[0].as_metric();
+        assert_eq!(metric.name(), "up");
+        assert_eq!(metric.data.value, MetricValue::Gauge { value: 1.0 });
+        assert_eq!(metric.tags().unwrap()["job"], "node");
+        assert!(!metric.tags().unwrap().contains_key("__name__"));
+        assert_eq!(
+            metric.data.timestamp,
+            Some(Utc.ymd(2021, 2, 3).and_hms(4, 5, 6))
+        );
+        assert_eq!(
+            events[1].as_metric().data.timestamp,
+            Some(Utc.ymd(2021, 2, 3).and_hms_milli(4, 5, 7, 500))
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_requests() {
+        let series = || {
+            vec![proto::TimeSeries {
+                labels: vec![label("__name__", "up")],
+                samples: vec![],
+            }]
+        };
+        let mut headers = HeaderMap::new();
+        headers.insert(VERSION_HEADER, "0.2.0".parse().unwrap());
+        assert!(decode(encode(series()), &headers, None).is_err());
+
+        let headers = HeaderMap::new();
+        assert!(decode(Bytes::from_static(b"not snappy"), &headers, None).is_err());
+        assert!(decode(encode(series()), &headers, Some(1)).is_err());
+        let unnamed = vec![proto::TimeSeries {
+            labels: vec![label("job", "node")],
+            samples: vec![],
+        }];
+        assert!(decode(encode(unnamed), &headers, None).is_err());
+        let out_of_range = vec![proto::TimeSeries {
+            labels: vec![label("__name__", "up")],
+            samples: vec![proto::Sample {
+                value: 1.0,
+                timestamp: i64::MAX,
+            }],
+        }];
+        assert_eq!(
+            decode(encode(out_of_range), &headers, None)
+                .unwrap_err()
+                .code(),
+            400
+        );
+    }
+}
@@ -5,7 +5,7 @@ use crate::{
     shutdown::ShutdownSignal,
     sources::{
         self,
-        util::{decode, ErrorMessage, HttpSource, HttpSourceAuthConfig},
+        util::{decode, ErrorMessage, HttpSource, HttpSourceAuthConfig, RequestInfo},
     },
     tls::TlsConfig,
     Event, Pipeline,
@@ -54,7 +54,14 @@ impl SourceConfig for PrometheusRemoteWriteConfig {
         out: Pipeline,
     ) -> crate::Result<sources::Source> {
         let source = RemoteWriteSource;
-        source.run(self.address, "", &self.tls, &self.auth, out, shutdown)
+        source.run(
+            self.address.into(),
+            "",
+            &self.tls,
+            &self.auth,
+            out,
+            shutdown,
+        )
     }
 
     fn output_type(&self) -> crate::config::DataType {
@@ -95,6 +102,7 @@ impl HttpSource for RemoteWriteSource {
         mut body: Bytes,
         header_map: HeaderMap,
         _query_parameters: HashMap<String, String>,
+        _request: &RequestInfo,
     ) -> Result<Vec<Event>, ErrorMessage> {
         // If `Content-Encoding` header isn't `snappy` HttpSource won't decode it for us
         // se we need to.
@@ -103,7 +111,7 @@ impl HttpSource for RemoteWriteSource {
             .map(|header| header.as_ref())
             != Some(b"snappy")
         {
-            body = decode(&Some("snappy".to_string()), body)?;
+            body = decode(&Some("snappy".to_string()), body, None)?;
         }
         let result = self.decode_body(body)?;
         let count = result.len();
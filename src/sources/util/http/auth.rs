@@ -0,0 +1,1022 @@
+use super::ErrorMessage;
+use crate::event::Value;
+use bytes::Bytes;
+use chrono::Utc;
+use derivative::Derivative;
+use headers::{Authorization, HeaderMapExt};
+use openssl::{
+    hash::MessageDigest,
+    memcmp,
+    pkey::{PKey, Public},
+    sha::sha256,
+    sign::{Signer, Verifier},
+};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value as JsonValue;
+use std::{collections::HashMap, convert::TryFrom, path::PathBuf, sync::Arc};
+use warp::http::{header::AUTHORIZATION, HeaderMap, StatusCode};
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(remote = "Self", tag = "strategy", rename_all = "snake_case")]
+pub enum HttpSourceAuthConfig {
+    Basic(BasicAuthConfig),
+    Jwt(JwtAuthConfig),
+    Hmac(HmacAuthConfig),
+    Github { secret: String },
+    Stripe(StripeAuthConfig),
+    ApiKey(ApiKeyAuthConfig),
+}
+
+impl HttpSourceAuthConfig {
+    #[cfg(test)]
+    pub fn basic(username: &str, password: &str) -> Self {
+        HttpSourceAuthConfig::Basic(BasicAuthConfig {
+            username: Some(username.to_owned()),
+            password: Some(password.to_owned()),
+            ..BasicAuthConfig::default()
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for HttpSourceAuthConfig {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        // Only basic authentication was supported before `strategy` was introduced,
+        // so it remains the default.
+        let mut value = JsonValue::deserialize(deserializer)?;
+        if let JsonValue::Object(map) = &mut value {
+            map.entry("strategy").or_insert_with(|| "basic".into());
+        }
+        HttpSourceAuthConfig::deserialize(value).map_err(de::Error::custom)
+    }
+}
+
+impl Serialize for HttpSourceAuthConfig {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        HttpSourceAuthConfig::serialize(self, serializer)
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+pub struct BasicAuthConfig {
+    pub username: Option<String>,
+    pub password: Option<String>,
+    /// Additional accepted credentials.
+    #[serde(default)]
+    pub credentials: Vec<BasicAuthCredential>,
+    /// Field the matched credential's name is inserted into.
+    pub name_key: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct BasicAuthCredential {
+    pub username: String,
+    pub password: String,
+    pub name: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct JwtAuthConfig {
+    /// Shared secret for `HS256` signed tokens.
+    pub secret: Option<String>,
+    /// PEM encoded public key for `RS256` signed tokens.
+    pub public_key_file: Option<PathBuf>,
+    pub audience: Option<String>,
+    pub issuer: Option<String>,
+    /// Claims added to each event as fields.
+    #[serde(default)]
+    pub claims: Vec<String>,
+    #[serde(default)]
+    pub leeway_secs: i64,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct HmacAuthConfig {
+    /// Header carrying the hex encoded signature of the request body.
+    pub signature_header: String,
+    pub secret: String,
+    #[serde(default)]
+    pub algorithm: HmacAlgorithm,
+    /// Prefix preceding the signature in the header, e.g. `sha256=`.
+    pub prefix: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Eq, PartialEq, Clone, Copy, Derivative)]
+#[serde(rename_all = "snake_case")]
+#[derivative(Default)]
+pub enum HmacAlgorithm {
+    Sha1,
+    #[derivative(Default)]
+    Sha256,
+    Sha512,
+}
+
+impl HmacAlgorithm {
+    fn digest(self) -> MessageDigest {
+        match self {
+            HmacAlgorithm::Sha1 => MessageDigest::sha1(),
+            HmacAlgorithm::Sha256 => MessageDigest::sha256(),
+            HmacAlgorithm::Sha512 => MessageDigest::sha512(),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct StripeAuthConfig {
+    /// The endpoint's signing secret.
+    pub secret: String,
+    /// Maximum age of the signed timestamp, guarding against replays.
+    #[serde(default = "default_stripe_tolerance_secs")]
+    pub tolerance_secs: i64,
+}
+
+const fn default_stripe_tolerance_secs() -> i64 {
+    300
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct ApiKeyAuthConfig {
+    /// Header carrying the API key.
+    pub api_key_header: Option<String>,
+    /// Prefix preceding the key in the header, e.g. `Splunk `.
+    pub api_key_prefix: Option<String>,
+    /// Query parameter carrying the API key.
+    pub api_key_query: Option<String>,
+    pub keys: Vec<ApiKey>,
+    /// Field the matched key's alias is inserted into.
+    pub alias_key: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct ApiKey {
+    pub key: String,
+    pub alias: Option<String>,
+}
+
+/// GitHub webhook headers surfaced as event fields, keyed by header name.
+const GITHUB_HEADERS: [(&str, &str); 2] = [
+    ("X-GitHub-Event", "github_event"),
+    ("X-GitHub-Delivery", "github_delivery"),
+];
+
+/// A built `HttpSourceAuthConfig`, checking request credentials.
+#[derive(Clone)]
+pub enum HttpSourceAuth {
+    None,
+    Basic {
+        /// Expected `Authorization` header values and the names of their credentials.
+        tokens: Vec<(String, Option<String>)>,
+        name_key: Option<String>,
+    },
+    Jwt(Arc<JwtAuth>),
+    Hmac(HmacAuthConfig),
+    Github(HmacAuthConfig),
+    Stripe(StripeAuthConfig),
+    ApiKey(ApiKeyAuthConfig),
+}
+
+impl TryFrom<Option<&HttpSourceAuthConfig>> for HttpSourceAuth {
+    type Error = String;
+
+    fn try_from(auth: Option<&HttpSourceAuthConfig>) -> Result<Self, Self::Error> {
+        match auth {
+            Some(HttpSourceAuthConfig::Basic(config)) => {
+                let single = match (&config.username, &config.password) {
+                    (Some(username), Some(password)) => Some((username, password, None)),
+                    (None, None) => None,
+                    _ => return Err("Both username and password are required".to_owned()),
+                };
+                let tokens = single
+                    .into_iter()
+                    .chain(config.credentials.iter().map(|credential| {
+                        (
+                            &credential.username,
+                            &credential.password,
+                            credential.name.as_ref(),
+                        )
+                    }))
+                    .map(|(username, password, name)| {
+                        basic_token(username, password).map(|token| (token, name.cloned()))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                if tokens.is_empty() {
+                    return Err("At least one username and password is required".to_owned());
+                }
+
+                Ok(HttpSourceAuth::Basic {
+                    tokens,
+                    name_key: config.name_key.clone(),
+                })
+            }
+            Some(HttpSourceAuthConfig::Jwt(config)) => {
+                Ok(HttpSourceAuth::Jwt(Arc::new(JwtAuth::try_from(config)?)))
+            }
+            Some(HttpSourceAuthConfig::Hmac(config)) => Ok(HttpSourceAuth::Hmac(config.clone())),
+            Some(HttpSourceAuthConfig::Github { secret }) => {
+                Ok(HttpSourceAuth::Github(HmacAuthConfig {
+                    signature_header: "X-Hub-Signature-256".to_owned(),
+                    secret: secret.clone(),
+                    algorithm: HmacAlgorithm::Sha256,
+                    prefix: Some("sha256=".to_owned()),
+                }))
+            }
+            Some(HttpSourceAuthConfig::Stripe(config)) => {
+                Ok(HttpSourceAuth::Stripe(config.clone()))
+            }
+            Some(HttpSourceAuthConfig::ApiKey(config)) => {
+                if config.api_key_header.is_none() && config.api_key_query.is_none() {
+                    return Err("One of api_key_header or api_key_query is required".to_owned());
+                }
+                if config.keys.is_empty() {
+                    return Err("At least one API key is required".to_owned());
+                }
+                if config.keys.iter().any(|api_key| api_key.key.is_empty()) {
+                    return Err("API keys can't be empty".to_owned());
+                }
+                Ok(HttpSourceAuth::ApiKey(config.clone()))
+            }
+            None => Ok(HttpSourceAuth::None),
+        }
+    }
+}
+
+impl HttpSourceAuth {
+    /// Whether checking the credentials needs the request body, as signatures do.
+    pub fn needs_body(&self) -> bool {
+        matches!(
+            self,
+            HttpSourceAuth::Hmac(_) | HttpSourceAuth::Github(_) | HttpSourceAuth::Stripe(_)
+        )
+    }
+
+    /// Checks the request credentials, returning the fields describing the
+    /// authenticated client that should be added to each event.
+    ///
+    /// `body` is the request body as received, before any decompression.
+    pub fn is_valid(
+        &self,
+        headers: &HeaderMap,
+        query_parameters: &HashMap<String, String>,
+        body: &Bytes,
+    ) -> Result<Vec<(String, Value)>, ErrorMessage> {
+        match self {
+            HttpSourceAuth::None => Ok(Vec::new()),
+            HttpSourceAuth::Basic { tokens, name_key } => {
+                let header = headers
+                    .get(AUTHORIZATION)
+                    .ok_or_else(|| unauthorized("No authorization header"))?;
+                // Every credential is compared so the time taken doesn't reveal which one matched.
+                let (_, name) = tokens
+                    .iter()
+                    .filter(|(token, _)| constant_time_eq(token.as_bytes(), header.as_bytes()))
+                    .last()
+                    .ok_or_else(|| unauthorized("Invalid username/password"))?;
+
+                Ok(match (name_key, name) {
+                    (Some(name_key), Some(name)) => {
+                        vec![(name_key.clone(), Value::from(name.as_str()))]
+                    }
+                    _ => Vec::new(),
+                })
+            }
+            HttpSourceAuth::Jwt(jwt) => jwt.verify(headers),
+            HttpSourceAuth::Hmac(config) => verify_hmac(config, headers, body).map(|()| Vec::new()),
+            HttpSourceAuth::Github(config) => {
+                verify_hmac(config, headers, body)?;
+                Ok(GITHUB_HEADERS
+                    .iter()
+                    .filter_map(|(header, field)| {
+                        let value = headers.get(*header)?;
+                        Some((
+                            (*field).to_owned(),
+                            Value::from(Bytes::copy_from_slice(value.as_bytes())),
+                        ))
+                    })
+                    .collect())
+            }
+            HttpSourceAuth::Stripe(config) => {
+                verify_stripe(config, headers, body).map(|()| Vec::new())
+            }
+            HttpSourceAuth::ApiKey(config) => verify_api_key(config, headers, query_parameters),
+        }
+    }
+}
+
+enum JwtKey {
+    Hmac(Vec<u8>),
+    Rsa(PKey<Public>),
+}
+
+pub struct JwtAuth {
+    key: JwtKey,
+    audience: Option<String>,
+    issuer: Option<String>,
+    claims: Vec<String>,
+    leeway_secs: i64,
+}
+
+impl TryFrom<&JwtAuthConfig> for JwtAuth {
+    type Error = String;
+
+    fn try_from(config: &JwtAuthConfig) -> Result<Self, Self::Error> {
+        let key = match (&config.secret, &config.public_key_file) {
+            (Some(secret), None) => JwtKey::Hmac(secret.as_bytes().to_vec()),
+            (None, Some(path)) => {
+                let pem = std::fs::read(path)
+                    .map_err(|error| format!("Could not read {:?}: {}", path, error))?;
+                let key = PKey::public_key_from_pem(&pem)
+                    .map_err(|error| format!("Invalid public key {:?}: {}", path, error))?;
+                JwtKey::Rsa(key)
+            }
+            _ => return Err("Exactly one of secret or public_key_file is required".to_owned()),
+        };
+
+        Ok(JwtAuth {
+            key,
+            audience: config.audience.clone(),
+            issuer: config.issuer.clone(),
+            claims: config.claims.clone(),
+            leeway_secs: config.leeway_secs,
+        })
+    }
+}
+
+impl JwtAuth {
+    fn verify(&self, headers: &HeaderMap) -> Result<Vec<(String, Value)>, ErrorMessage> {
+        let token = headers
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| {
+                let (scheme, token) = value.split_at(value.find(' ')?);
+                if scheme.eq_ignore_ascii_case("bearer") {
+                    Some(token.trim())
+                } else {
+                    None
+                }
+            })
+            .ok_or_else(|| unauthorized("No bearer token"))?;
+
+        let segments = token.split('.').collect::<Vec<_>>();
+        let (header, payload, signature) = match segments.as_slice() {
+            [header, payload, signature] => (*header, *payload, *signature),
+            _ => return Err(unauthorized("Malformed token")),
+        };
+        let signing_input = &token[..header.len() + 1 + payload.len()];
+        let signature = decode_segment(signature)?;
+
+        let algorithm = serde_json::from_slice::<JsonValue>(&decode_segment(header)?)
+            .ok()
+            .and_then(|header| header.get("alg")?.as_str().map(str::to_owned))
+            .ok_or_else(|| unauthorized("Malformed token header"))?;
+        let verified = match (&self.key, algorithm.as_str()) {
+            (JwtKey::Hmac(secret), "HS256") => hmac_sha256(secret, signing_input.as_bytes())
+                .map(|expected| constant_time_eq(&expected, &signature)),
+            (JwtKey::Rsa(key), "RS256") => {
+                Verifier::new(MessageDigest::sha256(), key).and_then(|mut verifier| {
+                    verifier.update(signing_input.as_bytes())?;
+                    verifier.verify(&signature)
+                })
+            }
+            _ => return Err(unauthorized("Unsupported token algorithm")),
+        };
+        if !verified.unwrap_or(false) {
+            return Err(unauthorized("Invalid token signature"));
+        }
+
+        let claims = match serde_json::from_slice::<JsonValue>(&decode_segment(payload)?) {
+            Ok(JsonValue::Object(claims)) => claims,
+            _ => return Err(unauthorized("Malformed token claims")),
+        };
+
+        let now = Utc::now().timestamp();
+        let time_claim = |name| {
+            claims
+                .get(name)
+                .and_then(JsonValue::as_f64)
+                .map(|t| t as i64)
+        };
+        if let Some(exp) = time_claim("exp") {
+            if now > exp + self.leeway_secs {
+                return Err(unauthorized("Token has expired"));
+            }
+        }
+        if let Some(nbf) = time_claim("nbf") {
+            if now + self.leeway_secs < nbf {
+                return Err(unauthorized("Token is not valid yet"));
+            }
+        }
+        if let Some(audience) = &self.audience {
+            let matches = match claims.get("aud") {
+                Some(JsonValue::String(aud)) => aud == audience,
+                Some(JsonValue::Array(auds)) => auds.iter().any(|aud| aud == audience.as_str()),
+                _ => false,
+            };
+            if !matches {
+                return Err(unauthorized("Invalid token audience"));
+            }
+        }
+        if let Some(issuer) = &self.issuer {
+            if claims.get("iss").and_then(JsonValue::as_str) != Some(issuer.as_str()) {
+                return Err(unauthorized("Invalid token issuer"));
+            }
+        }
+
+        Ok(self
+            .claims
+            .iter()
+            .filter_map(|name| {
+                claims
+                    .get(name)
+                    .map(|claim| (name.clone(), Value::from(claim.clone())))
+            })
+            .collect())
+    }
+}
+
+fn basic_token(username: &str, password: &str) -> Result<String, String> {
+    let mut headers = HeaderMap::new();
+    headers.typed_insert(Authorization::basic(username, password));
+    match headers.get("authorization") {
+        Some(value) => Ok(value
+            .to_str()
+            .map_err(|error| format!("Failed stringify HeaderValue: {:?}", error))?
+            .to_owned()),
+        None => Err("Authorization headers wasn't generated".to_owned()),
+    }
+}
+
+fn verify_hmac(
+    config: &HmacAuthConfig,
+    headers: &HeaderMap,
+    body: &Bytes,
+) -> Result<(), ErrorMessage> {
+    let signature = headers
+        .get(config.signature_header.as_str())
+        .and_then(|value| value.to_str().ok())
+        .ok_or_else(|| unauthorized("No signature header"))?
+        .trim();
+    let signature = match &config.prefix {
+        Some(prefix) => signature
+            .strip_prefix(prefix.as_str())
+            .ok_or_else(|| unauthorized("Invalid signature"))?,
+        None => signature,
+    };
+
+    let expected = hmac(config.algorithm.digest(), config.secret.as_bytes(), body)
+        .map(|digest| to_hex(&digest))
+        .map_err(|_| unauthorized("Invalid signature"))?;
+    if constant_time_eq(
+        expected.as_bytes(),
+        signature.to_ascii_lowercase().as_bytes(),
+    ) {
+        Ok(())
+    } else {
+        Err(unauthorized("Invalid signature"))
+    }
+}
+
+/// Verifies a `Stripe-Signature` header of the form `t=<timestamp>,v1=<signature>,...`.
+/// Failures are reported as 400, which is what Stripe's libraries use.
+fn verify_stripe(
+    config: &StripeAuthConfig,
+    headers: &HeaderMap,
+    body: &Bytes,
+) -> Result<(), ErrorMessage> {
+    let bad_request =
+        |message: &str| ErrorMessage::new(StatusCode::BAD_REQUEST, message.to_owned());
+
+    let header = headers
+        .get("Stripe-Signature")
+        .and_then(|value| value.to_str().ok())
+        .ok_or_else(|| bad_request("No Stripe-Signature header"))?;
+    let mut timestamp = None;
+    let mut signatures = Vec::new();
+    for item in header.split(',') {
+        let mut parts = item.trim().splitn(2, '=');
+        match (parts.next(), parts.next()) {
+            (Some("t"), Some(value)) => timestamp = value.parse::<i64>().ok(),
+            (Some("v1"), Some(value)) => signatures.push(value.to_ascii_lowercase()),
+            _ => (),
+        }
+    }
+    let timestamp =
+        timestamp.ok_or_else(|| bad_request("No timestamp in Stripe-Signature header"))?;
+    if signatures.is_empty() {
+        return Err(bad_request("No v1 signature in Stripe-Signature header"));
+    }
+
+    let mut payload = format!("{}.", timestamp).into_bytes();
+    payload.extend_from_slice(body);
+    let expected = hmac_sha256(config.secret.as_bytes(), &payload)
+        .map(|digest| to_hex(&digest))
+        .map_err(|_| bad_request("Invalid signature"))?;
+    if !signatures
+        .iter()
+        .any(|signature| constant_time_eq(expected.as_bytes(), signature.as_bytes()))
+    {
+        return Err(bad_request("Invalid signature"));
+    }
+
+    if Utc::now().timestamp() - timestamp > config.tolerance_secs {
+        return Err(bad_request("Timestamp outside the tolerance zone"));
+    }
+    Ok(())
+}
+
+fn verify_api_key(
+    config: &ApiKeyAuthConfig,
+    headers: &HeaderMap,
+    query_parameters: &HashMap<String, String>,
+) -> Result<Vec<(String, Value)>, ErrorMessage> {
+    let presented = config
+        .api_key_header
+        .as_ref()
+        .and_then(|header| headers.get(header.as_str()))
+        .map(|value| {
+            let value = value.as_bytes();
+            match &config.api_key_prefix {
+                Some(prefix) if value.starts_with(prefix.as_bytes()) => Ok(&value[prefix.len()..]),
+                Some(_) => Err(unauthorized("Missing API key prefix")),
+                None => Ok(value),
+            }
+        })
+        .transpose()?
+        .or_else(|| {
+            let name = config.api_key_query.as_ref()?;
+            query_parameters.get(name).map(|value| value.as_bytes())
+        })
+        .ok_or_else(|| unauthorized("No API key"))?;
+
+    // Every key is compared so the time taken doesn't reveal which one matched.
+    let matched = config
+        .keys
+        .iter()
+        .filter(|api_key| constant_time_eq(api_key.key.as_bytes(), presented))
+        .last()
+        .ok_or_else(|| unauthorized("Invalid API key"))?;
+
+    Ok(match (&config.alias_key, &matched.alias) {
+        (Some(alias_key), Some(alias)) => vec![(alias_key.clone(), Value::from(alias.as_str()))],
+        _ => Vec::new(),
+    })
+}
+
+fn decode_segment(segment: &str) -> Result<Vec<u8>, ErrorMessage> {
+    base64::decode_config(segment, base64::URL_SAFE_NO_PAD)
+        .map_err(|_| unauthorized("Malformed token"))
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Result<Vec<u8>, openssl::error::ErrorStack> {
+    hmac(MessageDigest::sha256(), key, data)
+}
+
+fn hmac(
+    digest: MessageDigest,
+    key: &[u8],
+    data: &[u8],
+) -> Result<Vec<u8>, openssl::error::ErrorStack> {
+    let key = PKey::hmac(key)?;
+    let mut signer = Signer::new(digest, &key)?;
+    signer.update(data)?;
+    signer.sign_to_vec()
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Compares digests of both sides so the time taken doesn't reveal the length
+/// of either.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    memcmp::eq(&sha256(a), &sha256(b))
+}
+
+fn unauthorized(message: &str) -> ErrorMessage {
+    ErrorMessage::new(StatusCode::UNAUTHORIZED, message.to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn jwt_auth(claims: &[&str]) -> HttpSourceAuth {
+        let config = HttpSourceAuthConfig::Jwt(JwtAuthConfig {
+            secret: Some("secret".to_owned()),
+            public_key_file: None,
+            audience: Some("vector".to_owned()),
+            issuer: None,
+            claims: claims.iter().map(|claim| claim.to_string()).collect(),
+            leeway_secs: 0,
+        });
+        HttpSourceAuth::try_from(Some(&config)).unwrap()
+    }
+
+    fn bearer(secret: &str, claims: JsonValue) -> HeaderMap {
+        let encode = |data: &[u8]| base64::encode_config(data, base64::URL_SAFE_NO_PAD);
+        let signing_input = format!(
+            "{}.{}",
+            encode(br#"{"alg":"HS256","typ":"JWT"}"#),
+            encode(claims.to_string().as_bytes())
+        );
+        let signature = hmac_sha256(secret.as_bytes(), signing_input.as_bytes()).unwrap();
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            AUTHORIZATION,
+            format!("Bearer {}.{}", signing_input, encode(&signature))
+                .parse()
+                .unwrap(),
+        );
+        headers
+    }
+
+    #[test]
+    fn deserialize_basic_without_strategy() {
+        let config: HttpSourceAuthConfig = toml::from_str(
+            r#"username = "user"
+password = "pass""#,
+        )
+        .unwrap();
+        assert!(matches!(config, HttpSourceAuthConfig::Basic(_)));
+    }
+
+    #[test]
+    fn deserialize_jwt() {
+        let config: HttpSourceAuthConfig = toml::from_str(
+            r#"strategy = "jwt"
+secret = "secret""#,
+        )
+        .unwrap();
+        assert!(matches!(config, HttpSourceAuthConfig::Jwt(_)));
+    }
+
+    #[test]
+    fn jwt_valid() {
+        let auth = jwt_auth(&["sub"]);
+        let exp = Utc::now().timestamp() + 60;
+        let headers = bearer(
+            "secret",
+            serde_json::json!({"sub": "client", "aud": "vector", "exp": exp}),
+        );
+
+        assert_eq!(
+            auth.is_valid(&headers, &HashMap::new(), &Bytes::new())
+                .unwrap(),
+            vec![("sub".to_owned(), Value::from("client"))]
+        );
+    }
+
+    #[test]
+    fn jwt_invalid_signature() {
+        let auth = jwt_auth(&[]);
+        let headers = bearer("wrong", serde_json::json!({"aud": "vector"}));
+
+        assert_eq!(
+            auth.is_valid(&headers, &HashMap::new(), &Bytes::new())
+                .unwrap_err()
+                .code,
+            401
+        );
+    }
+
+    #[test]
+    fn jwt_expired() {
+        let auth = jwt_auth(&[]);
+        let exp = Utc::now().timestamp() - 60;
+        let headers = bearer("secret", serde_json::json!({"aud": "vector", "exp": exp}));
+
+        assert_eq!(
+            auth.is_valid(&headers, &HashMap::new(), &Bytes::new())
+                .unwrap_err()
+                .code,
+            401
+        );
+    }
+
+    #[test]
+    fn jwt_wrong_audience() {
+        let auth = jwt_auth(&[]);
+        let headers = bearer("secret", serde_json::json!({"aud": "other"}));
+
+        assert_eq!(
+            auth.is_valid(&headers, &HashMap::new(), &Bytes::new())
+                .unwrap_err()
+                .code,
+            401
+        );
+    }
+
+    #[test]
+    fn jwt_missing_token() {
+        let auth = jwt_auth(&[]);
+
+        assert_eq!(
+            auth.is_valid(&HeaderMap::new(), &HashMap::new(), &Bytes::new())
+                .unwrap_err()
+                .code,
+            401
+        );
+    }
+
+    fn hmac_auth() -> HttpSourceAuth {
+        let config: HttpSourceAuthConfig = toml::from_str(
+            r#"strategy = "hmac"
+signature_header = "X-Signature"
+secret = "secret"
+prefix = "sha256=""#,
+        )
+        .unwrap();
+        HttpSourceAuth::try_from(Some(&config)).unwrap()
+    }
+
+    fn signed(secret: &str, body: &Bytes) -> HeaderMap {
+        let signature = hmac_sha256(secret.as_bytes(), body).unwrap();
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "X-Signature",
+            format!("sha256={}", to_hex(&signature)).parse().unwrap(),
+        );
+        headers
+    }
+
+    #[test]
+    fn hmac_valid() {
+        let body = Bytes::from("{\"key\":\"value\"}");
+
+        assert!(hmac_auth()
+            .is_valid(&signed("secret", &body), &HashMap::new(), &body)
+            .is_ok());
+    }
+
+    #[test]
+    fn hmac_tampered_body() {
+        let headers = signed("secret", &Bytes::from("{\"key\":\"value\"}"));
+        let body = Bytes::from("{\"key\":\"other\"}");
+
+        assert_eq!(
+            hmac_auth()
+                .is_valid(&headers, &HashMap::new(), &body)
+                .unwrap_err()
+                .code,
+            401
+        );
+    }
+
+    #[test]
+    fn hmac_missing_signature() {
+        let body = Bytes::from("{}");
+
+        assert_eq!(
+            hmac_auth()
+                .is_valid(&HeaderMap::new(), &HashMap::new(), &body)
+                .unwrap_err()
+                .code,
+            401
+        );
+    }
+
+    #[test]
+    fn github_valid() {
+        let config: HttpSourceAuthConfig = toml::from_str(
+            r#"strategy = "github"
+secret = "secret""#,
+        )
+        .unwrap();
+        let auth = HttpSourceAuth::try_from(Some(&config)).unwrap();
+
+        let body = Bytes::from("{\"zen\":\"Keep it logically awesome.\"}");
+        let signature = hmac_sha256(b"secret", &body).unwrap();
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "X-Hub-Signature-256",
+            format!("sha256={}", to_hex(&signature)).parse().unwrap(),
+        );
+        headers.insert("X-GitHub-Event", "ping".parse().unwrap());
+        headers.insert("X-GitHub-Delivery", "72d3162e".parse().unwrap());
+
+        assert_eq!(
+            auth.is_valid(&headers, &HashMap::new(), &body).unwrap(),
+            vec![
+                ("github_event".to_owned(), Value::from("ping")),
+                ("github_delivery".to_owned(), Value::from("72d3162e")),
+            ]
+        );
+
+        headers.remove("X-Hub-Signature-256");
+        assert_eq!(
+            auth.is_valid(&headers, &HashMap::new(), &body)
+                .unwrap_err()
+                .code,
+            401
+        );
+    }
+
+    fn stripe_signed(timestamp: i64, secret: &str, body: &Bytes) -> HeaderMap {
+        let mut payload = format!("{}.", timestamp).into_bytes();
+        payload.extend_from_slice(body);
+        let signature = to_hex(&hmac_sha256(secret.as_bytes(), &payload).unwrap());
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "Stripe-Signature",
+            format!("t={},v1={},v0=6ffbb59b", timestamp, signature)
+                .parse()
+                .unwrap(),
+        );
+        headers
+    }
+
+    #[test]
+    fn stripe() {
+        let config: HttpSourceAuthConfig = toml::from_str(
+            r#"strategy = "stripe"
+secret = "whsec_test""#,
+        )
+        .unwrap();
+        let auth = HttpSourceAuth::try_from(Some(&config)).unwrap();
+        let body = Bytes::from("{\"id\":\"evt_1\"}");
+        let now = Utc::now().timestamp();
+
+        assert!(auth
+            .is_valid(
+                &stripe_signed(now, "whsec_test", &body),
+                &HashMap::new(),
+                &body
+            )
+            .is_ok());
+        assert_eq!(
+            auth.is_valid(
+                &stripe_signed(now, "whsec_other", &body),
+                &HashMap::new(),
+                &body
+            )
+            .unwrap_err()
+            .code,
+            400
+        );
+        assert_eq!(
+            auth.is_valid(
+                &stripe_signed(now - 600, "whsec_test", &body),
+                &HashMap::new(),
+                &body
+            )
+            .unwrap_err()
+            .code,
+            400
+        );
+    }
+
+    #[test]
+    fn api_key() {
+        let config: HttpSourceAuthConfig = toml::from_str(
+            r#"strategy = "api_key"
+api_key_header = "X-API-Key"
+api_key_query = "api_key"
+alias_key = "client"
+keys = [{ key = "old-key" }, { key = "new-key", alias = "team-a" }]"#,
+        )
+        .unwrap();
+        let auth = HttpSourceAuth::try_from(Some(&config)).unwrap();
+        let body = Bytes::new();
+
+        let mut headers = HeaderMap::new();
+        headers.insert("X-API-Key", "new-key".parse().unwrap());
+        assert_eq!(
+            auth.is_valid(&headers, &HashMap::new(), &body).unwrap(),
+            vec![("client".to_owned(), Value::from("team-a"))]
+        );
+
+        let mut query_parameters = HashMap::new();
+        query_parameters.insert("api_key".to_owned(), "old-key".to_owned());
+        assert_eq!(
+            auth.is_valid(&HeaderMap::new(), &query_parameters, &body)
+                .unwrap(),
+            vec![]
+        );
+
+        headers.insert("X-API-Key", "wrong-key".parse().unwrap());
+        assert_eq!(
+            auth.is_valid(&headers, &HashMap::new(), &body)
+                .unwrap_err()
+                .code,
+            401
+        );
+        assert_eq!(
+            auth.is_valid(&HeaderMap::new(), &HashMap::new(), &body)
+                .unwrap_err()
+                .code,
+            401
+        );
+    }
+
+    #[test]
+    fn api_key_prefix() {
+        let config: HttpSourceAuthConfig = toml::from_str(
+            r#"strategy = "api_key"
+api_key_header = "Authorization"
+api_key_prefix = "Splunk "
+keys = [{ key = "token" }]"#,
+        )
+        .unwrap();
+        let auth = HttpSourceAuth::try_from(Some(&config)).unwrap();
+
+        for (header, code) in vec![
+            ("Splunk token", None),
+            ("token", Some(401)),
+            ("Splunk other", Some(401)),
+            ("Splunk ", Some(401)),
+            ("", Some(401)),
+        ] {
+            let mut headers = HeaderMap::new();
+            headers.insert(AUTHORIZATION, header.parse().unwrap());
+            assert_eq!(
+                auth.is_valid(&headers, &HashMap::new(), &Bytes::new())
+                    .err()
+                    .map(|error| error.code),
+                code,
+                "{}",
+                header
+            );
+        }
+    }
+
+    #[test]
+    fn api_key_rejects_empty_keys() {
+        let config: HttpSourceAuthConfig = toml::from_str(
+            r#"strategy = "api_key"
+api_key_header = "Authorization"
+api_key_prefix = "Splunk "
+keys = [{ key = "" }]"#,
+        )
+        .unwrap();
+        assert!(HttpSourceAuth::try_from(Some(&config)).is_err());
+    }
+
+    #[test]
+    fn basic() {
+        let auth =
+            HttpSourceAuth::try_from(Some(&HttpSourceAuthConfig::basic("user", "pass"))).unwrap();
+        let mut headers = HeaderMap::new();
+        headers.typed_insert(Authorization::basic("user", "pass"));
+
+        assert!(auth
+            .is_valid(&headers, &HashMap::new(), &Bytes::new())
+            .is_ok());
+        assert_eq!(
+            auth.is_valid(&HeaderMap::new(), &HashMap::new(), &Bytes::new())
+                .unwrap_err()
+                .code,
+            401
+        );
+    }
+
+    #[test]
+    fn basic_multiple_credentials() {
+        let config: HttpSourceAuthConfig = toml::from_str(
+            r#"name_key = "team"
+credentials = [
+  { username = "alice", password = "one", name = "team-a" },
+  { username = "bob", password = "two" },
+]"#,
+        )
+        .unwrap();
+        let auth = HttpSourceAuth::try_from(Some(&config)).unwrap();
+        let body = Bytes::new();
+        let authorized = |username, password| {
+            let mut headers = HeaderMap::new();
+            headers.typed_insert(Authorization::basic(username, password));
+            headers
+        };
+
+        assert_eq!(
+            auth.is_valid(&authorized("alice", "one"), &HashMap::new(), &body)
+                .unwrap(),
+            vec![("team".to_owned(), Value::from("team-a"))]
+        );
+        assert_eq!(
+            auth.is_valid(&authorized("bob", "two"), &HashMap::new(), &body)
+                .unwrap(),
+            vec![]
+        );
+        assert_eq!(
+            auth.is_valid(&authorized("alice", "two"), &HashMap::new(), &body)
+                .unwrap_err()
+                .code,
+            401
+        );
+    }
+
+    #[test]
+    fn constant_time_eq_compares_any_lengths() {
+        assert!(constant_time_eq(b"secret", b"secret"));
+        assert!(!constant_time_eq(b"secret", b"secre"));
+        assert!(!constant_time_eq(b"secret", b"secrets"));
+        assert!(!constant_time_eq(b"secret", b"public"));
+        assert!(constant_time_eq(b"", b""));
+    }
+}
@@ -0,0 +1,62 @@
+use headers::Origin;
+use serde::{Deserialize, Serialize};
+use std::{str::FromStr, time::Duration};
+use warp::{
+    filters::cors::Builder,
+    http::{header::HeaderName, Method},
+};
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct CorsConfig {
+    /// Origins allowed to make requests, or `*` for any origin.
+    pub allowed_origins: Vec<String>,
+    #[serde(default = "default_allowed_methods")]
+    pub allowed_methods: Vec<String>,
+    #[serde(default)]
+    pub allowed_headers: Vec<String>,
+    pub max_age_secs: Option<u64>,
+}
+
+fn default_allowed_methods() -> Vec<String> {
+    vec!["POST".to_owned()]
+}
+
+impl CorsConfig {
+    /// Validates the configuration up front, as `warp` panics on invalid values.
+    pub(super) fn build(&self) -> Result<Builder, String> {
+        for method in &self.allowed_methods {
+            Method::from_bytes(method.as_bytes())
+                .map_err(|_| format!("Invalid CORS method {:?}", method))?;
+        }
+        for header in &self.allowed_headers {
+            HeaderName::from_str(header)
+                .map_err(|_| format!("Invalid CORS header {:?}", header))?;
+        }
+
+        let mut cors = warp::cors()
+            .allow_methods(self.allowed_methods.iter().map(String::as_str))
+            .allow_headers(self.allowed_headers.iter().map(String::as_str));
+        if self.allowed_origins.iter().any(|origin| origin == "*") {
+            cors = cors.allow_any_origin();
+        } else {
+            for origin in &self.allowed_origins {
+                let mut parts = origin.splitn(2, "://");
+                let valid = match (parts.next(), parts.next()) {
+                    (Some(scheme), Some(host)) => {
+                        Origin::try_from_parts(scheme, host, None).is_ok()
+                    }
+                    _ => false,
+                };
+                if !valid {
+                    return Err(format!("Invalid CORS origin {:?}", origin));
+                }
+            }
+            cors = cors.allow_origins(self.allowed_origins.iter().map(String::as_str));
+        }
+        if let Some(max_age_secs) = self.max_age_secs {
+            cors = cors.max_age(Duration::from_secs(max_age_secs));
+        }
+
+        Ok(cors)
+    }
+}
@@ -0,0 +1,135 @@
+use lru::LruCache;
+use serde::{Deserialize, Serialize};
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use warp::http::{header::HeaderName, HeaderMap};
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct IdempotencyConfig {
+    /// Header carrying the key senders reuse when retrying a request.
+    pub header: String,
+    #[serde(default = "default_max_keys")]
+    pub max_keys: usize,
+    #[serde(default = "default_ttl_secs")]
+    pub ttl_secs: u64,
+}
+
+const fn default_max_keys() -> usize {
+    10_000
+}
+
+const fn default_ttl_secs() -> u64 {
+    24 * 60 * 60
+}
+
+impl IdempotencyConfig {
+    pub(super) fn build(&self) -> Result<IdempotencyCache, String> {
+        let header = HeaderName::from_bytes(self.header.as_bytes())
+            .map_err(|_| format!("Invalid idempotency header {:?}", self.header))?;
+        if self.max_keys == 0 {
+            return Err("idempotency.max_keys must be at least 1".to_owned());
+        }
+        Ok(IdempotencyCache {
+            header,
+            ttl: Duration::from_secs(self.ttl_secs),
+            keys: Arc::new(Mutex::new(LruCache::new(self.max_keys))),
+        })
+    }
+}
+
+/// The keys of recently accepted requests, with the least recently accepted
+/// forgotten first once full.
+#[derive(Clone)]
+pub(super) struct IdempotencyCache {
+    header: HeaderName,
+    ttl: Duration,
+    keys: Arc<Mutex<LruCache<String, Instant>>>,
+}
+
+impl IdempotencyCache {
+    /// The request's idempotency key, if it has one.
+    pub(super) fn key(&self, headers: &HeaderMap) -> Option<String> {
+        headers
+            .get(&self.header)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_owned)
+    }
+
+    /// Whether a request with the key was accepted within the TTL.
+    pub(super) fn contains(&self, key: &str) -> bool {
+        self.contains_at(key, Instant::now())
+    }
+
+    fn contains_at(&self, key: &str, now: Instant) -> bool {
+        let mut keys = self.keys.lock().unwrap();
+        match keys.peek(key) {
+            Some(accepted) if now.duration_since(*accepted) < self.ttl => true,
+            Some(_) => {
+                keys.pop(key);
+                false
+            }
+            None => false,
+        }
+    }
+
+    /// Remembers that a request with the key was accepted.
+    pub(super) fn insert(&self, key: String) {
+        self.insert_at(key, Instant::now())
+    }
+
+    fn insert_at(&self, key: String, now: Instant) {
+        self.keys.lock().unwrap().put(key, now);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cache(max_keys: usize) -> IdempotencyCache {
+        IdempotencyConfig {
+            header: "Idempotency-Key".to_owned(),
+            max_keys,
+            ttl_secs: 60,
+        }
+        .build()
+        .unwrap()
+    }
+
+    #[test]
+    fn expires_keys() {
+        let cache = cache(10);
+        let now = Instant::now();
+        cache.insert_at("a".to_owned(), now);
+
+        assert!(cache.contains_at("a", now + Duration::from_secs(59)));
+        assert!(!cache.contains_at("a", now + Duration::from_secs(60)));
+        assert!(!cache.contains_at("a", now));
+        assert!(!cache.contains_at("b", now));
+    }
+
+    #[test]
+    fn forgets_least_recent_keys() {
+        let cache = cache(2);
+        let now = Instant::now();
+        cache.insert_at("a".to_owned(), now);
+        cache.insert_at("b".to_owned(), now);
+        cache.insert_at("c".to_owned(), now);
+
+        assert!(!cache.contains_at("a", now));
+        assert!(cache.contains_at("b", now));
+        assert!(cache.contains_at("c", now));
+    }
+
+    #[test]
+    fn reads_key_header() {
+        let cache = cache(2);
+        let mut headers = HeaderMap::new();
+        assert_eq!(cache.key(&headers), None);
+
+        headers.insert("idempotency-key", "abc".parse().unwrap());
+        assert_eq!(cache.key(&headers).as_deref(), Some("abc"));
+    }
+}
@@ -0,0 +1,1610 @@
+mod auth;
+mod cors;
+mod idempotency;
+mod sse;
+
+pub use self::auth::{HttpSourceAuth, HttpSourceAuthConfig};
+pub use self::cors::CorsConfig;
+pub use self::idempotency::IdempotencyConfig;
+pub use self::sse::ServerSentEvent;
+use crate::{
+    event::{Event, Value},
+    internal_events::{
+        HTTPBadRequest, HTTPDecompressError, HTTPEventsReceived, HTTPRequestBodySize,
+        HTTPRequestReceived,
+    },
+    shutdown::ShutdownSignal,
+    tls::{MaybeTlsIncomingStream, MaybeTlsSettings, TlsConfig},
+    Pipeline,
+};
+use async_trait::async_trait;
+use brotli::Decompressor as BrotliDecoder;
+use bytes::{buf::BufExt, Buf, BufMut, Bytes, BytesMut};
+use flate2::{
+    read::{DeflateDecoder, GzDecoder},
+    write::GzEncoder,
+    Compression,
+};
+use futures::{
+    future::{self, Either},
+    stream::BoxStream,
+    FutureExt, SinkExt, Stream, StreamExt, TryFutureExt, TryStreamExt,
+};
+use hyper::{
+    service::{make_service_fn, service_fn, Service},
+    Server,
+};
+use openssl::{nid::Nid, x509::X509};
+use serde::{Deserialize, Serialize};
+use snap::{raw::Decoder as SnappyDecoder, read::FrameDecoder as SnappyFrameDecoder};
+#[cfg(unix)]
+use std::path::PathBuf;
+use std::{
+    collections::HashMap,
+    convert::{Infallible, TryFrom},
+    error::Error,
+    fmt,
+    future::Future,
+    io::{Read, Write},
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tokio::net::TcpStream;
+#[cfg(unix)]
+use tokio::net::{UnixListener, UnixStream};
+use tracing_futures::Instrument;
+use warp::{
+    filters::path::FullPath,
+    filters::BoxedFilter,
+    http::{
+        header::{
+            HeaderName, CONNECTION, CONTENT_ENCODING, CONTENT_LENGTH, CONTENT_TYPE, RETRY_AFTER,
+            VARY,
+        },
+        HeaderMap, HeaderValue, Method, StatusCode, Uri,
+    },
+    reject::Rejection,
+    reply::Response,
+    ws::{Message, WebSocket, Ws},
+    Filter, Reply,
+};
+use zstd::stream::read::Decoder as ZstdDecoder;
+
+#[cfg(any(feature = "sources-http", feature = "sources-heroku_logs"))]
+pub(crate) fn add_query_parameters(
+    mut events: Vec<Event>,
+    query_parameters_config: &[String],
+    skip_missing: bool,
+    query_parameters: HashMap<String, String>,
+) -> Vec<Event> {
+    for query_parameter_name in query_parameters_config {
+        let value = query_parameters.get(query_parameter_name);
+        if value.is_none() && skip_missing {
+            continue;
+        }
+        for event in events.iter_mut() {
+            event.as_mut_log().insert(
+                query_parameter_name as &str,
+                crate::event::Value::from(value.map(String::to_owned)),
+            );
+        }
+    }
+
+    events
+}
+
+#[derive(Serialize, Debug)]
+pub struct ErrorMessage {
+    code: u16,
+    message: String,
+    #[serde(skip)]
+    headers: HeaderMap,
+}
+impl ErrorMessage {
+    pub fn new(code: StatusCode, message: String) -> Self {
+        ErrorMessage {
+            code: code.as_u16(),
+            message,
+            headers: HeaderMap::new(),
+        }
+    }
+
+    /// Adds a header to the response the error is returned as.
+    pub fn with_header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.headers.insert(name, value);
+        self
+    }
+
+    #[cfg(test)]
+    pub fn code(&self) -> u16 {
+        self.code
+    }
+
+    fn response(&self) -> Response {
+        let mut response = warp::reply::with_status(
+            warp::reply::json(self),
+            StatusCode::from_u16(self.code).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+        )
+        .into_response();
+        response.headers_mut().extend(self.headers.clone());
+        response
+    }
+}
+impl Error for ErrorMessage {}
+impl fmt::Display for ErrorMessage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.code, self.message)
+    }
+}
+impl warp::reject::Reject for ErrorMessage {}
+
+struct RejectShuttingDown;
+impl fmt::Debug for RejectShuttingDown {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("shutting down")
+    }
+}
+impl warp::reject::Reject for RejectShuttingDown {}
+
+/// The address an HTTP source listens on: a TCP socket address, or the path of
+/// a Unix socket, which must be absolute.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum HttpListenAddr {
+    SocketAddr(SocketAddr),
+    #[cfg(unix)]
+    #[serde(deserialize_with = "parse_unix_path")]
+    UnixPath(PathBuf),
+}
+
+impl fmt::Display for HttpListenAddr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::SocketAddr(addr) => addr.fmt(f),
+            #[cfg(unix)]
+            Self::UnixPath(path) => write!(f, "unix:{}", path.display()),
+        }
+    }
+}
+
+impl From<SocketAddr> for HttpListenAddr {
+    fn from(addr: SocketAddr) -> Self {
+        Self::SocketAddr(addr)
+    }
+}
+
+#[cfg(unix)]
+fn parse_unix_path<'de, D>(des: D) -> Result<PathBuf, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let path = PathBuf::deserialize(des)?;
+    if path.is_absolute() {
+        Ok(path)
+    } else {
+        Err(serde::de::Error::custom(
+            "must be a socket address or an absolute path",
+        ))
+    }
+}
+
+/// Details of the connection a request was received on.
+#[derive(Clone, Debug, Default)]
+pub struct ClientInfo {
+    /// Address of the peer the connection was accepted from.
+    pub remote_addr: Option<SocketAddr>,
+    /// Subject common name of the verified client certificate.
+    pub certificate_cn: Option<String>,
+    /// Subject alternative names of the verified client certificate.
+    pub certificate_sans: Vec<String>,
+    /// Server name the client asked for with TLS SNI.
+    pub server_name: Option<String>,
+}
+
+/// Details of a request besides its headers, query and body.
+#[derive(Clone, Debug)]
+pub struct RequestInfo {
+    pub method: Method,
+    pub path: String,
+    /// The query string as received, without the leading `?`.
+    pub raw_query: Option<String>,
+    pub client: ClientInfo,
+}
+
+impl ClientInfo {
+    fn new(remote_addr: SocketAddr, certificate: Option<X509>) -> Self {
+        let certificate = match certificate {
+            Some(certificate) => certificate,
+            None => {
+                return Self {
+                    remote_addr: Some(remote_addr),
+                    ..Self::default()
+                }
+            }
+        };
+
+        let certificate_cn = certificate
+            .subject_name()
+            .entries_by_nid(Nid::COMMONNAME)
+            .next()
+            .and_then(|entry| entry.data().as_utf8().ok())
+            .map(|cn| cn.to_string());
+        let certificate_sans = certificate
+            .subject_alt_names()
+            .map(|names| {
+                names
+                    .iter()
+                    .filter_map(|name| {
+                        name.dnsname()
+                            .or_else(|| name.email())
+                            .or_else(|| name.uri())
+                            .map(str::to_owned)
+                            .or_else(|| ip_address(name.ipaddress()?).map(|ip| ip.to_string()))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Self {
+            remote_addr: Some(remote_addr),
+            certificate_cn,
+            certificate_sans,
+            server_name: None,
+        }
+    }
+
+    fn with_server_name(self, server_name: Option<String>) -> Self {
+        Self {
+            server_name,
+            ..self
+        }
+    }
+}
+
+fn ip_address(bytes: &[u8]) -> Option<IpAddr> {
+    match bytes.len() {
+        4 => {
+            let mut octets = [0; 4];
+            octets.copy_from_slice(bytes);
+            Some(Ipv4Addr::from(octets).into())
+        }
+        16 => {
+            let mut octets = [0; 16];
+            octets.copy_from_slice(bytes);
+            Some(Ipv6Addr::from(octets).into())
+        }
+        _ => None,
+    }
+}
+
+const SNAPPY_STREAM_IDENTIFIER: &[u8] = b"\xff\x06\x00\x00sNaPpY";
+
+/// Decompresses `body` according to the `Content-Encoding` header, rejecting
+/// it with 413 if it would expand past `limit` bytes.
+pub fn decode(
+    header: &Option<String>,
+    mut body: Bytes,
+    limit: Option<usize>,
+) -> Result<Bytes, ErrorMessage> {
+    if let Some(encodings) = header {
+        for encoding in encodings.rsplit(',').map(str::trim) {
+            body = match encoding {
+                "identity" => body,
+                "gzip" => read_limited(encoding, GzDecoder::new(body.reader()), limit)?,
+                "deflate" => read_limited(encoding, DeflateDecoder::new(body.reader()), limit)?,
+                "br" => read_limited(encoding, BrotliDecoder::new(body.reader(), 4096), limit)?,
+                "zstd" => {
+                    let decoder = ZstdDecoder::new(body.reader())
+                        .map_err(|error| handle_decode_error(encoding, error))?;
+                    read_limited(encoding, decoder, limit)?
+                }
+                // The framing format starts with a stream identifier chunk,
+                // otherwise the body is a single raw block
+                "snappy" if body.starts_with(SNAPPY_STREAM_IDENTIFIER) => {
+                    read_limited(encoding, SnappyFrameDecoder::new(body.reader()), limit)?
+                }
+                "snappy" => {
+                    let len = snap::raw::decompress_len(&body)
+                        .map_err(|error| handle_decode_error(encoding, error))?;
+                    check_body_size(len, limit)?;
+                    SnappyDecoder::new()
+                        .decompress_vec(&body)
+                        .map_err(|error| handle_decode_error(encoding, error))?
+                        .into()
+                }
+                encoding => {
+                    return Err(ErrorMessage::new(
+                        StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                        format!("Unsupported encoding {}", encoding),
+                    ))
+                }
+            }
+        }
+    }
+
+    Ok(body)
+}
+
+fn read_limited(
+    encoding: &str,
+    mut reader: impl Read,
+    limit: Option<usize>,
+) -> Result<Bytes, ErrorMessage> {
+    let mut decoded = Vec::new();
+    match limit {
+        // Reading one byte past the limit is enough to tell it was exceeded.
+        Some(limit) => reader.take(limit as u64 + 1).read_to_end(&mut decoded),
+        None => reader.read_to_end(&mut decoded),
+    }
+    .map_err(|error| handle_decode_error(encoding, error))?;
+    check_body_size(decoded.len(), limit)?;
+    Ok(decoded.into())
+}
+
+fn check_body_size(size: usize, limit: Option<usize>) -> Result<(), ErrorMessage> {
+    match limit {
+        Some(limit) if size > limit => Err(ErrorMessage::new(
+            StatusCode::PAYLOAD_TOO_LARGE,
+            format!("Request body exceeds the limit of {} bytes", limit),
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Reads the request body, stopping with 413 as soon as it exceeds `limit` bytes.
+async fn read_body(
+    body: impl Stream<Item = Result<impl Buf, warp::Error>>,
+    limit: Option<usize>,
+) -> Result<Bytes, Rejection> {
+    let mut body = Box::pin(body);
+    let mut buffer = BytesMut::new();
+    while let Some(chunk) = body.next().await {
+        let chunk = chunk.map_err(|error| {
+            reject(ErrorMessage::new(
+                StatusCode::BAD_REQUEST,
+                format!("Failed reading request body: {}", error),
+            ))
+        })?;
+        check_body_size(buffer.len() + chunk.remaining(), limit).map_err(reject)?;
+        buffer.put(chunk);
+    }
+    Ok(buffer.freeze())
+}
+
+/// Reads a body of `delimiter` separated lines, sending the events of each
+/// line to `out` as soon as it has been received, so that no more than a line
+/// of the body is held in memory. Lines are only decoded when `auth_fields` are
+/// given, which are added to their events. Otherwise, or after a line fails to
+/// decode, the rest of the body is still read so that responses are the same as
+/// when the body is read whole. Returns the number of events sent, or the first
+/// decoding error, along with the size of the body.
+#[allow(clippy::too_many_arguments)]
+async fn read_lines<S: HttpSource>(
+    source: &S,
+    mut body: BoxStream<'static, Result<Bytes, warp::Error>>,
+    delimiter: u8,
+    limit: Option<usize>,
+    auth_fields: Option<&[(String, Value)]>,
+    headers: &HeaderMap,
+    query_parameters: &HashMap<String, String>,
+    request: &RequestInfo,
+    out: &mut Pipeline,
+) -> Result<(Result<usize, ErrorMessage>, usize), Rejection> {
+    let reject_when_full = source.reject_when_full();
+    let decode = |line: Bytes, auth_fields: &[(String, Value)]| {
+        source
+            .build_event(line, headers.clone(), query_parameters.clone(), request)
+            .map(|events| with_auth_fields(events, auth_fields))
+    };
+    let mut sent = Ok(0);
+    let mut buffer = BytesMut::new();
+    let mut size = 0;
+    while let Some(chunk) = body.next().await {
+        let chunk = chunk.map_err(|error| {
+            reject(ErrorMessage::new(
+                StatusCode::BAD_REQUEST,
+                format!("Failed reading request body: {}", error),
+            ))
+        })?;
+        size += chunk.len();
+        check_body_size(size, limit).map_err(reject)?;
+        let auth_fields = match auth_fields {
+            Some(auth_fields) if sent.is_ok() => auth_fields,
+            _ => continue,
+        };
+
+        // Only the newly received bytes can end the line being read
+        let mut searched = buffer.len();
+        buffer.extend_from_slice(&chunk);
+        while let Some(position) = buffer[searched..].iter().position(|b| *b == delimiter) {
+            let mut line = buffer.split_to(searched + position + 1);
+            line.truncate(line.len() - 1);
+            searched = 0;
+            if !line.is_empty() {
+                send_line(
+                    out,
+                    reject_when_full,
+                    &mut sent,
+                    decode(line.freeze(), auth_fields),
+                )
+                .await?;
+                if sent.is_err() {
+                    buffer = BytesMut::new();
+                    break;
+                }
+            }
+        }
+    }
+
+    if let (Some(auth_fields), Ok(_)) = (auth_fields, &sent) {
+        if size == 0 {
+            // Empty bodies are decoded as such, like beacons
+            send_line(
+                out,
+                reject_when_full,
+                &mut sent,
+                decode(Bytes::new(), auth_fields),
+            )
+            .await?;
+        } else if !buffer.is_empty() {
+            send_line(
+                out,
+                reject_when_full,
+                &mut sent,
+                decode(buffer.freeze(), auth_fields),
+            )
+            .await?;
+        }
+    }
+    Ok((sent, size))
+}
+
+/// Sends the events decoded from a line, counting them, or keeps the first
+/// decoding error. Fails with a 503 response when `reject_when_full` is set and
+/// the pipeline has no room left.
+async fn send_line(
+    out: &mut Pipeline,
+    reject_when_full: bool,
+    sent: &mut Result<usize, ErrorMessage>,
+    line_events: Result<Vec<Event>, ErrorMessage>,
+) -> Result<(), Rejection> {
+    match line_events {
+        Ok(events) => {
+            let count = events.len();
+            if !forward_events(out, events, reject_when_full).await? {
+                return Err(reject(pipeline_full()));
+            }
+            if let Ok(sent) = sent {
+                *sent += count;
+            }
+        }
+        Err(error) => *sent = Err(error),
+    }
+    Ok(())
+}
+
+/// Sends events to the pipeline, waiting for room unless `reject_when_full` is
+/// set, in which case sending stops as soon as there's none. Returns whether
+/// every event was sent, failing only once the pipeline has been closed.
+async fn forward_events(
+    out: &mut Pipeline,
+    events: Vec<Event>,
+    reject_when_full: bool,
+) -> Result<bool, Rejection> {
+    let sent = if reject_when_full {
+        out.try_send_all(events).await
+    } else {
+        out.send_all(&mut futures::stream::iter(events).map(Ok))
+            .await
+            .map(|()| true)
+    };
+    sent.map_err(|error: crate::pipeline::ClosedError| {
+        // can only fail if receiving end disconnected, so we are shutting down,
+        // probably not gracefully.
+        error!(message = "Failed to forward events, downstream is closed.");
+        error!(message = "Tried to send the following event.", %error);
+        warp::reject::custom(RejectShuttingDown)
+    })
+}
+
+/// The response to requests whose events don't fit in the pipeline, asking
+/// clients to retry shortly.
+fn pipeline_full() -> ErrorMessage {
+    ErrorMessage::new(
+        StatusCode::SERVICE_UNAVAILABLE,
+        "Pipeline is full".to_owned(),
+    )
+    .with_header(RETRY_AFTER, HeaderValue::from(1))
+}
+
+/// Adds the fields taken from a request's credentials to its log events.
+fn with_auth_fields(mut events: Vec<Event>, auth_fields: &[(String, Value)]) -> Vec<Event> {
+    for event in events.iter_mut() {
+        if let Event::Log(log) = event {
+            for (key, value) in auth_fields.iter() {
+                log.insert(key.as_str(), value.clone());
+            }
+        }
+    }
+    events
+}
+
+/// Decodes each message received on a WebSocket connection as a request body,
+/// until the client closes the connection or Vector shuts down. Messages that
+/// can't be decoded are answered with the error, like a rejected request.
+#[allow(clippy::too_many_arguments)]
+async fn handle_websocket<S: HttpSource>(
+    source: S,
+    socket: WebSocket,
+    headers: HeaderMap,
+    query_parameters: HashMap<String, String>,
+    request: RequestInfo,
+    auth_fields: Vec<(String, Value)>,
+    mut out: Pipeline,
+    shutdown: ShutdownSignal,
+) {
+    let (mut sender, receiver) = socket.split();
+    let mut receiver = receiver.take_until(shutdown);
+    while let Some(message) = receiver.next().await {
+        let message = match message {
+            Ok(message) if message.is_close() => break,
+            Ok(message) if message.is_text() || message.is_binary() => message,
+            Ok(_) => continue,
+            Err(error) => {
+                debug!(message = "WebSocket connection failed.", %error);
+                break;
+            }
+        };
+
+        let body = Bytes::copy_from_slice(message.as_bytes());
+        let body_size = body.len();
+        match source.build_event(body, headers.clone(), query_parameters.clone(), &request) {
+            Ok(mut events) => {
+                for event in events.iter_mut() {
+                    if let Event::Log(log) = event {
+                        for (key, value) in auth_fields.iter() {
+                            log.insert(key.as_str(), value.clone());
+                        }
+                    }
+                }
+                emit!(HTTPEventsReceived {
+                    events_count: events.len(),
+                    byte_size: body_size,
+                });
+                match forward_events(&mut out, events, source.reject_when_full()).await {
+                    Ok(true) => (),
+                    Ok(false) => {
+                        let reply = serde_json::to_string(&pipeline_full())
+                            .expect("ErrorMessage serializes");
+                        if sender.send(Message::text(reply)).await.is_err() {
+                            break;
+                        }
+                    }
+                    Err(_) => break,
+                }
+            }
+            Err(error) => {
+                emit!(HTTPBadRequest {
+                    error_code: error.code,
+                    error_message: error.message.as_str(),
+                });
+                let reply = serde_json::to_string(&error).expect("ErrorMessage serializes");
+                if sender.send(Message::text(reply)).await.is_err() {
+                    break;
+                }
+            }
+        }
+    }
+    let _ = sender.send(Message::close()).await;
+}
+
+fn boxed_body(
+    body: impl Stream<Item = Result<impl Buf, warp::Error>> + Send + 'static,
+) -> BoxStream<'static, Result<Bytes, warp::Error>> {
+    body.map_ok(|mut chunk| chunk.to_bytes()).boxed()
+}
+
+/// Passes each server-sent event of the body to the source as soon as it has
+/// been received. Events that can't be decoded are skipped, so one bad event
+/// doesn't end a long-lived stream.
+#[allow(clippy::too_many_arguments)]
+async fn read_server_sent_events<S: HttpSource>(
+    source: &S,
+    mut body: BoxStream<'static, Result<Bytes, warp::Error>>,
+    headers: HeaderMap,
+    query_parameters: HashMap<String, String>,
+    request: &RequestInfo,
+    auth_fields: &[(String, Value)],
+    limit: Option<usize>,
+    out: &mut Pipeline,
+) -> Result<(), ErrorMessage> {
+    let mut buffer = BytesMut::new();
+    loop {
+        while let Some(event) = sse::next_event(&mut buffer) {
+            let body_size = event.data.len();
+            let mut events = match source.build_server_sent_event(
+                event,
+                headers.clone(),
+                query_parameters.clone(),
+                request,
+            ) {
+                Ok(events) => events,
+                Err(error) => {
+                    emit!(HTTPBadRequest {
+                        error_code: error.code,
+                        error_message: error.message.as_str(),
+                    });
+                    continue;
+                }
+            };
+            for event in events.iter_mut() {
+                if let Event::Log(log) = event {
+                    for (key, value) in auth_fields.iter() {
+                        log.insert(key.as_str(), value.clone());
+                    }
+                }
+            }
+            emit!(HTTPEventsReceived {
+                events_count: events.len(),
+                byte_size: body_size,
+            });
+            let sent = forward_events(out, events, source.reject_when_full())
+                .await
+                .map_err(|_| {
+                    ErrorMessage::new(StatusCode::SERVICE_UNAVAILABLE, "Shutting down".to_owned())
+                })?;
+            if !sent {
+                return Err(pipeline_full());
+            }
+        }
+
+        // Only a single event, rather than the whole body, is limited in size
+        match body.next().await {
+            Some(chunk) => {
+                let chunk = chunk.map_err(|error| {
+                    ErrorMessage::new(
+                        StatusCode::BAD_REQUEST,
+                        format!("Failed reading request body: {}", error),
+                    )
+                })?;
+                check_body_size(buffer.len() + chunk.len(), limit)?;
+                buffer.extend_from_slice(&chunk);
+            }
+            // An event cut short by the end of the body is dropped
+            None => return Ok(()),
+        }
+    }
+}
+
+fn reject(error: ErrorMessage) -> Rejection {
+    emit!(HTTPBadRequest {
+        error_code: error.code,
+        error_message: error.message.as_str(),
+    });
+    warp::reject::custom(error)
+}
+
+fn handle_decode_error(encoding: &str, error: impl std::error::Error) -> ErrorMessage {
+    emit!(HTTPDecompressError {
+        encoding,
+        error: &error
+    });
+    ErrorMessage::new(
+        StatusCode::UNPROCESSABLE_ENTITY,
+        format!("Failed decompressing payload with {} decoder.", encoding),
+    )
+}
+
+#[async_trait]
+pub trait HttpSource: Clone + Send + Sync + 'static {
+    fn build_event(
+        &self,
+        body: Bytes,
+        header_map: HeaderMap,
+        query_parameters: HashMap<String, String>,
+        request: &RequestInfo,
+    ) -> Result<Vec<Event>, ErrorMessage>;
+
+    /// Decides whether the client may send requests at all. This runs before
+    /// authentication, decompression or `build_event`.
+    fn admit_client(
+        &self,
+        _client: &ClientInfo,
+        _header_map: &HeaderMap,
+    ) -> Result<(), ErrorMessage> {
+        Ok(())
+    }
+
+    /// The request methods accepted.
+    fn methods(&self) -> Vec<Method> {
+        vec![Method::POST]
+    }
+
+    /// The authentication for requests to a path handled by a route, which may
+    /// be `HttpSourceAuth::None` for an open route, or `None` to use the
+    /// source's own.
+    fn path_auth(&self, _path: &str) -> Option<&HttpSourceAuth> {
+        None
+    }
+
+    /// A path answered with an empty 200 response, without authentication, for
+    /// load balancer health checks.
+    fn healthcheck_path(&self) -> Option<String> {
+        None
+    }
+
+    /// Whether requests are only accepted on `path` itself, rather than on any
+    /// path beneath it.
+    fn strict_path(&self) -> bool {
+        true
+    }
+
+    /// Whether trailing slashes are removed from request paths before they are
+    /// matched, so that `/ingest/` is treated like `/ingest`.
+    fn trim_trailing_slash(&self) -> bool {
+        false
+    }
+
+    /// Whether requests are rejected with a 503 response, rather than waiting,
+    /// when the pipeline is full.
+    fn reject_when_full(&self) -> bool {
+        false
+    }
+
+    /// The media types request bodies may have, with other requests rejected
+    /// with a 415 response before their body is decoded. Any are allowed if empty.
+    fn allowed_content_types(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// How long requests in flight at shutdown are waited for before their
+    /// connections are dropped, or `None` to wait for as long as they take.
+    fn shutdown_timeout(&self) -> Option<Duration> {
+        None
+    }
+
+    /// The largest request body accepted, both as received and once decompressed.
+    fn max_body_size(&self) -> Option<usize> {
+        None
+    }
+
+    /// The smallest response body compressed with gzip for clients accepting it,
+    /// or `None` to never compress responses.
+    fn response_gzip_threshold(&self) -> Option<usize> {
+        None
+    }
+
+    /// Cross-origin settings; when set, `OPTIONS` preflight requests are answered
+    /// and `Access-Control-*` headers are added to responses.
+    fn cors(&self) -> Option<CorsConfig> {
+        None
+    }
+
+    /// Settings for recognizing retried requests by a header; when set, requests
+    /// repeating one already accepted are answered as a success without their
+    /// events being sent again.
+    fn idempotency(&self) -> Option<IdempotencyConfig> {
+        None
+    }
+
+    /// The delimiter of the lines of a request's body, when each line can be
+    /// passed to `build_event` as soon as it's received rather than once the
+    /// whole body has been, or `None` when decoding needs the whole body.
+    fn line_delimiter(&self, _request: &RequestInfo, _header_map: &HeaderMap) -> Option<u8> {
+        None
+    }
+
+    /// Builds the response returned once a request's events have been accepted.
+    fn success_response(&self, _request: &RequestInfo, _header_map: &HeaderMap) -> Response {
+        warp::reply().into_response()
+    }
+
+    /// A path accepting WebSocket connections, each of whose messages is passed
+    /// to `build_event` as a request body.
+    fn websocket_path(&self) -> Option<String> {
+        None
+    }
+
+    /// Whether `text/event-stream` requests are read as they arrive, passing
+    /// each server-sent event to `build_server_sent_event`, rather than once
+    /// the whole body has been received.
+    fn server_sent_events(&self) -> bool {
+        false
+    }
+
+    /// Builds the events for one server-sent event, by default decoding its
+    /// data as a request body.
+    fn build_server_sent_event(
+        &self,
+        event: ServerSentEvent,
+        header_map: HeaderMap,
+        query_parameters: HashMap<String, String>,
+        request: &RequestInfo,
+    ) -> Result<Vec<Event>, ErrorMessage> {
+        self.build_event(event.data, header_map, query_parameters, request)
+    }
+
+    fn run(
+        self,
+        address: HttpListenAddr,
+        path: &'static str,
+        tls: &Option<TlsConfig>,
+        auth: &Option<HttpSourceAuthConfig>,
+        out: Pipeline,
+        shutdown: ShutdownSignal,
+    ) -> crate::Result<crate::sources::Source> {
+        let tls = MaybeTlsSettings::from_config(tls, true)?;
+        let auth = HttpSourceAuth::try_from(auth.as_ref())?;
+        let cors = self.cors().map(|cors| cors.build()).transpose()?;
+        let idempotency = self
+            .idempotency()
+            .map(|idempotency| idempotency.build())
+            .transpose()?;
+        Ok(Box::pin(async move {
+            let span = crate::trace::current_span();
+            let max_body_size = self.max_body_size();
+            let healthcheck_path = self.healthcheck_path();
+            let strict_path = self.strict_path();
+            let trim_trailing_slash = self.trim_trailing_slash();
+            let reject_when_full = self.reject_when_full();
+            let shutdown_timeout = self.shutdown_timeout();
+            let response_gzip_threshold = self.response_gzip_threshold();
+            let allowed_content_types = self
+                .allowed_content_types()
+                .iter()
+                .map(|content_type| content_type.trim().to_ascii_lowercase())
+                .collect::<Vec<_>>();
+            let websocket_path = self.websocket_path();
+            let websocket = {
+                let source = self.clone();
+                let auth = auth.clone();
+                let out = out.clone();
+                let shutdown = shutdown.clone();
+                let span = span.clone();
+                warp::path::full()
+                    .and_then(move |full_path: FullPath| {
+                        let matches = websocket_path.as_deref() == Some(full_path.as_str());
+                        async move {
+                            if matches {
+                                Ok(full_path)
+                            } else {
+                                Err(warp::reject::not_found())
+                            }
+                        }
+                    })
+                    .and(warp::ws())
+                    .and(warp::header::headers_cloned())
+                    .and(warp::query::<HashMap<String, String>>())
+                    .and(warp::ext::get::<ClientInfo>())
+                    .and_then(
+                        move |full_path: FullPath,
+                              ws: Ws,
+                              headers: HeaderMap,
+                              query_parameters: HashMap<String, String>,
+                              client: ClientInfo| {
+                            let _guard = span.enter();
+                            debug!(message = "Handling WebSocket upgrade.", headers = ?headers);
+                            emit!(HTTPRequestReceived {
+                                method: Method::GET.as_str(),
+                                path: full_path.as_str(),
+                            });
+
+                            // Authentication happens once, at the handshake
+                            let auth_fields =
+                                source.admit_client(&client, &headers).and_then(|()| {
+                                    source
+                                        .path_auth(full_path.as_str())
+                                        .unwrap_or(&auth)
+                                        .is_valid(&headers, &query_parameters, &Bytes::new())
+                                });
+                            let request = RequestInfo {
+                                method: Method::GET,
+                                path: full_path.as_str().to_owned(),
+                                raw_query: None,
+                                client,
+                            };
+                            let source = source.clone();
+                            let out = out.clone();
+                            let shutdown = shutdown.clone();
+                            let span = span.clone();
+                            async move {
+                                let auth_fields = auth_fields.map_err(reject)?;
+                                Ok::<_, Rejection>(ws.on_upgrade(move |socket| {
+                                    handle_websocket(
+                                        source,
+                                        socket,
+                                        headers,
+                                        query_parameters,
+                                        request,
+                                        auth_fields,
+                                        out,
+                                        shutdown,
+                                    )
+                                    .instrument(span)
+                                }))
+                            }
+                        },
+                    )
+            };
+
+            // The path is matched before the method, so that requests to unknown
+            // paths get a 404 response rather than a 405
+            let mut path_filter: BoxedFilter<()> = warp::any().boxed();
+            if !path.is_empty() && path != "/" {
+                for s in path.split('/') {
+                    path_filter = path_filter.and(warp::path(s)).boxed();
+                }
+            }
+            if strict_path {
+                path_filter = path_filter.and(warp::path::end()).boxed();
+            }
+            let methods = self.methods();
+            let filter: BoxedFilter<(Method,)> = path_filter
+                .and(warp::method())
+                .and_then(move |method: Method| {
+                    let allowed = methods.contains(&method);
+                    async move {
+                        if allowed {
+                            Ok(method)
+                        } else {
+                            Err(warp::reject::custom(ErrorMessage::new(
+                                StatusCode::METHOD_NOT_ALLOWED,
+                                format!("Method {} is not allowed", method),
+                            )))
+                        }
+                    }
+                })
+                .boxed();
+            let server_sent_events = {
+                let source = self.clone();
+                let auth = auth.clone();
+                let out = out.clone();
+                let shutdown = shutdown.clone();
+                let span = span.clone();
+                let enabled = self.server_sent_events();
+                filter
+                    .clone()
+                    .and(warp::header::optional::<String>("content-type"))
+                    .and_then(
+                        move |method: Method, content_type: Option<String>| async move {
+                            let is_stream = content_type.map_or(false, |content_type| {
+                                content_type.trim().starts_with("text/event-stream")
+                            });
+                            if enabled && is_stream {
+                                Ok(method)
+                            } else {
+                                Err(warp::reject::not_found())
+                            }
+                        },
+                    )
+                    .and(warp::path::full())
+                    .and(warp::header::optional::<String>("content-encoding"))
+                    .and(warp::header::headers_cloned())
+                    .and(warp::body::stream().map(boxed_body))
+                    .and(warp::query::<HashMap<String, String>>())
+                    .and(
+                        warp::query::raw()
+                            .map(Some)
+                            .or(warp::any().map(|| None))
+                            .unify(),
+                    )
+                    .and(warp::ext::get::<ClientInfo>())
+                    .and_then(
+                        move |method: Method,
+                              full_path: FullPath,
+                              encoding_header: Option<String>,
+                              headers: HeaderMap,
+                              body: BoxStream<'static, Result<Bytes, warp::Error>>,
+                              query_parameters: HashMap<String, String>,
+                              raw_query: Option<String>,
+                              client: ClientInfo| {
+                            let _guard = span.enter();
+                            debug!(message = "Handling HTTP event stream.", headers = ?headers);
+                            emit!(HTTPRequestReceived {
+                                method: method.as_str(),
+                                path: full_path.as_str(),
+                            });
+
+                            let request = RequestInfo {
+                                method,
+                                path: full_path.as_str().to_owned(),
+                                raw_query,
+                                client,
+                            };
+                            // Authentication happens once, before any of the body is read
+                            let auth_fields = source
+                                .admit_client(&request.client, &headers)
+                                .and_then(|()| {
+                                    source.path_auth(&request.path).unwrap_or(&auth).is_valid(
+                                        &headers,
+                                        &query_parameters,
+                                        &Bytes::new(),
+                                    )
+                                })
+                                .and_then(|auth_fields| match encoding_header.as_deref() {
+                                    None | Some("identity") => Ok(auth_fields),
+                                    Some(encoding) => Err(ErrorMessage::new(
+                                        StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                                        format!(
+                                            "Unsupported encoding {} for event streams",
+                                            encoding
+                                        ),
+                                    )),
+                                });
+                            // Streams can stay open indefinitely, so end once Vector shuts down
+                            let body = body.take_until(shutdown.clone()).boxed();
+                            let source = source.clone();
+                            let mut out = out.clone();
+                            async move {
+                                let auth_fields = auth_fields.map_err(reject)?;
+                                let response = source.success_response(&request, &headers);
+                                read_server_sent_events(
+                                    &source,
+                                    body,
+                                    headers,
+                                    query_parameters,
+                                    &request,
+                                    &auth_fields,
+                                    max_body_size,
+                                    &mut out,
+                                )
+                                .await
+                                .map_err(reject)?;
+                                Ok::<_, Rejection>(response)
+                            }
+                            .instrument(span.clone())
+                        },
+                    )
+            };
+            // Shared by every request, rather than cloned for each
+            let source = Arc::new(self);
+            let auth = Arc::new(auth);
+            let svc = filter
+                .and(warp::path::full())
+                .and(warp::header::optional::<String>("content-encoding"))
+                .and(warp::header::headers_cloned())
+                .and(warp::body::stream().map(boxed_body))
+                .and(warp::query::<HashMap<String, String>>())
+                .and(
+                    warp::query::raw()
+                        .map(Some)
+                        .or(warp::any().map(|| None))
+                        .unify(),
+                )
+                .and(warp::ext::get::<ClientInfo>())
+                .and_then(
+                    move |method: Method,
+                          full_path: FullPath,
+                          encoding_header: Option<String>,
+                          headers: HeaderMap,
+                          body: BoxStream<'static, Result<Bytes, warp::Error>>,
+                          query_parameters: HashMap<String, String>,
+                          raw_query: Option<String>,
+                          client: ClientInfo| {
+                        let _guard=span.enter();
+                        debug!(message = "Handling HTTP request.", headers = ?headers);
+                        emit!(HTTPRequestReceived {
+                            method: method.as_str(),
+                            path: full_path.as_str(),
+                        });
+
+                        let mut out = out.clone();
+                        let request = RequestInfo {
+                            method,
+                            path: full_path.as_str().to_owned(),
+                            raw_query,
+                            client,
+                        };
+                        let source = Arc::clone(&source);
+                        let auth = Arc::clone(&auth);
+                        let allowed_content_types = allowed_content_types.clone();
+                        let idempotency = idempotency.clone();
+                        async move {
+                            let auth = source.path_auth(&request.path).unwrap_or(&*auth);
+                            // Everything checked before the body is decoded
+                            let check = |body: &Bytes| {
+                                source
+                                    .admit_client(&request.client, &headers)
+                                    .and_then(|()| auth.is_valid(&headers, &query_parameters, body))
+                                    .and_then(|auth_fields| {
+                                        check_content_type(&allowed_content_types, &headers)?;
+                                        let response = source.success_response(&request, &headers);
+                                        let key =
+                                            idempotency.as_ref().and_then(|cache| cache.key(&headers));
+                                        if let (Some(cache), Some(key)) = (&idempotency, &key) {
+                                            if cache.contains(key) {
+                                                debug!(message = "Skipping repeated request.", idempotency_key = %key);
+                                                return Ok((auth_fields, response, None, true));
+                                            }
+                                        }
+                                        Ok((auth_fields, response, key, false))
+                                    })
+                            };
+                            // Compressed bodies and signed ones have to be read whole
+                            let delimiter = match encoding_header.as_deref() {
+                                None | Some("identity") if !auth.needs_body() => {
+                                    source.line_delimiter(&request, &headers)
+                                }
+                                _ => None,
+                            };
+
+                            // The decompressed body size, once known
+                            let mut body_size = None;
+                            // The events already sent as the body was read
+                            let mut sent = 0;
+                            let events = match delimiter {
+                                Some(delimiter) => {
+                                    let checked = check(&Bytes::new());
+                                    let auth_fields = match &checked {
+                                        Ok((auth_fields, _, _, false)) => Some(auth_fields.as_slice()),
+                                        _ => None,
+                                    };
+                                    let (lines_sent, body_len) = read_lines(
+                                        &*source,
+                                        body,
+                                        delimiter,
+                                        max_body_size,
+                                        auth_fields,
+                                        &headers,
+                                        &query_parameters,
+                                        &request,
+                                        &mut out,
+                                    )
+                                    .await?;
+                                    checked.and_then(|(_, response, key, repeated)| {
+                                        if repeated {
+                                            return Ok((Vec::new(), 0, response, key, true));
+                                        }
+                                        body_size = Some(body_len);
+                                        sent = lines_sent?;
+                                        Ok((Vec::new(), body_len, response, key, false))
+                                    })
+                                }
+                                None => {
+                                    let body = read_body(body, max_body_size).await?;
+                                    check(&body).and_then(|(auth_fields, response, key, repeated)| {
+                                        if repeated {
+                                            return Ok((Vec::new(), 0, response, key, true));
+                                        }
+                                        let body = decode(&encoding_header, body, max_body_size)?;
+                                        let body_len = body.len();
+                                        body_size = Some(body_len);
+                                        let events = source.build_event(
+                                            body,
+                                            headers,
+                                            query_parameters,
+                                            &request,
+                                        )?;
+                                        let events = with_auth_fields(events, &auth_fields);
+                                        Ok((events, body_len, response, key, false))
+                                    })
+                                }
+                            };
+
+                            match events {
+                                Ok((_, _, response, _, true)) => Ok(response),
+                                Ok((events, body_size, response, key, false)) => {
+                                    emit!(HTTPEventsReceived {
+                                        events_count: sent + events.len(),
+                                        byte_size: body_size,
+                                    });
+                                    if !forward_events(&mut out, events, reject_when_full).await? {
+                                        emit!(HTTPRequestBodySize {
+                                            byte_size: body_size,
+                                            status: StatusCode::SERVICE_UNAVAILABLE.as_u16(),
+                                        });
+                                        return Err(reject(pipeline_full()));
+                                    }
+                                    // Only remembered once accepted, so failed requests can be retried
+                                    if let (Some(cache), Some(key)) = (idempotency, key) {
+                                        cache.insert(key);
+                                    }
+                                    emit!(HTTPRequestBodySize {
+                                        byte_size: body_size,
+                                        status: response.status().as_u16(),
+                                    });
+                                    Ok(response)
+                                }
+                                Err(error) => {
+                                    emit!(HTTPBadRequest {
+                                        error_code: error.code,
+                                        error_message: error.message.as_str(),
+                                    });
+                                    if let Some(byte_size) = body_size {
+                                        emit!(HTTPRequestBodySize {
+                                            byte_size,
+                                            status: error.code,
+                                        });
+                                    }
+                                    Err(warp::reject::custom(error))
+                                }
+                            }
+                        }
+                        .instrument(span.clone())
+                    },
+                );
+
+            let healthcheck = warp::path::full().and_then(move |full_path: FullPath| {
+                let matches = healthcheck_path.as_deref() == Some(full_path.as_str());
+                async move {
+                    if matches {
+                        Ok(warp::reply())
+                    } else {
+                        Err(warp::reject::not_found())
+                    }
+                }
+            });
+            let ping = warp::path("ping").and(warp::get()).map(|| "pong");
+            let routes = healthcheck
+                .or(websocket)
+                .or(server_sent_events)
+                .or(svc)
+                .or(ping)
+                .recover(|r: Rejection| async move {
+                    if let Some(e_msg) = r.find::<ErrorMessage>() {
+                        Ok(e_msg.response())
+                    } else {
+                        //other internal error - will return 500 internal server error
+                        Err(r)
+                    }
+                })
+                .map(Reply::into_response);
+            // Preflight requests are answered by the CORS filter itself and never
+            // reach the routes above.
+            let routes: BoxedFilter<(Response,)> = match cors {
+                Some(cors) => routes.with(cors).map(Reply::into_response).boxed(),
+                None => routes.boxed(),
+            };
+            let routes: BoxedFilter<(Response,)> = match response_gzip_threshold {
+                Some(threshold) => warp::header::optional::<String>("accept-encoding")
+                    .and(routes)
+                    .and_then(move |accept_encoding: Option<String>, response| {
+                        gzip_response(response, accept_encoding, threshold)
+                    })
+                    .boxed(),
+                None => routes,
+            };
+
+            info!(message = "Building HTTP server.", address = %address);
+
+            // Connection details aren't visible to warp filters, so they are
+            // passed along as a request extension.
+            let routes = warp::service(routes);
+            // Set once shutdown starts, after which no new connections are
+            // accepted and requests still arriving on open ones are turned away.
+            let draining = Arc::new(AtomicBool::new(false));
+            let drain = {
+                let draining = Arc::clone(&draining);
+                shutdown
+                    .clone()
+                    .map(move |_| draining.store(true, Ordering::Relaxed))
+            };
+            match address {
+                HttpListenAddr::SocketAddr(address) => {
+                    let new_service =
+                        make_service_fn(move |conn: &MaybeTlsIncomingStream<TcpStream>| {
+                            let remote_addr = conn.peer_addr();
+                            let certificate = conn.peer_certificate();
+                            let server_name = conn.server_name();
+                            let mut routes = routes.clone();
+                            let draining = Arc::clone(&draining);
+
+                            async move {
+                                Ok::<_, Infallible>(service_fn(
+                                    move |mut req: hyper::Request<hyper::Body>| {
+                                        let client =
+                                            ClientInfo::new(remote_addr, certificate.get())
+                                                .with_server_name(server_name.get());
+                                        prepare_request(&mut req, client, trim_trailing_slash);
+                                        call_unless_draining(&mut routes, req, &draining)
+                                    },
+                                ))
+                            }
+                        });
+
+                    let listener = tls.bind(&address).await.unwrap();
+                    let server = Server::builder(hyper::server::accept::from_stream(
+                        listener.accept_stream(),
+                    ))
+                    .serve(new_service)
+                    .with_graceful_shutdown(drain);
+                    wait_for_drain(server, shutdown.clone(), shutdown_timeout).await;
+                }
+                #[cfg(unix)]
+                HttpListenAddr::UnixPath(path) => {
+                    // Unix socket peers have no address or certificate
+                    let new_service = make_service_fn(move |_: &UnixStream| {
+                        let mut routes = routes.clone();
+                        let draining = Arc::clone(&draining);
+
+                        async move {
+                            Ok::<_, Infallible>(service_fn(
+                                move |mut req: hyper::Request<hyper::Body>| {
+                                    prepare_request(
+                                        &mut req,
+                                        ClientInfo::default(),
+                                        trim_trailing_slash,
+                                    );
+                                    call_unless_draining(&mut routes, req, &draining)
+                                },
+                            ))
+                        }
+                    });
+
+                    let mut listener = UnixListener::bind(&path).map_err(|error| {
+                        error!(message = "Failed to bind to listener socket.", ?path, %error)
+                    })?;
+                    let server =
+                        Server::builder(hyper::server::accept::from_stream(listener.incoming()))
+                            .serve(new_service)
+                            .with_graceful_shutdown(drain);
+                    wait_for_drain(server, shutdown.clone(), shutdown_timeout).await;
+                    if let Err(error) = std::fs::remove_file(&path) {
+                        warn!(message = "Failed to remove listener socket.", ?path, %error);
+                    }
+                }
+            }
+            // We need to drop the last copy of ShutdownSignalToken only after server has shut down.
+            drop(shutdown);
+            Ok(())
+        }))
+    }
+}
+
+/// Rejects requests whose `Content-Type`, ignoring parameters such as
+/// `charset`, isn't one of `allowed`, unless `allowed` is empty.
+fn check_content_type(allowed: &[String], headers: &HeaderMap) -> Result<(), ErrorMessage> {
+    if allowed.is_empty() {
+        return Ok(());
+    }
+    let content_type = headers
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(';').next())
+        .map(|media_type| media_type.trim().to_ascii_lowercase());
+    match content_type {
+        Some(content_type) if allowed.contains(&content_type) => Ok(()),
+        Some(content_type) => Err(ErrorMessage::new(
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            format!("Unsupported content type {}", content_type),
+        )),
+        None => Err(ErrorMessage::new(
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            "Missing content type".to_owned(),
+        )),
+    }
+}
+
+/// Whether an `Accept-Encoding` header value accepts gzip, which it doesn't
+/// when gzip is absent or given a quality of zero.
+fn accepts_gzip(accept_encoding: &str) -> bool {
+    accept_encoding.split(',').any(|coding| {
+        let mut params = coding.split(';').map(str::trim);
+        let name = params.next().unwrap_or_default();
+        let refused = params.any(|param| {
+            param
+                .strip_prefix("q=")
+                .and_then(|quality| quality.parse::<f32>().ok())
+                .map_or(false, |quality| quality <= 0.0)
+        });
+        name.eq_ignore_ascii_case("gzip") && !refused
+    })
+}
+
+/// Compresses the response body with gzip when the client accepts it and the
+/// body is at least `threshold` bytes long. Protocol upgrades and responses
+/// already carrying a `Content-Encoding` are left untouched.
+async fn gzip_response(
+    response: Response,
+    accept_encoding: Option<String>,
+    threshold: usize,
+) -> Result<Response, Rejection> {
+    if !accept_encoding.as_deref().map_or(false, accepts_gzip)
+        || response.status() == StatusCode::SWITCHING_PROTOCOLS
+        || response.headers().contains_key(CONTENT_ENCODING)
+    {
+        return Ok(response);
+    }
+    let (mut parts, body) = response.into_parts();
+    let body = hyper::body::to_bytes(body)
+        .await
+        .map_err(|_| warp::reject::reject())?;
+    parts
+        .headers
+        .append(VARY, HeaderValue::from_static("accept-encoding"));
+    if body.is_empty() || body.len() < threshold {
+        return Ok(Response::from_parts(parts, body.into()));
+    }
+    let mut encoder = GzEncoder::new(Vec::with_capacity(body.len()), Compression::default());
+    let compressed = encoder
+        .write_all(&body)
+        .and_then(|_| encoder.finish())
+        .map_err(|_| warp::reject::reject())?;
+    parts
+        .headers
+        .insert(CONTENT_ENCODING, HeaderValue::from_static("gzip"));
+    parts.headers.remove(CONTENT_LENGTH);
+    Ok(Response::from_parts(parts, compressed.into()))
+}
+
+/// Answers requests arriving once shutdown has started with a 503 response,
+/// asking the client to close the connection, and passes the rest to the routes.
+fn call_unless_draining<S>(
+    routes: &mut S,
+    req: hyper::Request<hyper::Body>,
+    draining: &AtomicBool,
+) -> Either<future::Ready<Result<Response, S::Error>>, S::Future>
+where
+    S: Service<hyper::Request<hyper::Body>, Response = Response>,
+{
+    if draining.load(Ordering::Relaxed) {
+        let mut response =
+            ErrorMessage::new(StatusCode::SERVICE_UNAVAILABLE, "Shutting down".to_owned())
+                .with_header(RETRY_AFTER, HeaderValue::from(1))
+                .response();
+        response
+            .headers_mut()
+            .insert(CONNECTION, HeaderValue::from_static("close"));
+        Either::Left(future::ok(response))
+    } else {
+        Either::Right(routes.call(req))
+    }
+}
+
+/// Runs the server until it has shut down, which happens once the requests in
+/// flight when `shutdown` fires complete, or `timeout` after it fires at most.
+async fn wait_for_drain<F: Future>(server: F, shutdown: ShutdownSignal, timeout: Option<Duration>) {
+    let timeout = match timeout {
+        Some(timeout) => timeout,
+        None => {
+            server.await;
+            return;
+        }
+    };
+    futures::pin_mut!(server, shutdown);
+    if let Either::Left(_) = future::select(&mut server, shutdown).await {
+        return;
+    }
+    if tokio::time::timeout(timeout, server).await.is_err() {
+        warn!(
+            message = "Timed out waiting for in-flight requests; closing their connections.",
+            ?timeout
+        );
+    }
+}
+
+/// Passes the connection details along to the routes as a request extension,
+/// and removes trailing slashes from the path when configured to.
+fn prepare_request(
+    req: &mut hyper::Request<hyper::Body>,
+    client: ClientInfo,
+    trim_trailing_slash: bool,
+) {
+    req.extensions_mut().insert(client);
+    if trim_trailing_slash {
+        if let Some(uri) = without_trailing_slash(req.uri()) {
+            *req.uri_mut() = uri;
+        }
+    }
+}
+
+/// Returns `uri` with the trailing slashes of its path removed, if it has any.
+fn without_trailing_slash(uri: &Uri) -> Option<Uri> {
+    let path = uri.path();
+    if path.len() <= 1 || !path.ends_with('/') {
+        return None;
+    }
+    let path = match path.trim_end_matches('/') {
+        "" => "/",
+        path => path,
+    };
+    let path_and_query = match uri.query() {
+        Some(query) => format!("{}?{}", path, query),
+        None => path.to_owned(),
+    };
+    let mut parts = uri.clone().into_parts();
+    parts.path_and_query = Some(path_and_query.parse().ok()?);
+    Uri::from_parts(parts).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tls::TEST_PEM_CRT_PATH;
+
+    #[test]
+    fn trims_trailing_slash() {
+        let trim =
+            |uri: &str| without_trailing_slash(&uri.parse().unwrap()).map(|uri| uri.to_string());
+
+        assert_eq!(trim("/ingest/").as_deref(), Some("/ingest"));
+        assert_eq!(
+            trim("/ingest//?env=prod").as_deref(),
+            Some("/ingest?env=prod")
+        );
+        assert_eq!(trim("//").as_deref(), Some("/"));
+        assert_eq!(trim("/ingest"), None);
+        assert_eq!(trim("/"), None);
+    }
+
+    #[test]
+    fn checks_content_type() {
+        let allowed = vec!["application/json".to_owned()];
+        let headers = |content_type: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(CONTENT_TYPE, content_type.parse().unwrap());
+            headers
+        };
+
+        assert!(check_content_type(&allowed, &headers("application/json")).is_ok());
+        assert!(check_content_type(&allowed, &headers("Application/JSON; charset=utf-8")).is_ok());
+        assert!(check_content_type(&allowed, &headers("text/plain")).is_err());
+        assert!(check_content_type(&allowed, &HeaderMap::new()).is_err());
+        assert!(check_content_type(&[], &HeaderMap::new()).is_ok());
+    }
+
+    #[test]
+    fn checks_accept_encoding() {
+        assert!(accepts_gzip("gzip"));
+        assert!(accepts_gzip("deflate, GZIP;q=0.5"));
+        assert!(!accepts_gzip("deflate, br"));
+        assert!(!accepts_gzip("gzip;q=0"));
+        assert!(!accepts_gzip(""));
+    }
+
+    #[tokio::test]
+    async fn drains_within_timeout() {
+        let (trigger, shutdown, _) = ShutdownSignal::new_wired();
+        trigger.cancel();
+        // A request that never completes
+        let server = future::pending::<()>();
+
+        tokio::time::timeout(
+            Duration::from_secs(5),
+            wait_for_drain(server, shutdown, Some(Duration::from_millis(10))),
+        )
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn rejects_requests_while_draining() {
+        let mut routes = service_fn(|_: hyper::Request<hyper::Body>| {
+            future::ok::<_, Infallible>(Response::default())
+        });
+        let draining = AtomicBool::new(false);
+
+        let response = call_unless_draining(&mut routes, Default::default(), &draining)
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        draining.store(true, Ordering::Relaxed);
+        let response = call_unless_draining(&mut routes, Default::default(), &draining)
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(response.headers()[CONNECTION], "close");
+    }
+
+    #[test]
+    fn client_info_from_certificate() {
+        let certificate = X509::from_pem(&std::fs::read(TEST_PEM_CRT_PATH).unwrap()).unwrap();
+        let client = ClientInfo::new("127.0.0.1:9000".parse().unwrap(), Some(certificate));
+
+        assert_eq!(client.certificate_cn.as_deref(), Some("localhost"));
+        assert!(client.certificate_sans.is_empty());
+    }
+
+    #[test]
+    fn client_info_without_certificate() {
+        let client = ClientInfo::new("127.0.0.1:9000".parse().unwrap(), None);
+
+        assert_eq!(client.certificate_cn, None);
+        assert!(client.certificate_sans.is_empty());
+    }
+}
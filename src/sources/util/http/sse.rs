@@ -0,0 +1,136 @@
+use bytes::{Buf, Bytes, BytesMut};
+
+/// An event of a `text/event-stream` body.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ServerSentEvent {
+    /// The `data` lines, joined with newlines.
+    pub data: Bytes,
+    pub event: Option<String>,
+    pub id: Option<String>,
+    /// Lines starting with `:`, without it.
+    pub comments: Vec<String>,
+}
+
+/// Removes the complete events at the start of `buffer`, returning the first one
+/// with data. Events without data aren't dispatched, as in browsers.
+pub(super) fn next_event(buffer: &mut BytesMut) -> Option<ServerSentEvent> {
+    loop {
+        let (event, len) = parse_event(buffer)?;
+        buffer.advance(len);
+        if let Some(event) = event {
+            return Some(event);
+        }
+    }
+}
+
+/// Parses the event at the start of `buffer`, returning it along with the
+/// number of bytes it took up, or `None` if its blank line hasn't arrived yet.
+fn parse_event(buffer: &[u8]) -> Option<(Option<ServerSentEvent>, usize)> {
+    let mut event = ServerSentEvent::default();
+    let mut data: Option<Vec<u8>> = None;
+    let mut start = 0;
+    loop {
+        let end = start
+            + buffer[start..]
+                .iter()
+                .position(|b| *b == b'\n' || *b == b'\r')?;
+        // Lines end with `\r\n`, `\n` or `\r`, so a trailing `\r` needs the next byte
+        let next = match (buffer[end], buffer.get(end + 1)) {
+            (b'\r', Some(b'\n')) => end + 2,
+            (b'\r', None) => return None,
+            _ => end + 1,
+        };
+        let line = &buffer[start..end];
+        start = next;
+
+        if line.is_empty() {
+            let event = data.map(|mut data| {
+                data.pop();
+                ServerSentEvent {
+                    data: data.into(),
+                    ..event
+                }
+            });
+            return Some((event, start));
+        }
+
+        let (field, value) = match line.iter().position(|b| *b == b':') {
+            Some(colon) => {
+                let value = &line[colon + 1..];
+                match value.first() {
+                    Some(b' ') => (&line[..colon], &value[1..]),
+                    _ => (&line[..colon], value),
+                }
+            }
+            None => (line, &[][..]),
+        };
+        match field {
+            b"" => event
+                .comments
+                .push(String::from_utf8_lossy(value).into_owned()),
+            b"data" => {
+                let data = data.get_or_insert_with(Vec::new);
+                data.extend_from_slice(value);
+                data.push(b'\n');
+            }
+            b"event" => event.event = Some(String::from_utf8_lossy(value).into_owned()),
+            b"id" => event.id = Some(String::from_utf8_lossy(value).into_owned()),
+            // Other fields, such as `retry`, are meaningless here
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn events(body: &str) -> (Vec<ServerSentEvent>, String) {
+        let mut buffer = BytesMut::from(body);
+        let events = std::iter::from_fn(|| next_event(&mut buffer)).collect();
+        (events, String::from_utf8(buffer.to_vec()).unwrap())
+    }
+
+    #[test]
+    fn parses_events() {
+        let (events, rest) = events(
+            ": hello\nevent: update\nid: 1\ndata: {\"a\":1}\n\ndata:one\r\ndata\r\ndata:two\r\n\r\ndata: partial",
+        );
+        assert_eq!(
+            events,
+            vec![
+                ServerSentEvent {
+                    data: Bytes::from("{\"a\":1}"),
+                    event: Some("update".to_owned()),
+                    id: Some("1".to_owned()),
+                    comments: vec!["hello".to_owned()],
+                },
+                ServerSentEvent {
+                    data: Bytes::from("one\n\ntwo"),
+                    ..Default::default()
+                },
+            ]
+        );
+        assert_eq!(rest, "data: partial");
+    }
+
+    #[test]
+    fn skips_events_without_data() {
+        let (events, rest) = events(": keepalive\n\nid: 2\n\ndata\n\n");
+        assert_eq!(
+            events,
+            vec![ServerSentEvent {
+                data: Bytes::new(),
+                ..Default::default()
+            }]
+        );
+        assert!(rest.is_empty());
+    }
+
+    #[test]
+    fn waits_for_line_end() {
+        let (events, rest) = events("data: x\r\n\r");
+        assert!(events.is_empty());
+        assert_eq!(rest, "data: x\r\n\r");
+    }
+}
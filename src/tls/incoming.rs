@@ -8,13 +8,17 @@ use super::{
 use crate::tcp::TcpKeepaliveConfig;
 use bytes::{Buf, BufMut};
 use futures::{future::BoxFuture, stream, FutureExt, Stream};
-use openssl::ssl::{SslAcceptor, SslMethod};
+use openssl::{
+    ssl::{NameType, SslAcceptor, SslMethod},
+    x509::X509,
+};
 use snafu::ResultExt;
 use std::{
     future::Future,
     mem::MaybeUninit,
     net::SocketAddr,
     pin::Pin,
+    sync::{Arc, Mutex},
     task::{Context, Poll},
 };
 use tokio::{
@@ -106,6 +110,43 @@ pub struct MaybeTlsIncomingStream<S> {
     // of MaybeTlsIncomingStream want access to the peer address while
     // still handshaking, so we have to cache it here.
     peer_addr: SocketAddr,
+    peer_certificate: PeerCertificate,
+    server_name: ServerName,
+}
+
+/// Shared handle to the certificate presented by the client, which only
+/// becomes available once the TLS handshake has completed.
+#[derive(Clone, Debug, Default)]
+pub struct PeerCertificate(Arc<Mutex<Option<X509>>>);
+
+impl PeerCertificate {
+    #[cfg_attr(not(feature = "sources-utils-http"), allow(dead_code))]
+    pub fn get(&self) -> Option<X509> {
+        self.0.lock().unwrap().clone()
+    }
+
+    fn set_from<S>(&self, stream: &SslStream<S>) {
+        *self.0.lock().unwrap() = stream.ssl().peer_certificate();
+    }
+}
+
+/// Shared handle to the server name the client asked for with SNI, which only
+/// becomes available once the TLS handshake has completed.
+#[derive(Clone, Debug, Default)]
+pub struct ServerName(Arc<Mutex<Option<String>>>);
+
+impl ServerName {
+    #[cfg_attr(not(feature = "sources-utils-http"), allow(dead_code))]
+    pub fn get(&self) -> Option<String> {
+        self.0.lock().unwrap().clone()
+    }
+
+    fn set_from<S>(&self, stream: &SslStream<S>) {
+        *self.0.lock().unwrap() = stream
+            .ssl()
+            .servername(NameType::HOST_NAME)
+            .map(str::to_owned);
+    }
 }
 
 enum StreamState<S> {
@@ -120,6 +161,16 @@ impl<S> MaybeTlsIncomingStream<S> {
         self.peer_addr
     }
 
+    #[cfg_attr(not(feature = "sources-utils-http"), allow(dead_code))]
+    pub fn peer_certificate(&self) -> PeerCertificate {
+        self.peer_certificate.clone()
+    }
+
+    #[cfg_attr(not(feature = "sources-utils-http"), allow(dead_code))]
+    pub fn server_name(&self) -> ServerName {
+        self.server_name.clone()
+    }
+
     /// None if connection still hasn't been established.
     #[cfg(any(
         feature = "listenfd",
@@ -152,7 +203,12 @@ impl MaybeTlsIncomingStream<TcpStream> {
             ),
             None => StreamState::Accepted(MaybeTlsStream::Raw(stream)),
         };
-        Self { peer_addr, state }
+        Self {
+            peer_addr,
+            peer_certificate: PeerCertificate::default(),
+            server_name: ServerName::default(),
+            state,
+        }
     }
 
     // Explicit handshake method
@@ -160,6 +216,8 @@ impl MaybeTlsIncomingStream<TcpStream> {
     pub(crate) async fn handshake(&mut self) -> crate::tls::Result<()> {
         if let StreamState::Accepting(fut) = &mut self.state {
             let stream = fut.await.context(Handshake)?;
+            self.peer_certificate.set_from(&stream);
+            self.server_name.set_from(&stream);
             self.state = StreamState::Accepted(MaybeTlsStream::Tls(stream));
         }
 
@@ -204,6 +262,8 @@ impl MaybeTlsIncomingStream<TcpStream> {
                 StreamState::Accepted(stream) => poll_fn(Pin::new(stream), cx),
                 StreamState::Accepting(fut) => match futures::ready!(fut.as_mut().poll(cx)) {
                     Ok(stream) => {
+                        this.peer_certificate.set_from(&stream);
+                        this.server_name.set_from(&stream);
                         this.state = StreamState::Accepted(MaybeTlsStream::Tls(stream));
                         continue;
                     }